@@ -1,20 +1,40 @@
 use serde::{ Deserialize, Serialize };
 use tracing::{ warn, error, debug, info }; // Add 'info' to the tracing import
 use serde_json;
-use std::collections::HashMap;
+use std::collections::{ BTreeSet, HashMap };
 use std::fs;
-use std::path::PathBuf;
+use std::path::{ Path, PathBuf };
 use uuid::Uuid;
 use async_openai::types::ChatCompletionRequestUserMessageArgs;
 use async_openai::types::ChatCompletionRequestSystemMessageArgs;
 use async_openai::types::ChatCompletionRequestAssistantMessageArgs;
-// Removed unused tool choice imports since tools are now in system message
+use async_openai::types::{
+    ChatCompletionMessageToolCall,
+    ChatCompletionRequestToolMessageArgs,
+    ChatCompletionToolChoiceOption,
+    ChatCompletionToolType,
+    FunctionCall,
+};
 use async_openai::{ types::CreateChatCompletionRequestArgs, Client };
 use async_openai::{ config::OpenAIConfig };
+use async_openai::types::ChatCompletionTool;
 use futures::StreamExt;
 use tauri::{ AppHandle, Emitter };
 use crate::mcp;
 
+// Default minimum cosine similarity for a chunk to be used as RAG context
+const DEFAULT_RAG_MIN_SCORE: f32 = 0.2;
+
+// Upper bound on reranked documents injected as context, whatever rag_limit asks for
+const MAX_RAG_CONTEXT_DOCS: usize = 10;
+
+// Characters of each context document sent to the model by default, and the most allowed
+const DEFAULT_RAG_CONTEXT_CHARS: usize = 500;
+const MAX_RAG_CONTEXT_CHARS: usize = 4000;
+
+// Tool call / continuation rounds allowed per chat request before giving up
+const DEFAULT_MAX_TOOL_ITERATIONS: u32 = 5;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub id: String,
@@ -33,6 +53,62 @@ pub struct ChatSession {
     pub updated_at: i64,
     pub model_id: Option<String>,
     pub messages: Vec<ChatMessage>,
+    /// Pinned sessions can be kept when old sessions are pruned
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// A document chunk that was injected into the RAG context, used for citations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagSource {
+    pub document_id: String,
+    pub title: String,
+    pub file_path: String,
+    pub score: f32,
+}
+
+/// How MCP tools are offered to the model
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolMode {
+    /// Describe tools in the system prompt and scrape `<tool_call>` XML from the output
+    #[default]
+    XmlPrompt,
+    /// Send tools in the request `tools` array and read `delta.tool_calls` from the stream
+    NativeTools,
+}
+
+// A native tool call assembled from streamed fragments
+#[derive(Debug, Default)]
+struct NativeToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+// Text streamed by a continuation request plus any native tool calls it asked for
+#[derive(Debug, Default)]
+struct ContinuationStep {
+    text: String,
+    tool_calls: Vec<NativeToolCall>,
+}
+
+/// Size of a stored conversation, for context and cost monitoring
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionStats {
+    pub message_count: usize,
+    pub total_characters: usize,
+    pub approximate_tokens: usize,
+    pub by_role: HashMap<String, RoleStats>,
+    /// Average over assistant messages that recorded a speed, None if none did
+    pub average_tokens_per_second: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RoleStats {
+    pub message_count: usize,
+    pub total_characters: usize,
+    pub approximate_tokens: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,56 +126,212 @@ impl Default for ChatSessionsStorage {
     }
 }
 
-fn get_chat_sessions_path() -> Result<PathBuf, String> {
-    let home_dir = std::env
-        ::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map_err(|_| "Failed to get user home directory".to_string())?;
+// Sessions live in .sparrow/sessions, one <id>.json file each plus an index of ids, so adding
+// a message rewrites only that session rather than every stored conversation
+const SESSION_INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionIndex {
+    session_ids: BTreeSet<String>,
+    active_session_id: Option<String>,
+}
+
+// Write to a sibling file and rename over the old one, so a crash mid-write can't leave a
+// truncated file behind
+fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let partial_path = path.with_extension("json.partial");
+
+    let contents = serde_json
+        ::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+
+    fs
+        ::write(&partial_path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", partial_path.display(), e))?;
+    fs
+        ::rename(&partial_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+fn session_file_path(dir: &Path, session_id: &str) -> Result<PathBuf, String> {
+    // Session ids are UUIDs; anything else could escape the directory
+    if Uuid::parse_str(session_id).is_err() {
+        return Err(format!("Invalid chat session id: {}", session_id));
+    }
+    Ok(dir.join(format!("{}.json", session_id)))
+}
+
+fn read_session_file(path: &Path) -> Result<ChatSession, String> {
+    let contents = fs
+        ::read_to_string(path)
+        .map_err(|e| format!("Failed to read chat session file: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse chat session: {}", e))
+}
+
+fn read_session_index(sessions_dir: &Path) -> Result<SessionIndex, String> {
+    let path = sessions_dir.join(SESSION_INDEX_FILE);
+    if !path.exists() {
+        return Ok(SessionIndex::default());
+    }
+
+    let contents = fs
+        ::read_to_string(&path)
+        .map_err(|e| format!("Failed to read chat session index: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse chat session index: {}", e))
+}
+
+// Split the chat_sessions.json written by earlier versions into per-session files. The old
+// file is kept as chat_sessions.json.migrated. Returns how many sessions were moved
+fn migrate_legacy_sessions(legacy_path: &Path, sessions_dir: &Path) -> Result<usize, String> {
+    let contents = fs
+        ::read_to_string(legacy_path)
+        .map_err(|e| format!("Failed to read chat sessions file: {}", e))?;
+    let legacy = serde_json
+        ::from_str::<ChatSessionsStorage>(&contents)
+        .map_err(|e| format!("Failed to parse chat sessions: {}", e))?;
+
+    let mut index = SessionIndex::default();
+    for session in legacy.sessions.values() {
+        match session_file_path(sessions_dir, &session.id) {
+            Ok(path) => {
+                write_json_file(&path, session)?;
+                index.session_ids.insert(session.id.clone());
+            }
+            Err(e) => warn!(error = %e, "Skipping chat session during migration"),
+        }
+    }
+    index.active_session_id = legacy.active_session_id.filter(|id| index.session_ids.contains(id));
+
+    // The index is written last, its presence marks the migration as done
+    write_json_file(&sessions_dir.join(SESSION_INDEX_FILE), &index)?;
+    if let Err(e) = fs::rename(legacy_path, legacy_path.with_extension("json.migrated")) {
+        warn!(error = %e, "Failed to rename migrated chat sessions file");
+    }
+
+    info!(session_count = index.session_ids.len(), "Migrated chat sessions to per-session files");
+    Ok(index.session_ids.len())
+}
 
-    let sparrow_dir = PathBuf::from(home_dir).join(".sparrow");
+fn get_sessions_dir() -> Result<PathBuf, String> {
+    let sparrow_dir = crate::paths::sparrow_data_dir()?;
+    let sessions_dir = sparrow_dir.join("sessions");
 
-    // Create .sparrow directory if it doesn't exist
-    if !sparrow_dir.exists() {
+    // Create the sessions directory if it doesn't exist
+    if !sessions_dir.exists() {
         fs
-            ::create_dir_all(&sparrow_dir)
-            .map_err(|e| format!("Failed to create .sparrow directory: {}", e))?;
+            ::create_dir_all(&sessions_dir)
+            .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
     }
 
-    Ok(sparrow_dir.join("chat_sessions.json"))
+    let legacy_path = sparrow_dir.join("chat_sessions.json");
+    if legacy_path.exists() && !sessions_dir.join(SESSION_INDEX_FILE).exists() {
+        migrate_legacy_sessions(&legacy_path, &sessions_dir)?;
+    }
+
+    Ok(sessions_dir)
+}
+
+fn load_session_index() -> Result<SessionIndex, String> {
+    read_session_index(&get_sessions_dir()?)
+}
+
+fn save_session_index(index: &SessionIndex) -> Result<(), String> {
+    write_json_file(&get_sessions_dir()?.join(SESSION_INDEX_FILE), index)
+}
+
+fn load_session(session_id: &str) -> Result<ChatSession, String> {
+    let path = session_file_path(&get_sessions_dir()?, session_id)?;
+    if !path.exists() {
+        return Err(format!("Chat session not found: {}", session_id));
+    }
+    read_session_file(&path)
+}
+
+fn save_session(session: &ChatSession) -> Result<(), String> {
+    write_json_file(&session_file_path(&get_sessions_dir()?, &session.id)?, session)
+}
+
+fn remove_session(session_id: &str) -> Result<(), String> {
+    let path = session_file_path(&get_sessions_dir()?, session_id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove chat session file: {}", e))?;
+    }
+    Ok(())
 }
 
+// Every stored session, for the session list. Unreadable session files are skipped
 fn load_chat_sessions() -> Result<ChatSessionsStorage, String> {
     debug!("Loading chat sessions");
-    let path = get_chat_sessions_path()?;
+    let sessions_dir = get_sessions_dir()?;
+    let index = read_session_index(&sessions_dir)?;
+
+    let mut sessions = HashMap::new();
+    for session_id in &index.session_ids {
+        let loaded = session_file_path(&sessions_dir, session_id).and_then(|path| read_session_file(&path));
+        match loaded {
+            Ok(session) => {
+                sessions.insert(session_id.clone(), session);
+            }
+            Err(e) => warn!(session_id = %session_id, error = %e, "Skipping unreadable chat session"),
+        }
+    }
+
+    debug!(session_count = sessions.len(), "Chat sessions loaded successfully");
+    Ok(ChatSessionsStorage {
+        sessions,
+        active_session_id: index.active_session_id,
+    })
+}
+
+// Sessions moved out of the main list by archive_chat_session, keyed by session id
+fn get_archived_sessions_path() -> Result<PathBuf, String> {
+    Ok(crate::paths::sparrow_data_dir()?.join("archived_sessions.json"))
+}
 
+fn load_archived_sessions() -> Result<HashMap<String, ChatSession>, String> {
+    let path = get_archived_sessions_path()?;
     if !path.exists() {
-        return Ok(ChatSessionsStorage::default());
+        return Ok(HashMap::new());
     }
 
     let contents = fs
         ::read_to_string(&path)
-        .map_err(|e| format!("Failed to read chat sessions file: {}", e))?;
+        .map_err(|e| format!("Failed to read archived sessions file: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse archived sessions: {}", e))
+}
 
-    let result = serde_json
-        ::from_str::<ChatSessionsStorage>(&contents)
-        .map_err(|e| format!("Failed to parse chat sessions: {}", e));
-    match &result {
-        Ok(sessions) =>
-            debug!(session_count = sessions.sessions.len(), "Chat sessions loaded successfully"),
-        Err(e) => error!(error = %e, "Failed to load chat sessions"),
-    }
-    result
+fn save_archived_sessions(sessions: &HashMap<String, ChatSession>) -> Result<(), String> {
+    let contents = serde_json
+        ::to_string_pretty(sessions)
+        .map_err(|e| format!("Failed to serialize archived sessions: {}", e))?;
+    fs
+        ::write(get_archived_sessions_path()?, contents)
+        .map_err(|e| format!("Failed to write archived sessions file: {}", e))
 }
 
-fn save_chat_sessions(storage: &ChatSessionsStorage) -> Result<(), String> {
-    debug!(session_count = storage.sessions.len(), "Saving chat sessions");
-    let path = get_chat_sessions_path()?;
+// Temporary sessions with autosave enabled are mirrored here until they're persisted
+fn get_temp_sessions_dir() -> Result<PathBuf, String> {
+    let temp_dir = crate::paths::sparrow_data_dir()?.join("temp_sessions");
 
-    let contents = serde_json
-        ::to_string_pretty(storage)
-        .map_err(|e| format!("Failed to serialize chat sessions: {}", e))?;
+    if !temp_dir.exists() {
+        fs
+            ::create_dir_all(&temp_dir)
+            .map_err(|e| format!("Failed to create temp sessions directory: {}", e))?;
+    }
 
-    fs::write(&path, contents).map_err(|e| format!("Failed to write chat sessions file: {}", e))
+    Ok(temp_dir)
+}
+
+fn write_temp_session(session: &ChatSession) -> Result<(), String> {
+    write_json_file(&session_file_path(&get_temp_sessions_dir()?, &session.id)?, session)
+}
+
+fn remove_temp_session(session_id: &str) -> Result<(), String> {
+    let path = session_file_path(&get_temp_sessions_dir()?, session_id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove temp session file: {}", e))?;
+    }
+    Ok(())
 }
 
 fn generate_chat_title(content: &str) -> String {
@@ -144,9 +376,15 @@ pub async fn get_chat_sessions() -> Result<ChatSessionsStorage, String> {
     load_chat_sessions()
 }
 
+/// One stored session, read without loading the others
+#[tauri::command]
+pub async fn get_chat_session(session_id: String) -> Result<ChatSession, String> {
+    load_session(&session_id)
+}
+
 #[tauri::command]
 pub async fn create_chat_session(title: Option<String>) -> Result<ChatSession, String> {
-    let mut storage = load_chat_sessions()?;
+    let mut index = load_session_index()?;
 
     let session_id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp_millis();
@@ -158,12 +396,13 @@ pub async fn create_chat_session(title: Option<String>) -> Result<ChatSession, S
         updated_at: now,
         model_id: None,
         messages: Vec::new(),
+        pinned: false,
     };
 
-    storage.sessions.insert(session_id.clone(), session.clone());
-    storage.active_session_id = Some(session_id);
-
-    save_chat_sessions(&storage)?;
+    save_session(&session)?;
+    index.session_ids.insert(session_id.clone());
+    index.active_session_id = Some(session_id);
+    save_session_index(&index)?;
 
     Ok(session)
 }
@@ -180,6 +419,7 @@ pub async fn create_temporary_chat_session(title: Option<String>) -> Result<Chat
         updated_at: now,
         model_id: None,
         messages: Vec::new(),
+        pinned: false,
     };
 
     // Don't save to storage yet - this is a temporary session
@@ -192,11 +432,7 @@ pub async fn update_chat_session(
     title: Option<String>,
     model_id: Option<String>
 ) -> Result<ChatSession, String> {
-    let mut storage = load_chat_sessions()?;
-
-    let session = storage.sessions
-        .get_mut(&session_id)
-        .ok_or_else(|| format!("Chat session not found: {}", session_id))?;
+    let mut session = load_session(&session_id)?;
 
     if let Some(new_title) = title {
         session.title = new_title;
@@ -207,43 +443,212 @@ pub async fn update_chat_session(
     }
 
     session.updated_at = chrono::Utc::now().timestamp_millis();
+    save_session(&session)?;
 
-    let updated_session = session.clone();
-    save_chat_sessions(&storage)?;
-
-    Ok(updated_session)
+    Ok(session)
 }
 
 #[tauri::command]
 pub async fn delete_chat_session(session_id: String) -> Result<String, String> {
-    let mut storage = load_chat_sessions()?;
+    let mut index = load_session_index()?;
 
-    if !storage.sessions.contains_key(&session_id) {
+    if !index.session_ids.remove(&session_id) {
         return Err(format!("Chat session not found: {}", session_id));
     }
 
-    storage.sessions.remove(&session_id);
-
     // If this was the active session, clear it
-    if storage.active_session_id.as_ref() == Some(&session_id) {
-        storage.active_session_id = None;
+    if index.active_session_id.as_ref() == Some(&session_id) {
+        index.active_session_id = None;
     }
 
-    save_chat_sessions(&storage)?;
+    // Unlist the session before removing its file, so a failure leaves at most an orphan file
+    save_session_index(&index)?;
+    remove_session(&session_id)?;
 
     Ok(format!("Chat session deleted: {}", session_id))
 }
 
+#[tauri::command]
+pub async fn set_chat_session_pinned(session_id: String, pinned: bool) -> Result<ChatSession, String> {
+    let mut session = load_session(&session_id)?;
+    session.pinned = pinned;
+    save_session(&session)?;
+
+    Ok(session)
+}
+
+// Ids of sessions last updated before `cutoff` (ms since epoch)
+fn expired_session_ids(
+    sessions: &HashMap<String, ChatSession>,
+    cutoff: i64,
+    keep_pinned: bool
+) -> Vec<String> {
+    sessions
+        .values()
+        .filter(|session| session.updated_at < cutoff && !(keep_pinned && session.pinned))
+        .map(|session| session.id.clone())
+        .collect()
+}
+
+/// Delete sessions not updated in the last `older_than_days` days, returning how many were removed
+#[tauri::command]
+pub async fn prune_chat_sessions(older_than_days: u32, keep_pinned: bool) -> Result<usize, String> {
+    let storage = load_chat_sessions()?;
+
+    let cutoff = chrono::Utc::now().timestamp_millis() - i64::from(older_than_days) * 24 * 60 * 60 * 1000;
+    let expired = expired_session_ids(&storage.sessions, cutoff, keep_pinned);
+    if expired.is_empty() {
+        return Ok(0);
+    }
+
+    let mut index = load_session_index()?;
+    for session_id in &expired {
+        index.session_ids.remove(session_id);
+    }
+    if index.active_session_id.as_ref().is_some_and(|id| expired.contains(id)) {
+        index.active_session_id = None;
+    }
+
+    save_session_index(&index)?;
+    for session_id in &expired {
+        remove_session(session_id)?;
+    }
+    info!(removed = expired.len(), older_than_days, "Pruned old chat sessions");
+
+    Ok(expired.len())
+}
+
+/// Move a session to archived_sessions.json, out of the session list but not deleted
+#[tauri::command]
+pub async fn archive_chat_session(session_id: String) -> Result<String, String> {
+    let session = load_session(&session_id)?;
+
+    // Write the archive first so a failure can't lose the session
+    let mut archived = load_archived_sessions()?;
+    archived.insert(session_id.clone(), session);
+    save_archived_sessions(&archived)?;
+
+    let mut index = load_session_index()?;
+    index.session_ids.remove(&session_id);
+    if index.active_session_id.as_ref() == Some(&session_id) {
+        index.active_session_id = None;
+    }
+    save_session_index(&index)?;
+    remove_session(&session_id)?;
+
+    Ok(format!("Chat session archived: {}", session_id))
+}
+
+#[tauri::command]
+pub async fn get_archived_chat_sessions() -> Result<Vec<ChatSession>, String> {
+    let mut sessions: Vec<ChatSession> = load_archived_sessions()?.into_values().collect();
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(sessions)
+}
+
+#[tauri::command]
+pub async fn merge_chat_sessions(
+    source_id: String,
+    target_id: String
+) -> Result<ChatSession, String> {
+    if source_id == target_id {
+        return Err("Cannot merge a chat session into itself".to_string());
+    }
+
+    let mut target = load_session(&target_id)?;
+    let source = load_session(&source_id)?;
+
+    merge_session_messages(&mut target, source);
+    target.updated_at = chrono::Utc::now().timestamp_millis();
+    save_session(&target)?;
+
+    // The source no longer exists, so don't leave it selected
+    let mut index = load_session_index()?;
+    index.session_ids.remove(&source_id);
+    if index.active_session_id.as_ref() == Some(&source_id) {
+        index.active_session_id = None;
+    }
+    save_session_index(&index)?;
+    remove_session(&source_id)?;
+
+    Ok(target)
+}
+
+// Append the source messages with fresh ids and keep the result in chronological order
+fn merge_session_messages(target: &mut ChatSession, source: ChatSession) {
+    target.messages.extend(
+        source.messages.into_iter().map(|mut message| {
+            message.id = Uuid::new_v4().to_string();
+            message
+        })
+    );
+    // Stable sort, so messages with equal timestamps keep their relative order
+    target.messages.sort_by_key(|message| message.timestamp);
+}
+
+#[tauri::command]
+pub async fn fork_chat_session(
+    session_id: String,
+    up_to_message_id: Option<String>
+) -> Result<ChatSession, String> {
+    let original = load_session(&session_id)?;
+
+    let messages = fork_messages(&original.messages, up_to_message_id.as_deref()).ok_or_else(||
+        format!("Message not found in session {}: {}", session_id, up_to_message_id.unwrap_or_default())
+    )?;
+
+    let fork_id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp_millis();
+
+    let fork = ChatSession {
+        id: fork_id.clone(),
+        title: format!("Fork of {}", original.title),
+        created_at: now,
+        updated_at: now,
+        model_id: original.model_id.clone(),
+        messages,
+        pinned: false,
+    };
+
+    save_session(&fork)?;
+    let mut index = load_session_index()?;
+    index.session_ids.insert(fork_id.clone());
+    index.active_session_id = Some(fork_id);
+    save_session_index(&index)?;
+
+    Ok(fork)
+}
+
+// Copy messages up to and including `up_to_message_id` (all when None) with fresh ids.
+// Returns None if the message isn't in the session
+fn fork_messages(messages: &[ChatMessage], up_to_message_id: Option<&str>) -> Option<Vec<ChatMessage>> {
+    let end = match up_to_message_id {
+        Some(message_id) => messages.iter().position(|m| m.id == message_id)? + 1,
+        None => messages.len(),
+    };
+
+    Some(
+        messages[..end]
+            .iter()
+            .cloned()
+            .map(|mut message| {
+                message.id = Uuid::new_v4().to_string();
+                message
+            })
+            .collect()
+    )
+}
+
 #[tauri::command]
 pub async fn set_active_chat_session(session_id: String) -> Result<String, String> {
-    let mut storage = load_chat_sessions()?;
+    let mut index = load_session_index()?;
 
-    if !storage.sessions.contains_key(&session_id) {
+    if !index.session_ids.contains(&session_id) {
         return Err(format!("Chat session not found: {}", session_id));
     }
 
-    storage.active_session_id = Some(session_id.clone());
-    save_chat_sessions(&storage)?;
+    index.active_session_id = Some(session_id.clone());
+    save_session_index(&index)?;
 
     Ok(session_id)
 }
@@ -256,11 +661,7 @@ pub async fn add_message_to_session(
     tokens_per_second: Option<f64>,
     is_error: Option<bool>
 ) -> Result<ChatMessage, String> {
-    let mut storage = load_chat_sessions()?;
-
-    let session = storage.sessions
-        .get_mut(&session_id)
-        .ok_or_else(|| format!("Chat session not found: {}", session_id))?;
+    let mut session = load_session(&session_id)?;
 
     let message_id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp_millis();
@@ -283,21 +684,71 @@ pub async fn add_message_to_session(
         session.title = title;
     }
 
-    save_chat_sessions(&storage)?;
+    // Only this session's file is rewritten
+    save_session(&session)?;
 
     Ok(message)
 }
 
 #[tauri::command]
 pub async fn persist_temporary_session(session: ChatSession) -> Result<ChatSession, String> {
-    let mut storage = load_chat_sessions()?;
+    save_session(&session)?;
+    let mut index = load_session_index()?;
+    index.session_ids.insert(session.id.clone());
+    index.active_session_id = Some(session.id.clone());
+    save_session_index(&index)?;
+
+    // The session is safe in storage now, so the autosave copy is no longer needed
+    if let Err(e) = remove_temp_session(&session.id) {
+        warn!(session_id = %session.id, error = %e, "Failed to remove autosaved temp session");
+    }
+
+    Ok(session)
+}
+
+/// Start mirroring a temporary session to `.sparrow/temp_sessions/<id>.json`. Every later
+/// add_message_to_temporary_session call rewrites the file until the session is persisted
+#[tauri::command]
+pub async fn enable_temp_session_autosave(session: ChatSession) -> Result<(), String> {
+    write_temp_session(&session)
+}
 
-    storage.sessions.insert(session.id.clone(), session.clone());
-    storage.active_session_id = Some(session.id.clone());
+/// Temporary sessions left behind by a crash or forced quit, newest first
+#[tauri::command]
+pub async fn list_recoverable_temp_sessions() -> Result<Vec<ChatSession>, String> {
+    let temp_dir = get_temp_sessions_dir()?;
+
+    let entries = fs
+        ::read_dir(&temp_dir)
+        .map_err(|e| format!("Failed to read temp sessions directory: {}", e))?;
+
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
 
-    save_chat_sessions(&storage)?;
+        let parsed = fs
+            ::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                serde_json::from_str::<ChatSession>(&contents).map_err(|e| e.to_string())
+            });
+        match parsed {
+            Ok(session) => sessions.push(session),
+            Err(e) => warn!(path = %path.display(), error = %e, "Skipping unreadable temp session"),
+        }
+    }
 
-    Ok(session)
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(sessions)
+}
+
+/// Throw away an autosaved temporary session without recovering it
+#[tauri::command]
+pub async fn discard_temp_session(session_id: String) -> Result<(), String> {
+    remove_temp_session(&session_id)
 }
 
 #[tauri::command]
@@ -329,27 +780,26 @@ pub async fn add_message_to_temporary_session(
         session.title = title;
     }
 
+    // Keep the autosave copy current if it was enabled for this session
+    let autosaved = get_temp_sessions_dir()
+        .and_then(|dir| session_file_path(&dir, &session.id))
+        .map(|path| path.exists())
+        .unwrap_or(false);
+    if autosaved {
+        write_temp_session(&session)?;
+    }
+
     Ok((session, message))
 }
 
 #[tauri::command]
 pub async fn get_session_messages(session_id: String) -> Result<Vec<ChatMessage>, String> {
-    let storage = load_chat_sessions()?;
-
-    let session = storage.sessions
-        .get(&session_id)
-        .ok_or_else(|| format!("Chat session not found: {}", session_id))?;
-
-    Ok(session.messages.clone())
+    Ok(load_session(&session_id)?.messages)
 }
 
 #[tauri::command]
 pub async fn get_conversation_history(session_id: String) -> Result<Vec<ChatMessage>, String> {
-    let storage = load_chat_sessions()?;
-
-    let session = storage.sessions
-        .get(&session_id)
-        .ok_or_else(|| format!("Chat session not found: {}", session_id))?;
+    let session = load_session(&session_id)?;
 
     // Return all messages except any currently streaming ones
     let messages: Vec<ChatMessage> = session.messages
@@ -361,28 +811,65 @@ pub async fn get_conversation_history(session_id: String) -> Result<Vec<ChatMess
     Ok(messages)
 }
 
-// Chat with the currently loaded model using streaming
 #[tauri::command]
-pub async fn chat_with_loaded_model_streaming(
-    app: AppHandle,
-    model_name: String,
-    message: String,
-    session_id: Option<String>,
-    include_history: Option<bool>,
-    system_prompt: Option<String>,
-    temperature: Option<f64>,
-    top_p: Option<f64>,
-    seed: Option<i64>,
-    max_tokens: Option<u32>,
-    max_completion_tokens: Option<u32>
-) -> Result<String, String> {
-    let config = OpenAIConfig::new()
-        .with_api_key("unused")
-        .with_api_base("http://localhost:1114/v3");
-    let client = Client::with_config(config);
+pub async fn get_session_stats(session_id: String) -> Result<SessionStats, String> {
+    let session = load_session(&session_id)?;
+
+    Ok(compute_session_stats(&session.messages))
+}
+
+fn compute_session_stats(messages: &[ChatMessage]) -> SessionStats {
+    let mut stats = SessionStats::default();
+    let mut speeds = Vec::new();
+
+    for message in messages {
+        let characters = message.content.chars().count();
+        let tokens = approximate_token_count(&message.content);
+
+        stats.message_count += 1;
+        stats.total_characters += characters;
+        stats.approximate_tokens += tokens;
+
+        let role = stats.by_role.entry(message.role.clone()).or_default();
+        role.message_count += 1;
+        role.total_characters += characters;
+        role.approximate_tokens += tokens;
+
+        if message.role == "assistant" {
+            if let Some(speed) = message.tokens_per_second {
+                speeds.push(speed);
+            }
+        }
+    }
+
+    if !speeds.is_empty() {
+        stats.average_tokens_per_second = Some(speeds.iter().sum::<f64>() / (speeds.len() as f64));
+    }
+
+    stats
+}
 
-    // Get MCP tools info for system message
-    let mcp_tools = match mcp::get_all_mcp_tools_for_chat(app.clone()).await {
+// Rough token estimate without a tokenizer: about four characters per token for prose, but
+// never fewer tokens than whitespace-separated words
+pub(crate) fn approximate_token_count(text: &str) -> usize {
+    let words = text.split_whitespace().count();
+    let by_chars = text.chars().count().div_ceil(4);
+    words.max(by_chars)
+}
+
+const DEFAULT_SYSTEM_PROMPT: &str =
+    "You are a helpful AI assistant with access to various functions/tools. 
+        You MUST use the available tools when they are relevant to answer the user's request.
+
+        IMPORTANT RULES:
+        1. NEVER make up or guess information that could be obtained from a function call
+        2. If you have a tool that can answer the question, USE IT
+
+        Available tools should be called whenever relevant to provide accurate, up-to-date information.";
+
+// Load the MCP tools offered to the model, falling back to none on failure
+async fn load_chat_tools(app: &AppHandle) -> Vec<ChatCompletionTool> {
+    match mcp::get_all_mcp_tools_for_chat(app.clone()).await {
         Ok(tools) => {
             debug!("Successfully loaded {} MCP tools for system message", tools.len());
             tools
@@ -391,34 +878,34 @@ pub async fn chat_with_loaded_model_streaming(
             warn!("Failed to load MCP tools for system message: {}", e);
             Vec::new()
         }
-    };
+    }
+}
 
-    let tools_info = if !mcp_tools.is_empty() {
-        debug!("Processing MCP tools for system message...");
+// Generate tool descriptions in simple text format for the custom template
+fn format_tools_block(tools: &[ChatCompletionTool]) -> String {
+    if tools.is_empty() {
+        return String::new();
+    }
 
-        // Generate tool descriptions in simple text format for the custom template
-        let tool_descs: Vec<String> = mcp_tools
-            .iter()
-            .enumerate()
-            .map(|(i, tool)| {
-                debug!("Processing tool {}: {}", i, tool.function.name);
-                let params_str = match &tool.function.parameters {
-                    Some(params) => serde_json::to_string_pretty(params).unwrap_or_default(),
-                    None => "{}".to_string(),
-                };
-
-                format!(
-                    "{}({}) - {}",
-                    tool.function.name,
-                    params_str,
-                    tool.function.description.as_ref().unwrap_or(&"".to_string())
-                )
-            })
-            .collect();
+    let tool_descs_text = tools
+        .iter()
+        .map(|tool| {
+            let params_str = match &tool.function.parameters {
+                Some(params) => serde_json::to_string_pretty(params).unwrap_or_default(),
+                None => "{}".to_string(),
+            };
+
+            format!(
+                "{}({}) - {}",
+                tool.function.name,
+                params_str,
+                tool.function.description.as_deref().unwrap_or("")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
 
-        let tool_descs_text = tool_descs.join("\n");
-        let formatted_tools =
-            format!(r#"
+    format!(r#"
 
 # Tools
 
@@ -432,50 +919,49 @@ You are provided with function signatures within <tools></tools> XML tags:
 For each function call, return a json object with function name and arguments within <tool_call></tool_call> XML tags:
 <tool_call>
 {{"name": <function-name>, "arguments": <args-json-object>}}
-</tool_call>"#, tool_descs_text);
-
-        debug!("Generated custom tool template: {} characters", formatted_tools.len());
-        formatted_tools
-    } else {
-        debug!("No MCP tools available for system message");
-        "".to_string()
-    };
+</tool_call>"#, tool_descs_text)
+}
 
-    let base_system_message = system_prompt.unwrap_or_else(|| {
-        "You are a helpful AI assistant with access to various functions/tools. 
-        You MUST use the available tools when they are relevant to answer the user's request.
+/// Build the system message sent to the model: the base prompt followed by the <tools> block
+pub fn build_system_message(system_prompt: Option<String>, tools: &[ChatCompletionTool]) -> String {
+    let base_system_message = system_prompt.unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string());
 
-        IMPORTANT RULES:
-        1. NEVER make up or guess information that could be obtained from a function call
-        2. If you have a tool that can answer the question, USE IT
+    // Always append tools info to system message (whether custom or default)
+    format!("{}{}", base_system_message, format_tools_block(tools))
+}
 
-        Available tools should be called whenever relevant to provide accurate, up-to-date information.".to_string()
-    });
+// Show the exact system message the model would receive with the current MCP tools
+#[tauri::command]
+pub async fn preview_system_message(
+    app: AppHandle,
+    system_prompt: Option<String>
+) -> Result<String, String> {
+    let mcp_tools = load_chat_tools(&app).await;
+    Ok(build_system_message(system_prompt, &mcp_tools))
+}
 
-    // Always append tools info to system message (whether custom or default)
-    let system_message = format!("{}{}", base_system_message, tools_info);
-
-    debug!("Message: {}", system_message);
-    // Log what we're including
-    debug!("System message length: {} chars", system_message.len());
-    debug!("Tools info length: {} chars", tools_info.len());
-    if !tools_info.is_empty() {
-        debug!("Including tools info in system message");
-    } else {
-        debug!("No tools info to include");
+// Chat with the currently loaded model using streaming
+// Build the request messages: system message, optional stored history, then the user message
+async fn build_chat_messages(
+    system_message: Option<&str>,
+    message: &str,
+    session_id: Option<&str>,
+    include_history: bool
+) -> Result<Vec<async_openai::types::ChatCompletionRequestMessage>, String> {
+    let mut messages = Vec::new();
+    if let Some(system_message) = system_message {
+        messages.push(
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(system_message.to_string())
+                .build()
+                .map_err(|e| format!("Failed to build system message: {}", e))?
+                .into()
+        );
     }
 
-    let mut messages = vec![
-        ChatCompletionRequestSystemMessageArgs::default()
-            .content(system_message.clone())
-            .build()
-            .map_err(|e| format!("Failed to build system message: {}", e))?
-            .into()
-    ];
-
     // Include conversation history if requested and session_id is provided
-    if include_history.unwrap_or(false) && session_id.is_some() {
-        match get_conversation_history(session_id.clone().unwrap()).await {
+    if let (true, Some(session_id)) = (include_history, session_id) {
+        match get_conversation_history(session_id.to_string()).await {
             Ok(mut history) => {
                 // Remove the last user message if it matches the current message
                 // This prevents duplicate user messages
@@ -523,20 +1009,25 @@ For each function call, return a json object with function name and arguments wi
     // Always add the current user message
     messages.push(
         ChatCompletionRequestUserMessageArgs::default()
-            .content(message.clone())
+            .content(message.to_string())
             .build()
             .map_err(|e| format!("Failed to build user message: {}", e))?
             .into()
     );
 
-    debug!("Starting chat request");
+    Ok(messages)
+}
 
-    // Create streaming chat completion
-    let mut request_builder = CreateChatCompletionRequestArgs::default();
+// Sampling and length settings shared by every chat completion request
+fn apply_sampling_params(
+    request_builder: &mut CreateChatCompletionRequestArgs,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    seed: Option<i64>,
+    max_tokens: Option<u32>,
+    max_completion_tokens: Option<u32>
+) {
     request_builder
-        .model(model_name.clone())
-        .messages(messages.clone())
-        .stream(true)
         .temperature(temperature.unwrap_or(0.7) as f32)
         .top_p(top_p.unwrap_or(1.0) as f32);
 
@@ -552,67 +1043,204 @@ For each function call, return a json object with function name and arguments wi
     if let Some(max_completion_tokens) = max_completion_tokens {
         request_builder.max_completion_tokens(max_completion_tokens);
     }
+}
 
-    // Commented out: Add MCP tools using modern tools format
-    /*
-    match mcp::get_all_mcp_tools_for_chat(app.clone()).await {
-        Ok(mcp_tools) => {
-            if !mcp_tools.is_empty() {
-                let mcp_info = format!("Adding {} MCP tools to chat completion", mcp_tools.len());
-                debug!("{}", mcp_info);
-
-                // Log each tool for debugging
-                for (i, tool) in mcp_tools.iter().enumerate() {
-                    let tool_info = format!(
-                        "Tool {}: {} - {}",
-                        i,
-                        &tool.function.name,
-                        tool.function.description.as_ref().unwrap_or(&"No description".to_string())
-                    );
-                    debug!("{}", tool_info);
-                }
+/// Single non-streaming completion against the loaded model, returning the whole reply.
+/// Tools aren't offered; use chat_with_loaded_model_streaming for tool calling
+#[tauri::command]
+pub async fn chat_with_loaded_model(
+    model_name: String,
+    message: String,
+    session_id: Option<String>,
+    include_history: Option<bool>,
+    system_prompt: Option<String>,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    seed: Option<i64>,
+    max_tokens: Option<u32>,
+    max_completion_tokens: Option<u32>
+) -> Result<String, String> {
+    crate::ovms::ensure_ovms_ready().await?;
 
-                debug!("Using modern 'tools' format...");
-                request_builder.tools(mcp_tools.clone());
-
-                // Determine tool choice based on message content
-                let message_lower = message.to_lowercase();
-                let forced_tool = if message_lower.contains("time") || message_lower.contains("current") {
-                    mcp_tools.iter().find(|tool| tool.function.name.contains("time_get_current_time"))
-                } else if message_lower.contains("convert") && message_lower.contains("time") {
-                    mcp_tools.iter().find(|tool| tool.function.name.contains("time_convert_time"))
-                } else {
-                    None
-                };
-
-                if let Some(tool) = forced_tool {
-                    debug!("Forcing specific tool call: {}", tool.function.name);
-                    
-                    let specific_choice = ChatCompletionNamedToolChoice {
-                        r#type: ChatCompletionToolType::Function,
-                        function: FunctionName {
-                            name: tool.function.name.clone(),
-                        },
-                    };
-                    request_builder.tool_choice(ChatCompletionToolChoiceOption::Named(specific_choice));
-                } else {
-                    debug!("Using auto tool choice (no specific match found)");
-                    request_builder.tool_choice(ChatCompletionToolChoiceOption::Auto);
-                }
-            } else {
-                debug!("No MCP tools available");
-            }
-        }
-        Err(e) => {
-            let mcp_error = format!("Failed to get MCP tools: {}", e);
-            warn!("{}", mcp_error);
-            // Continue without tools
+    let config = OpenAIConfig::new()
+        .with_api_key("unused")
+        .with_api_base(format!("{}/v3", crate::settings::ovms_base_url()));
+    let client = Client::with_config(config);
+
+    let system_message = build_system_message(system_prompt, &[]);
+    let messages = build_chat_messages(
+        Some(&system_message),
+        &message,
+        session_id.as_deref(),
+        include_history.unwrap_or(false)
+    ).await?;
+
+    let mut request_builder = CreateChatCompletionRequestArgs::default();
+    request_builder.model(model_name.clone()).messages(messages);
+    apply_sampling_params(
+        &mut request_builder,
+        temperature,
+        top_p,
+        seed,
+        max_tokens,
+        max_completion_tokens
+    );
+
+    let request = request_builder
+        .build()
+        .map_err(|e| format!("Failed to build chat request: {}", e))?;
+
+    let response = client
+        .chat()
+        .create(request).await
+        .map_err(|e| format!("Chat request failed: {}", e))?;
+
+    let content = response.choices
+        .into_iter()
+        .next()
+        .and_then(|choice| choice.message.content)
+        .unwrap_or_default();
+
+    debug!(model = %model_name, response_length = content.len(), "Chat completion finished");
+    Ok(content)
+}
+
+// Limits how many chat streams run against OVMS at once, see Settings::max_concurrent_chats.
+// Rebuilt when the setting changes; streams holding a permit from the old one just finish
+static CHAT_LIMITER: std::sync::Mutex<Option<(usize, std::sync::Arc<tokio::sync::Semaphore>)>> =
+    std::sync::Mutex::new(None);
+
+fn chat_semaphore(permits: usize) -> std::sync::Arc<tokio::sync::Semaphore> {
+    let mut limiter = CHAT_LIMITER.lock().unwrap_or_else(|e| e.into_inner());
+    match limiter.as_ref() {
+        Some((current, semaphore)) if *current == permits => semaphore.clone(),
+        _ => {
+            let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(permits));
+            *limiter = Some((permits, semaphore.clone()));
+            semaphore
         }
     }
-    */
+}
+
+/// Wait for a free chat slot when a limit is configured, emitting chat-queued if the
+/// request has to wait. The slot is released when the returned permit is dropped
+async fn acquire_chat_slot(
+    app: &AppHandle,
+    session_id: Option<&str>
+) -> Result<Option<tokio::sync::OwnedSemaphorePermit>, String> {
+    let permits = crate::settings::current().max_concurrent_chats;
+    if permits == 0 {
+        return Ok(None);
+    }
+
+    let semaphore = chat_semaphore(permits);
+    if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+        return Ok(Some(permit));
+    }
 
-    // Tools info is now in system message instead
-    debug!("Tools info included in system message instead of request tools array");
+    info!(max_concurrent_chats = permits, "Chat request queued until another chat finishes");
+    let _ = app.emit(
+        "chat-queued",
+        serde_json::json!({
+            "sessionId": session_id,
+            "maxConcurrentChats": permits
+        })
+    );
+
+    semaphore
+        .acquire_owned().await
+        .map(Some)
+        .map_err(|e| format!("Chat queue closed: {}", e))
+}
+
+#[tauri::command]
+pub async fn chat_with_loaded_model_streaming(
+    app: AppHandle,
+    model_name: String,
+    message: String,
+    session_id: Option<String>,
+    include_history: Option<bool>,
+    system_prompt: Option<String>,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    seed: Option<i64>,
+    max_tokens: Option<u32>,
+    max_completion_tokens: Option<u32>,
+    tool_mode: Option<ToolMode>,
+    max_tool_iterations: Option<u32>,
+    preset: Option<String>,
+    use_system_message: Option<bool>
+) -> Result<String, String> {
+    crate::ovms::ensure_ovms_ready().await?;
+
+    // Parameters passed explicitly win; the preset fills in the rest
+    let preset = match preset {
+        Some(name) => crate::presets::find_preset(&name)?,
+        None => Default::default(),
+    };
+    let system_prompt = system_prompt.or(preset.system_prompt);
+    let temperature = temperature.or(preset.temperature);
+    let top_p = top_p.or(preset.top_p);
+    let seed = seed.or(preset.seed);
+    let max_tokens = max_tokens.or(preset.max_tokens);
+    let max_completion_tokens = max_completion_tokens.or(preset.max_completion_tokens);
+    let tool_mode = tool_mode.or(preset.tool_mode);
+
+    let config = OpenAIConfig::new()
+        .with_api_key("unused")
+        .with_api_base(format!("{}/v3", crate::settings::ovms_base_url()));
+    let client = Client::with_config(config);
+    let tool_mode = tool_mode.unwrap_or_default();
+
+    // Base models and some chat templates reject the system role, so those chats send only
+    // user and assistant turns, and without a system message there is nowhere to describe tools
+    let use_system_message = use_system_message.unwrap_or(true);
+
+    // In XML mode the tools are described in the system message, native mode sends them in the request
+    let mcp_tools = if use_system_message { load_chat_tools(&app).await } else { Vec::new() };
+    let system_message = if use_system_message {
+        let system_message = match tool_mode {
+            ToolMode::XmlPrompt => build_system_message(system_prompt, &mcp_tools),
+            ToolMode::NativeTools => build_system_message(system_prompt, &[]),
+        };
+        debug!("Message: {}", system_message);
+        debug!("System message length: {} chars", system_message.len());
+        Some(system_message)
+    } else {
+        debug!("System message disabled for this chat");
+        None
+    };
+
+    let messages = build_chat_messages(
+        system_message.as_deref(),
+        &message,
+        session_id.as_deref(),
+        include_history.unwrap_or(false)
+    ).await?;
+
+    debug!("Starting chat request");
+
+    // Create streaming chat completion
+    let mut request_builder = CreateChatCompletionRequestArgs::default();
+    request_builder
+        .model(model_name.clone())
+        .messages(messages.clone())
+        .stream(true);
+    apply_sampling_params(
+        &mut request_builder,
+        temperature,
+        top_p,
+        seed,
+        max_tokens,
+        max_completion_tokens
+    );
+
+    if tool_mode == ToolMode::NativeTools && !mcp_tools.is_empty() {
+        debug!("Adding {} MCP tools to request tools array", mcp_tools.len());
+        request_builder.tools(mcp_tools.clone()).tool_choice(ChatCompletionToolChoiceOption::Auto);
+    } else {
+        debug!("Tools info included in system message instead of request tools array");
+    }
 
     let request = request_builder
         .build()
@@ -641,8 +1269,10 @@ For each function call, return a json object with function name and arguments wi
             }
         }
 
-        // Verify no tools array in request (should be commented out)
-        if request_value.get("tools").is_some() {
+        // Verify no tools array in request unless native tools were requested
+        if tool_mode == ToolMode::NativeTools {
+            debug!("Native tools mode, tools array expected in request");
+        } else if request_value.get("tools").is_some() {
             warn!("Tools array still present in request!");
         } else {
             debug!("Confirmed: No tools array in request (as expected)");
@@ -650,14 +1280,30 @@ For each function call, return a json object with function name and arguments wi
     }
     // Request logging complete
 
-    let mut stream = client
-        .chat()
-        .create_stream(request).await
-        .map_err(|e| format!("Failed to create chat stream: {}", e))?;
+    // Held until the response, including any tool continuations, has been streamed
+    let _chat_slot = acquire_chat_slot(&app, session_id.as_deref()).await?;
+
+    // A request gets one retry in total, whether creating the stream or reading it fails
+    let mut stream_retried = false;
+    let mut stream = match client.chat().create_stream(request.clone()).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            stream_retried = true;
+            warn!(error = %e, "Failed to create chat stream, retrying once");
+            client
+                .chat()
+                .create_stream(request.clone()).await
+                .map_err(|e| format!("Failed to create chat stream: {}", e))?
+        }
+    };
 
     let mut full_response = String::new();
     let mut executed_tools = std::collections::HashSet::new();
     let mut needs_continuation = false;
+    let mut native_tool_calls: std::collections::BTreeMap<u32, NativeToolCall> =
+        std::collections::BTreeMap::new();
+    // A dropped connection is only retried while nothing has been streamed to the UI yet
+    let mut received_content = false;
 
     // Process streaming responses with function call support
     while let Some(result) = stream.next().await {
@@ -668,6 +1314,10 @@ For each function call, return a json object with function name and arguments wi
                 for chat_choice in response.choices {
                     // Processing stream choice (verbose logging disabled)
 
+                    if chat_choice.delta.content.is_some() || chat_choice.delta.tool_calls.is_some() {
+                        received_content = true;
+                    }
+
                     // Handle content and look for <tool_call> XML tags
                     if let Some(content) = &chat_choice.delta.content {
                         full_response.push_str(content);
@@ -682,93 +1332,39 @@ For each function call, return a json object with function name and arguments wi
                         );
 
                         // Process any complete tool calls found in the response so far
-                        let tool_calls = extract_all_tool_calls_from_xml(&full_response);
+                        let tool_calls = if tool_mode == ToolMode::XmlPrompt {
+                            extract_all_tool_calls_from_xml(&full_response)
+                        } else {
+                            Vec::new()
+                        };
+
+                        if
+                            run_xml_tool_calls(
+                                &app,
+                                tool_calls,
+                                &mcp_tools,
+                                &mut executed_tools,
+                                &mut full_response
+                            ).await
+                        {
+                            // Continue the conversation after tool execution, even on tool errors
+                            needs_continuation = true;
+                        }
+                    }
 
-                        for (fn_name, fn_args) in tool_calls {
-                            // Skip if we already executed this exact tool call
-                            let tool_signature = format!("{}:{}", fn_name, fn_args);
-                            if executed_tools.contains(&tool_signature) {
-                                continue;
+                    // Native tool calls arrive as fragments keyed by index
+                    if let Some(tool_call_chunks) = &chat_choice.delta.tool_calls {
+                        for chunk in tool_call_chunks {
+                            let call = native_tool_calls.entry(chunk.index).or_default();
+                            if let Some(id) = &chunk.id {
+                                call.id = id.clone();
                             }
-
-                            executed_tools.insert(tool_signature);
-
-                            debug!("Found complete tool call: name={}, args={}", fn_name, fn_args);
-
-                            // Parse arguments as JSON for MCP tool call
-                            let args_map = match
-                                serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(
-                                    &fn_args
-                                )
-                            {
-                                Ok(mut map) => {
-                                    // Remove null values as MCP tools don't handle them well
-                                    map.retain(|_k, v| !v.is_null());
-                                    Some(map)
-                                }
-                                Err(e) => {
-                                    let parse_error =
-                                        format!("Failed to parse tool arguments: {}", e);
-                                    warn!("{}", parse_error);
-                                    None
+                            if let Some(function) = &chunk.function {
+                                if let Some(name) = &function.name {
+                                    call.name.push_str(name);
                                 }
-                            };
-
-                            // Call the MCP tool
-                            match mcp::call_mcp_tool(app.clone(), fn_name.clone(), args_map).await {
-                                Ok(tool_result) => {
-                                    let tool_result_info = format!(
-                                        "Tool {} returned: {}",
-                                        fn_name,
-                                        tool_result
-                                    );
-                                    debug!("{}", tool_result_info);
-
-                                    // Emit function call result to frontend
-                                    let _ = app.emit(
-                                        "tool-call",
-                                        serde_json::json!({
-                                            "tool_name": fn_name,
-                                            "arguments": fn_args,
-                                            "result": tool_result
-                                        })
-                                    );
-
-                                    // Add tool response in Qwen-Agent format and emit to frontend
-                                    let tool_response_text =
-                                        format!("\n<tool_response>\n{}\n</tool_response>", tool_result);
-                                    full_response.push_str(&tool_response_text);
-
-                                    // Emit tool response as streaming content (including XML tags)
-                                    let _ = app.emit(
-                                        "chat-token",
-                                        serde_json::json!({
-                                            "token": tool_response_text,
-                                            "finished": false
-                                        })
-                                    );
-
-                                    // Mark that we need to continue the conversation after tool execution
-                                    needs_continuation = true;
-                                }
-                                Err(e) => {
-                                    let tool_error = format!("Tool call failed: {}", e);
-                                    error!("{}", tool_error);
-                                    let error_response_text =
-                                        format!("\n<tool_response>\nError: {}\n</tool_response>", e);
-                                    full_response.push_str(&error_response_text);
-
-                                    // Emit error response as streaming content (including XML tags)
-                                    let _ = app.emit(
-                                        "chat-token",
-                                        serde_json::json!({
-                                            "token": error_response_text,
-                                            "finished": false
-                                        })
-                                    );
-
-                                    // Mark that we need to continue the conversation even after tool error
-                                    needs_continuation = true;
+                                if let Some(arguments) = &function.arguments {
+                                    call.arguments.push_str(arguments);
                                 }
                             }
                         }
@@ -785,6 +1381,25 @@ For each function call, return a json object with function name and arguments wi
                     }
                 }
             }
+            Err(err) if !received_content && !stream_retried => {
+                stream_retried = true;
+                warn!(error = %err, "Chat stream failed before any content, retrying once");
+                match client.chat().create_stream(request.clone()).await {
+                    Ok(new_stream) => {
+                        stream = new_stream;
+                    }
+                    Err(e) => {
+                        error!(error = %e, "Failed to recreate chat stream");
+                        let _ = app.emit(
+                            "chat-error",
+                            serde_json::json!({
+                                "error": format!("Stream error: {}", e)
+                            })
+                        );
+                        break;
+                    }
+                }
+            }
             Err(err) => {
                 let error_info = format!("error: {err}");
                 error!("{}", error_info);
@@ -799,53 +1414,112 @@ For each function call, return a json object with function name and arguments wi
         }
     }
 
-    // Continue the conversation if we executed tools and got JSON responses
-    if needs_continuation {
-        debug!("Checking if continuation is needed after tool execution...");
-
-        // Check if the tool responses contain JSON structures that need interpretation
-        let should_continue = check_if_continuation_needed(&full_response);
-
-        if should_continue {
-            debug!("Tool response contains JSON - continuing conversation...");
-
-            match
-                continue_conversation_after_tools(
-                    app.clone(),
-                    &client,
-                    &system_message,
-                    &messages,
-                    full_response.clone(),
-                    &model_name,
-                    temperature,
-                    top_p,
-                    seed,
-                    max_tokens,
-                    max_completion_tokens
-                ).await
-            {
-                Ok(continued_response) => {
-                    if !continued_response.trim().is_empty() {
-                        // Append the continued response (streaming is already handled by continue_conversation_after_tools)
-                        full_response.push_str(&continued_response);
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to continue conversation: {}", e);
-                    let error_msg = format!("\n\n[Continuation Error: {}]", e);
-                    full_response.push_str(&error_msg);
-
-                    let _ = app.emit(
-                        "chat-token",
-                        serde_json::json!({
-                            "token": error_msg,
-                            "finished": false
-                        })
-                    );
+    // Run tools and continue the conversation until the model stops asking for tools. Each
+    // continuation may request more tools, so multi-step chains work, up to a fixed cap
+    let max_tool_iterations = max_tool_iterations.unwrap_or(DEFAULT_MAX_TOOL_ITERATIONS);
+    let native_tools: &[ChatCompletionTool] = if tool_mode == ToolMode::NativeTools {
+        &mcp_tools
+    } else {
+        &[]
+    };
+    let mut pending_native_calls = finish_native_tool_calls(native_tool_calls);
+    // Native mode replays the conversation as structured messages, starting from the request
+    let mut native_conversation = messages.clone();
+    let mut step_text = full_response.clone();
+    let mut iteration = 0;
+
+    loop {
+        let xml_pending = needs_continuation && check_if_continuation_needed(&full_response);
+        if pending_native_calls.is_empty() && !xml_pending {
+            break;
+        }
+
+        if iteration >= max_tool_iterations {
+            warn!(max_tool_iterations, "Tool call limit reached, stopping");
+            let limit_msg = format!(
+                "\n\n[Stopped after {} tool call rounds; the model kept requesting tools]",
+                max_tool_iterations
+            );
+            full_response.push_str(&limit_msg);
+            let _ = app.emit(
+                "chat-token",
+                serde_json::json!({
+                    "token": limit_msg,
+                    "finished": false
+                })
+            );
+            break;
+        }
+        iteration += 1;
+        debug!(iteration, "Continuing conversation after tool execution");
+
+        let step = if !pending_native_calls.is_empty() {
+            let calls = std::mem::take(&mut pending_native_calls);
+            run_native_tool_calls(
+                app.clone(),
+                &client,
+                &mut native_conversation,
+                &calls,
+                &step_text,
+                &mut full_response,
+                native_tools,
+                &model_name,
+                temperature,
+                top_p,
+                seed,
+                max_tokens,
+                max_completion_tokens
+            ).await
+        } else {
+            continue_conversation_after_tools(
+                app.clone(),
+                &client,
+                &system_message,
+                &messages,
+                full_response.clone(),
+                &model_name,
+                temperature,
+                top_p,
+                seed,
+                max_tokens,
+                max_completion_tokens
+            ).await
+        };
+
+        match step {
+            Ok(step) => {
+                // Append the continued response (streaming is already handled by the continuation)
+                full_response.push_str(&step.text);
+                pending_native_calls = step.tool_calls;
+
+                // Tool calls written by the continuation itself are executed too
+                needs_continuation = false;
+                if tool_mode == ToolMode::XmlPrompt {
+                    let tool_calls = extract_all_tool_calls_from_xml(&step.text);
+                    needs_continuation = run_xml_tool_calls(
+                        &app,
+                        tool_calls,
+                        &mcp_tools,
+                        &mut executed_tools,
+                        &mut full_response
+                    ).await;
                 }
+                step_text = step.text;
+            }
+            Err(e) => {
+                error!("Failed to continue conversation: {}", e);
+                let error_msg = format!("\n\n[Continuation Error: {}]", e);
+                full_response.push_str(&error_msg);
+
+                let _ = app.emit(
+                    "chat-token",
+                    serde_json::json!({
+                        "token": error_msg,
+                        "finished": false
+                    })
+                );
+                break;
             }
-        } else {
-            debug!("Tool response doesn't contain JSON - no continuation needed");
         }
     }
 
@@ -895,7 +1569,7 @@ async fn continue_conversation_after_tools(
     seed: Option<i64>,
     max_tokens: Option<u32>,
     max_completion_tokens: Option<u32>
-) -> Result<String, String> {
+) -> Result<ContinuationStep, String> {
     debug!("Continuing conversation after tool execution");
 
     // Build new message list with the assistant's response containing tool calls and results
@@ -921,24 +1595,271 @@ async fn continue_conversation_after_tools(
             .into()
     );
 
-    // Create a new streaming request to continue the conversation
-    let mut request_builder = CreateChatCompletionRequestArgs::default();
-    request_builder
-        .model(model_name.to_string())
-        .messages(continuation_messages)
-        .stream(true)
-        .temperature(temperature.unwrap_or(0.7) as f32)
-        .top_p(top_p.unwrap_or(1.0) as f32);
+    stream_continuation(
+        app,
+        client,
+        continuation_messages,
+        &[],
+        model_name,
+        temperature,
+        top_p,
+        seed,
+        max_tokens,
+        max_completion_tokens
+    ).await
+}
 
-    if let Some(seed) = seed {
-        request_builder.seed(seed);
+// Give a tool call an id and tell the UI it started. The id comes back in the
+// tool-call-result event and the <tool_response> tag so the UI can pair them
+fn emit_tool_call_start(app: &AppHandle, name: &str, arguments: &str) -> String {
+    let call_id = Uuid::new_v4().to_string();
+    let _ = app.emit(
+        "tool-call-start",
+        serde_json::json!({
+            "id": call_id,
+            "tool_name": name,
+            "arguments": arguments
+        })
+    );
+    call_id
+}
+
+fn emit_tool_call_result(app: &AppHandle, call_id: &str, result: &Result<String, String>) {
+    let payload = match result {
+        Ok(tool_result) => serde_json::json!({ "id": call_id, "result": tool_result }),
+        Err(e) => serde_json::json!({ "id": call_id, "error": e }),
+    };
+    let _ = app.emit("tool-call-result", payload);
+}
+
+fn tool_response_block(call_id: &str, content: &str) -> String {
+    format!("\n<tool_response id=\"{}\">\n{}\n</tool_response>", call_id, content)
+}
+
+// Execute XML tool calls that haven't run yet, appending their responses to full_response.
+// Returns true if any tool ran, successfully or not
+async fn run_xml_tool_calls(
+    app: &AppHandle,
+    tool_calls: Vec<(String, String)>,
+    tools: &[ChatCompletionTool],
+    executed_tools: &mut std::collections::HashSet<String>,
+    full_response: &mut String
+) -> bool {
+    let mut executed_any = false;
+
+    for (fn_name, fn_args) in tool_calls {
+        // Skip if we already executed this tool call, even if the model
+        // re-emitted it with its argument keys in a different order
+        let tool_signature = tool_call_signature(&fn_name, &fn_args);
+        if executed_tools.contains(&tool_signature) {
+            continue;
+        }
+
+        executed_tools.insert(tool_signature);
+        executed_any = true;
+
+        debug!("Found complete tool call: name={}, args={}", fn_name, fn_args);
+
+        // Parse arguments as JSON for MCP tool call
+        let args_map = parse_tool_arguments(&fn_args);
+
+        // Call the MCP tool, unless the arguments don't fit its schema
+        let call_id = emit_tool_call_start(app, &fn_name, &fn_args);
+        let result = match check_tool_arguments(tools, &fn_name, args_map.as_ref()) {
+            Ok(()) => mcp::call_mcp_tool(app.clone(), fn_name.clone(), args_map).await,
+            Err(e) => {
+                warn!("Rejected tool call {}: {}", fn_name, e);
+                Err(e)
+            }
+        };
+        emit_tool_call_result(app, &call_id, &result);
+
+        // Add tool response in Qwen-Agent format
+        let tool_response_text = match result {
+            Ok(tool_result) => {
+                debug!("Tool {} returned: {}", fn_name, tool_result);
+                tool_response_block(&call_id, &tool_result)
+            }
+            Err(e) => {
+                error!("Tool call failed: {}", e);
+                tool_response_block(&call_id, &format!("Error: {}", e))
+            }
+        };
+        full_response.push_str(&tool_response_text);
+
+        // Emit tool response as streaming content (including XML tags)
+        let _ = app.emit(
+            "chat-token",
+            serde_json::json!({
+                "token": tool_response_text,
+                "finished": false
+            })
+        );
     }
 
-    let effective_max_tokens = max_tokens.unwrap_or(1000).max(100);
-    request_builder.max_tokens(effective_max_tokens);
+    executed_any
+}
 
-    if let Some(max_completion_tokens) = max_completion_tokens {
-        request_builder.max_completion_tokens(max_completion_tokens);
+// Order streamed native tool calls by index, filling in ids the server left out
+fn finish_native_tool_calls(
+    calls: std::collections::BTreeMap<u32, NativeToolCall>
+) -> Vec<NativeToolCall> {
+    calls
+        .into_iter()
+        .map(|(index, mut call)| {
+            if call.id.is_empty() {
+                call.id = format!("call_{}", index);
+            }
+            call
+        })
+        .collect()
+}
+
+// Run the tool calls the model requested in native mode and stream its follow-up answer.
+// The assistant turn and tool results are appended to `conversation` for the next round
+async fn run_native_tool_calls(
+    app: AppHandle,
+    client: &Client<OpenAIConfig>,
+    conversation: &mut Vec<async_openai::types::ChatCompletionRequestMessage>,
+    calls: &[NativeToolCall],
+    assistant_text: &str,
+    full_response: &mut String,
+    tools: &[ChatCompletionTool],
+    model_name: &str,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    seed: Option<i64>,
+    max_tokens: Option<u32>,
+    max_completion_tokens: Option<u32>
+) -> Result<ContinuationStep, String> {
+    let assistant_tool_calls: Vec<ChatCompletionMessageToolCall> = calls
+        .iter()
+        .map(|call| ChatCompletionMessageToolCall {
+            id: call.id.clone(),
+            r#type: ChatCompletionToolType::Function,
+            function: FunctionCall {
+                name: call.name.clone(),
+                arguments: call.arguments.clone(),
+            },
+        })
+        .collect();
+
+    let mut assistant_builder = ChatCompletionRequestAssistantMessageArgs::default();
+    assistant_builder.tool_calls(assistant_tool_calls);
+    if !assistant_text.trim().is_empty() {
+        assistant_builder.content(assistant_text.to_string());
+    }
+    conversation.push(
+        assistant_builder
+            .build()
+            .map_err(|e| format!("Failed to build assistant message with tools: {}", e))?
+            .into()
+    );
+
+    for call in calls {
+        debug!("Native tool call: name={}, args={}", call.name, call.arguments);
+
+        // Render native calls with the same tags as XML mode so the UI shows them identically
+        let arguments_value = serde_json
+            ::from_str::<serde_json::Value>(&call.arguments)
+            .unwrap_or_else(|_| serde_json::Value::String(call.arguments.clone()));
+        let tool_call_text = format!(
+            "\n<tool_call>\n{}\n</tool_call>",
+            serde_json::json!({ "name": call.name, "arguments": arguments_value })
+        );
+        full_response.push_str(&tool_call_text);
+        let _ = app.emit(
+            "chat-token",
+            serde_json::json!({
+                "token": tool_call_text,
+                "finished": false
+            })
+        );
+
+        let args_map = parse_tool_arguments(&call.arguments);
+        let call_id = emit_tool_call_start(&app, &call.name, &call.arguments);
+        let result = match check_tool_arguments(tools, &call.name, args_map.as_ref()) {
+            Ok(()) => mcp::call_mcp_tool(app.clone(), call.name.clone(), args_map).await,
+            Err(e) => {
+                warn!("Rejected tool call {}: {}", call.name, e);
+                Err(e)
+            }
+        };
+        emit_tool_call_result(&app, &call_id, &result);
+
+        let tool_result = match result {
+            Ok(tool_result) => {
+                debug!("Tool {} returned: {}", call.name, tool_result);
+                tool_result
+            }
+            Err(e) => {
+                error!("Tool call failed: {}", e);
+                format!("Error: {}", e)
+            }
+        };
+
+        let tool_response_text = tool_response_block(&call_id, &tool_result);
+        full_response.push_str(&tool_response_text);
+        let _ = app.emit(
+            "chat-token",
+            serde_json::json!({
+                "token": tool_response_text,
+                "finished": false
+            })
+        );
+
+        conversation.push(
+            ChatCompletionRequestToolMessageArgs::default()
+                .tool_call_id(call.id.clone())
+                .content(tool_result)
+                .build()
+                .map_err(|e| format!("Failed to build tool message: {}", e))?
+                .into()
+        );
+    }
+
+    stream_continuation(
+        app,
+        client,
+        conversation.clone(),
+        tools,
+        model_name,
+        temperature,
+        top_p,
+        seed,
+        max_tokens,
+        max_completion_tokens
+    ).await
+}
+
+// Stream a follow-up completion for the given messages, emitting tokens as they arrive.
+// When `tools` is non-empty they're offered natively and any requested calls are returned
+async fn stream_continuation(
+    app: AppHandle,
+    client: &Client<OpenAIConfig>,
+    continuation_messages: Vec<async_openai::types::ChatCompletionRequestMessage>,
+    tools: &[ChatCompletionTool],
+    model_name: &str,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    seed: Option<i64>,
+    max_tokens: Option<u32>,
+    max_completion_tokens: Option<u32>
+) -> Result<ContinuationStep, String> {
+    // Create a new streaming request to continue the conversation
+    let mut request_builder = CreateChatCompletionRequestArgs::default();
+    request_builder.model(model_name.to_string()).messages(continuation_messages).stream(true);
+    apply_sampling_params(
+        &mut request_builder,
+        temperature,
+        top_p,
+        seed,
+        max_tokens,
+        max_completion_tokens
+    );
+
+    if !tools.is_empty() {
+        request_builder.tools(tools.to_vec()).tool_choice(ChatCompletionToolChoiceOption::Auto);
     }
 
     let request = request_builder
@@ -953,6 +1874,8 @@ async fn continue_conversation_after_tools(
         .map_err(|e| format!("Failed to create continuation stream: {}", e))?;
 
     let mut continued_response = String::new();
+    let mut native_tool_calls: std::collections::BTreeMap<u32, NativeToolCall> =
+        std::collections::BTreeMap::new();
 
     // Process the continuation stream
     while let Some(result) = stream.next().await {
@@ -972,6 +1895,23 @@ async fn continue_conversation_after_tools(
                         );
                     }
 
+                    if let Some(tool_call_chunks) = &chat_choice.delta.tool_calls {
+                        for chunk in tool_call_chunks {
+                            let call = native_tool_calls.entry(chunk.index).or_default();
+                            if let Some(id) = &chunk.id {
+                                call.id = id.clone();
+                            }
+                            if let Some(function) = &chunk.function {
+                                if let Some(name) = &function.name {
+                                    call.name.push_str(name);
+                                }
+                                if let Some(arguments) = &function.arguments {
+                                    call.arguments.push_str(arguments);
+                                }
+                            }
+                        }
+                    }
+
                     if let Some(finish_reason) = &chat_choice.finish_reason {
                         debug!("Continuation finished with reason: {:?}", finish_reason);
                         break;
@@ -987,7 +1927,10 @@ async fn continue_conversation_after_tools(
     }
 
     debug!("Continuation response: {}", continued_response);
-    Ok(continued_response)
+    Ok(ContinuationStep {
+        text: continued_response,
+        tool_calls: finish_native_tool_calls(native_tool_calls),
+    })
 }
 
 // RAG-enhanced chat with streaming
@@ -1005,21 +1948,65 @@ pub async fn chat_with_rag_streaming(
     max_tokens: Option<u32>,
     max_completion_tokens: Option<u32>,
     use_rag: Option<bool>,
-    rag_limit: Option<usize>
+    rag_limit: Option<usize>,
+    use_query_expansion: Option<bool>,
+    min_score: Option<f32>,
+    tool_mode: Option<ToolMode>,
+    rag_context_chars: Option<usize>,
+    max_tool_iterations: Option<u32>,
+    preset: Option<String>,
+    use_system_message: Option<bool>
 ) -> Result<String, String> {
+    // The context is added to the system prompt here, so a preset's prompt must be resolved first
+    let system_prompt = match &preset {
+        Some(name) => system_prompt.or(crate::presets::find_preset(name)?.system_prompt),
+        None => system_prompt,
+    };
+
     let mut context_content = String::new();
+    let mut sources: Vec<RagSource> = Vec::new();
+
+    // Retrieved context is only sent in the system message
+    let use_rag = use_rag.unwrap_or(false);
+    if use_rag && use_system_message == Some(false) {
+        let _ = app.emit(
+            "rag-warning",
+            serde_json::json!({
+                "message": "Document context is skipped because system messages are disabled"
+            })
+        );
+    }
 
     // RAG retrieval if enabled
-    if use_rag.unwrap_or(false) {
-        match perform_rag_retrieval(&message, rag_limit.unwrap_or(5)).await {
-            Ok(context) => {
+    if use_rag && use_system_message != Some(false) {
+        match
+            perform_rag_retrieval(
+                &message,
+                rag_limit.unwrap_or(5),
+                &model_name,
+                use_query_expansion.unwrap_or(false),
+                min_score.unwrap_or(DEFAULT_RAG_MIN_SCORE),
+                rag_context_chars.unwrap_or(DEFAULT_RAG_CONTEXT_CHARS)
+            ).await
+        {
+            Ok((context, context_sources)) => {
                 context_content = context;
+                sources = context_sources;
             }
             Err(e) => {
-                error!(error = %e, "RAG retrieval failed");
+                warn!(error = %e, "RAG retrieval failed, answering without document context");
                 // Continue without RAG context rather than failing completely
+                let _ = app.emit(
+                    "rag-warning",
+                    serde_json::json!({
+                        "message": format!("Answering without document context: {}", e)
+                    })
+                );
             }
         }
+
+        // Let the UI render citations before the answer starts streaming
+        let _ = app.emit("rag-sources", serde_json::json!({ "sources": sources }));
     }
 
     // Enhanced system prompt with context
@@ -1049,57 +2036,419 @@ pub async fn chat_with_rag_streaming(
         top_p,
         seed,
         max_tokens,
-        max_completion_tokens
+        max_completion_tokens,
+        tool_mode,
+        max_tool_iterations,
+        preset,
+        use_system_message
     ).await
 }
 
-async fn perform_rag_retrieval(query: &str, limit: usize) -> Result<String, String> {
-    // Create query embedding
+async fn perform_rag_retrieval(
+    query: &str,
+    limit: usize,
+    model_name: &str,
+    use_query_expansion: bool,
+    min_score: f32,
+    context_chars: usize
+) -> Result<(String, Vec<RagSource>), String> {
+    crate::ovms::ensure_embedding_model_loaded().await?;
+
+    let limit = limit.clamp(1, MAX_RAG_CONTEXT_DOCS);
+    let context_chars = context_chars.clamp(1, MAX_RAG_CONTEXT_CHARS);
+
+    // Optionally ask the loaded model for paraphrases to widen the search
+    let mut queries = vec![query.to_string()];
+    if use_query_expansion {
+        match expand_query(query, model_name).await {
+            Ok(paraphrases) => {
+                debug!(paraphrases = ?paraphrases, "Query expansion generated paraphrases");
+                queries.extend(paraphrases);
+            }
+            Err(e) => {
+                warn!(error = %e, "Query expansion failed, using original query only");
+            }
+        }
+    }
+
+    // Create query embeddings (original query first)
     let embedding_service = crate::rag::embeddings::EmbeddingService::new();
-    let query_embedding = embedding_service.create_single_embedding(query.to_string()).await?;
+    let query_embeddings = embedding_service.create_embeddings(queries.clone()).await?;
 
-    // Search similar documents
+    // Search similar documents for every query and merge by document id
     let vector_store = crate::rag::vector_store::VectorStore::new()?;
-    let search_results = vector_store.search_similar(&query_embedding, limit * 2)?; // Get more for reranking
+    let mut merged: HashMap<String, crate::rag::SearchResult> = HashMap::new();
+    let mut original_hits = 0;
+
+    for (i, query_embedding) in query_embeddings.iter().enumerate() {
+        let results = vector_store.search_similar(query_embedding, limit * 2)?; // Get more for reranking
+        if i == 0 {
+            original_hits = results.len();
+        }
+
+        for result in results {
+            match merged.get_mut(&result.document.id) {
+                Some(existing) => {
+                    // Keep the best similarity seen across all queries
+                    if result.score > existing.score {
+                        existing.score = result.score;
+                    }
+                }
+                None => {
+                    merged.insert(result.document.id.clone(), result);
+                }
+            }
+        }
+    }
+
+    if queries.len() > 1 {
+        info!(
+            queries = queries.len(),
+            original_hits = original_hits,
+            expanded_hits = merged.len(),
+            added_by_expansion = merged.len().saturating_sub(original_hits),
+            "Query expansion recall"
+        );
+    }
+
+    let mut search_results: Vec<crate::rag::SearchResult> = merged.into_values().collect();
+    search_results.sort_by(|a, b|
+        b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+    );
+
+    // Drop weak matches so irrelevant chunks never reach the context
+    let candidate_count = search_results.len();
+    search_results.retain(|result| result.score >= min_score);
+    if search_results.len() < candidate_count {
+        debug!(
+            min_score = min_score,
+            kept = search_results.len(),
+            dropped = candidate_count - search_results.len(),
+            "Filtered RAG results below similarity threshold"
+        );
+    }
 
     if search_results.is_empty() {
-        return Ok(String::new());
+        return Ok((String::new(), Vec::new()));
     }
 
     // Rerank results
     let reranker = crate::rag::reranker::RerankerService::new();
-    let reranked_results = reranker.rerank(query, search_results).await?;
+    let reranked_results = reranker.rerank(query, search_results, &Default::default()).await?;
+
+    // Use the caller's limit (already capped) for how many reranked results become context
+    let context_results: Vec<&crate::rag::SearchResult> = reranked_results
+        .iter()
+        .take(limit)
+        .collect();
+
+    let sources = context_results
+        .iter()
+        .map(|result| RagSource {
+            document_id: result.document.id.clone(),
+            title: result.document.title.clone(),
+            file_path: result.document.file_path.clone(),
+            score: result.rerank_score.unwrap_or(result.score),
+        })
+        .collect();
 
     // Build context from top results
-    let context_content = reranked_results
+    let context_content = context_results
         .iter()
-        .take(std::cmp::min(3, limit)) // Use top 3 results or limit, whichever is smaller
         .enumerate()
         .map(|(i, result)| {
             format!(
                 "Source {}: {}\nContent: {}\nRelevance Score: {:.2}\n---",
                 i + 1,
                 result.document.title,
-                truncate_content(&result.document.content, 500), // Limit content length
+                truncate_content(&result.document.content, context_chars), // Limit content length
                 result.rerank_score.unwrap_or(result.score)
             )
         })
         .collect::<Vec<_>>()
         .join("\n");
 
-    Ok(context_content)
+    Ok((context_content, sources))
+}
+
+// Ask the loaded model for a few paraphrases of the query
+async fn expand_query(query: &str, model_name: &str) -> Result<Vec<String>, String> {
+    let config = OpenAIConfig::new()
+        .with_api_key("unused")
+        .with_api_base(format!("{}/v3", crate::settings::ovms_base_url()));
+    let client = Client::with_config(config);
+
+    let prompt = format!(
+        "Rewrite the following search query in 3 different ways that could help find relevant documents. \
+        Return only the rewritten queries, one per line, without numbering or explanations.\n\nQuery: {}",
+        query
+    );
+
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(model_name.to_string())
+        .messages(
+            vec![
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(prompt)
+                    .build()
+                    .map_err(|e| format!("Failed to build user message: {}", e))?
+                    .into()
+            ]
+        )
+        .temperature(0.3)
+        .max_tokens(200u32)
+        .build()
+        .map_err(|e| format!("Failed to build query expansion request: {}", e))?;
+
+    let response = client
+        .chat()
+        .create(request).await
+        .map_err(|e| format!("Query expansion request failed: {}", e))?;
+
+    let content = response.choices
+        .first()
+        .and_then(|choice| choice.message.content.clone())
+        .unwrap_or_default();
+
+    Ok(parse_query_paraphrases(&content, query))
+}
+
+// Drop a leading "1. ", "2) ", "- " or "* " list marker, leaving content like "2024 tax
+// rules" or "-5 offset" alone because no whitespace follows the digits or dash
+fn strip_list_marker(line: &str) -> &str {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker_len = if digits > 0 && line[digits..].starts_with(|c: char| matches!(c, '.' | ')')) {
+        digits + 1
+    } else if line.starts_with(|c: char| matches!(c, '-' | '*')) {
+        1
+    } else {
+        return line;
+    };
+
+    let rest = &line[marker_len..];
+    if rest.starts_with(char::is_whitespace) {
+        rest.trim_start()
+    } else {
+        line
+    }
+}
+
+fn parse_query_paraphrases(text: &str, original_query: &str) -> Vec<String> {
+    let mut paraphrases: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        // Strip list markers and surrounding quotes
+        let cleaned = strip_list_marker(line.trim())
+            .trim_matches('"')
+            .trim();
+
+        if cleaned.is_empty() || cleaned.eq_ignore_ascii_case(original_query.trim()) {
+            continue;
+        }
+
+        if !paraphrases.iter().any(|p| p.eq_ignore_ascii_case(cleaned)) {
+            paraphrases.push(cleaned.to_string());
+        }
+
+        if paraphrases.len() == 3 {
+            break;
+        }
+    }
+
+    paraphrases
+}
+
+// Parse tool call arguments into the map MCP expects, dropping nulls
+fn parse_tool_arguments(arguments: &str) -> Option<serde_json::Map<String, serde_json::Value>> {
+    if arguments.trim().is_empty() {
+        return Some(serde_json::Map::new());
+    }
+
+    match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(arguments) {
+        Ok(mut map) => {
+            // Remove null values as MCP tools don't handle them well
+            map.retain(|_k, v| !v.is_null());
+            Some(map)
+        }
+        Err(e) => {
+            warn!("Failed to parse tool arguments: {}", e);
+            None
+        }
+    }
+}
+
+fn matches_json_type(value: &serde_json::Value, json_type: &str) -> bool {
+    match json_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+// Accepted types of a schema property, `"type": "string"` or `"type": ["string", "null"]`
+fn schema_types(schema: &serde_json::Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(serde_json::Value::String(json_type)) => vec![json_type.as_str()],
+        Some(serde_json::Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// One line per parameter, e.g. `city (string, required)`, to show the model what to send
+fn describe_tool_parameters(schema: &serde_json::Value) -> String {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|name| name.as_str()).collect())
+        .unwrap_or_default();
+
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return "no parameters".to_string();
+    };
+    if properties.is_empty() {
+        return "no parameters".to_string();
+    }
+
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let mut details = schema_types(property).join(" or ");
+            if details.is_empty() {
+                details = "any".to_string();
+            }
+            if required.contains(&name.as_str()) {
+                details.push_str(", required");
+            }
+            if let Some(values) = property.get("enum").and_then(|e| e.as_array()) {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                details.push_str(&format!(", one of {}", values.join(", ")));
+            }
+            format!("{} ({})", name, details)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
+// Problems with the top-level arguments of a call: missing required keys, unknown keys when
+// the schema forbids them, wrong value types and values outside an enum
+fn tool_argument_problems(
+    schema: &serde_json::Value,
+    arguments: &serde_json::Map<String, serde_json::Value>
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let properties = schema.get("properties").and_then(|p| p.as_object());
+
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        for name in required.iter().filter_map(|name| name.as_str()) {
+            if !arguments.contains_key(name) {
+                problems.push(format!("missing required parameter '{}'", name));
+            }
+        }
+    }
+
+    for (name, value) in arguments {
+        let Some(property) = properties.and_then(|p| p.get(name)) else {
+            if schema.get("additionalProperties") == Some(&serde_json::Value::Bool(false)) {
+                problems.push(format!("unknown parameter '{}'", name));
+            }
+            continue;
+        };
+
+        let types = schema_types(property);
+        if !types.is_empty() && !types.iter().any(|t| matches_json_type(value, t)) {
+            problems.push(format!("parameter '{}' must be {}, got {}", name, types.join(" or "), value));
+            continue;
+        }
+
+        if let Some(values) = property.get("enum").and_then(|e| e.as_array()) {
+            if !values.contains(value) {
+                problems.push(format!("parameter '{}' has value {} which is not allowed", name, value));
+            }
+        }
+    }
+
+    problems
+}
+
+// Check a call against the tool's declared input schema before it reaches the MCP server, so a
+// model that got the arguments wrong is told what to send instead of seeing a server error.
+// Unknown tools are passed through and fail in the MCP layer as before
+fn check_tool_arguments(
+    tools: &[ChatCompletionTool],
+    name: &str,
+    arguments: Option<&serde_json::Map<String, serde_json::Value>>
+) -> Result<(), String> {
+    let Some(schema) = tools
+        .iter()
+        .find(|tool| tool.function.name == name)
+        .and_then(|tool| tool.function.parameters.as_ref()) else {
+        return Ok(());
+    };
+
+    let problems = match arguments {
+        Some(arguments) => tool_argument_problems(schema, arguments),
+        None => vec!["arguments are not a valid JSON object".to_string()],
+    };
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    Err(
+        format!(
+            "Invalid arguments for tool '{}': {}. Expected parameters: {}. Call the tool again with corrected arguments.",
+            name,
+            problems.join("; "),
+            describe_tool_parameters(schema)
+        )
+    )
+}
+
+// Identify a tool call by name and its arguments re-serialized with sorted keys, so
+// `{"a":1,"b":2}` and `{ "b": 2, "a": 1 }` count as the same call
+fn tool_call_signature(name: &str, arguments: &str) -> String {
+    fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                serde_json::Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sort_keys(value)))
+                        .collect()
+                )
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(sort_keys).collect())
+            }
+            other => other,
+        }
+    }
+
+    let normalized = match serde_json::from_str::<serde_json::Value>(arguments) {
+        Ok(value) => sort_keys(value).to_string(),
+        // Unparseable arguments can only be compared as written
+        Err(_) => arguments.trim().to_string(),
+    };
+    format!("{}:{}", name, normalized)
+}
+
+const TOOL_CALL_OPEN: &str = "<tool_call>";
+const TOOL_CALL_CLOSE: &str = "</tool_call>";
+
 fn extract_all_tool_calls_from_xml(text: &str) -> Vec<(String, String)> {
     let mut tool_calls = Vec::new();
-    let mut search_start = 0;
-
-    while let Some(start) = text[search_start..].find("<tool_call>") {
-        let actual_start = search_start + start;
-        if let Some(end) = text[actual_start..].find("</tool_call>") {
-            let actual_end = actual_start + end;
-            let tool_call_content = &text[actual_start + 11..actual_end]; // 11 is length of "<tool_call>"
+    let mut rest = text;
 
+    // Walk the text with split_once so every slice lands on a char boundary,
+    // even when the model puts multi-byte text inside or around the tags
+    while let Some((_, after_open)) = rest.split_once(TOOL_CALL_OPEN) {
+        if let Some((tool_call_content, after_close)) = after_open.split_once(TOOL_CALL_CLOSE) {
             // Parse JSON inside the tool_call tags
             if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(tool_call_content) {
                 if let (Some(name), Some(args)) = (parsed.get("name"), parsed.get("arguments")) {
@@ -1111,7 +2460,7 @@ fn extract_all_tool_calls_from_xml(text: &str) -> Vec<(String, String)> {
                     }
                 }
             }
-            search_start = actual_end + 12; // 12 is length of "</tool_call>"
+            rest = after_close;
         } else {
             break;
         }
@@ -1121,8 +2470,8 @@ fn extract_all_tool_calls_from_xml(text: &str) -> Vec<(String, String)> {
 }
 
 fn has_incomplete_tool_call(text: &str) -> bool {
-    if let Some(start) = text.rfind("<tool_call>") {
-        if let Some(_end) = text[start..].find("</tool_call>") {
+    if let Some(start) = text.rfind(TOOL_CALL_OPEN) {
+        if let Some(_end) = text[start..].find(TOOL_CALL_CLOSE) {
             return false; // Complete tool call found
         }
         return true; // Incomplete tool call
@@ -1132,14 +2481,19 @@ fn has_incomplete_tool_call(text: &str) -> bool {
 
 fn check_if_continuation_needed(text: &str) -> bool {
     // Always continue conversation when any tool_response is found, regardless of content
-    text.contains("<tool_response>")
+    text.contains("<tool_response")
 }
 
 fn truncate_content(content: &str, max_length: usize) -> String {
     if content.len() <= max_length {
         content.to_string()
     } else {
-        let truncated = &content[..max_length];
+        // Back up to a char boundary so multi-byte text can't panic the slice
+        let mut end = max_length;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        let truncated = &content[..end];
         // Try to find the last complete word
         if let Some(last_space) = truncated.rfind(' ') {
             format!("{}...", &truncated[..last_space])
@@ -1148,3 +2502,271 @@ fn truncate_content(content: &str, max_length: usize) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_openai::types::FunctionObject;
+
+    fn sample_tool() -> ChatCompletionTool {
+        ChatCompletionTool {
+            r#type: ChatCompletionToolType::Function,
+            function: FunctionObject {
+                name: "time_get_current_time".to_string(),
+                description: Some("Get the current time".to_string()),
+                parameters: Some(serde_json::json!({ "type": "object" })),
+                strict: Some(false),
+            },
+        }
+    }
+
+    fn message(id: &str, timestamp: i64) -> ChatMessage {
+        ChatMessage {
+            id: id.to_string(),
+            role: "user".to_string(),
+            content: id.to_string(),
+            timestamp,
+            tokens_per_second: None,
+            is_error: None,
+        }
+    }
+
+    fn session(id: &str, messages: Vec<ChatMessage>) -> ChatSession {
+        ChatSession {
+            id: id.to_string(),
+            title: id.to_string(),
+            created_at: 0,
+            updated_at: 0,
+            model_id: None,
+            messages,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn test_expired_session_ids_keeps_pinned() {
+        let mut old = session("old", Vec::new());
+        old.updated_at = 100;
+        let mut old_pinned = session("old-pinned", Vec::new());
+        old_pinned.updated_at = 100;
+        old_pinned.pinned = true;
+        let mut recent = session("recent", Vec::new());
+        recent.updated_at = 1_000;
+
+        let sessions: HashMap<String, ChatSession> = [old, old_pinned, recent]
+            .into_iter()
+            .map(|session| (session.id.clone(), session))
+            .collect();
+
+        assert_eq!(expired_session_ids(&sessions, 500, true), vec!["old".to_string()]);
+
+        let mut expired = expired_session_ids(&sessions, 500, false);
+        expired.sort();
+        assert_eq!(expired, vec!["old".to_string(), "old-pinned".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_session_messages_orders_and_restamps() {
+        let mut target = session("target", vec![message("a", 10), message("c", 30)]);
+        let source = session("source", vec![message("b", 20), message("a", 40)]);
+
+        merge_session_messages(&mut target, source);
+
+        let contents: Vec<&str> = target.messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["a", "b", "c", "a"]);
+
+        let mut ids: Vec<&str> = target.messages
+            .iter()
+            .map(|m| m.id.as_str())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn test_fork_messages_stops_at_message() {
+        let messages = vec![message("a", 10), message("b", 20), message("c", 30)];
+
+        let forked = fork_messages(&messages, Some("b")).unwrap();
+        let contents: Vec<&str> = forked
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["a", "b"]);
+        assert!(forked.iter().all(|m| m.id != "a" && m.id != "b"));
+
+        assert_eq!(fork_messages(&messages, None).unwrap().len(), 3);
+        assert!(fork_messages(&messages, Some("missing")).is_none());
+    }
+
+    #[test]
+    fn test_compute_session_stats() {
+        let mut reply = message("hello there friend", 20);
+        reply.role = "assistant".to_string();
+        reply.tokens_per_second = Some(10.0);
+        let mut slow_reply = message("ok", 30);
+        slow_reply.role = "assistant".to_string();
+        slow_reply.tokens_per_second = Some(20.0);
+
+        let stats = compute_session_stats(&[message("hi", 10), reply, slow_reply]);
+
+        assert_eq!(stats.message_count, 3);
+        assert_eq!(stats.total_characters, 2 + 18 + 2);
+        assert_eq!(stats.by_role["user"].message_count, 1);
+        assert_eq!(stats.by_role["assistant"].message_count, 2);
+        assert_eq!(stats.average_tokens_per_second, Some(15.0));
+        assert_eq!(approximate_token_count("hello there friend"), 5);
+        assert_eq!(approximate_token_count(""), 0);
+    }
+
+    #[test]
+    fn test_tool_call_signature_ignores_key_order() {
+        let calls = vec![
+            ("weather_lookup", r#"{"city": "Paris", "units": {"temp": "c", "wind": "kmh"}}"#),
+            ("weather_lookup", r#"{ "units": { "wind": "kmh", "temp": "c" }, "city": "Paris" }"#)
+        ];
+
+        let mut executed_tools = std::collections::HashSet::new();
+        let executions = calls
+            .iter()
+            .filter(|(name, args)| executed_tools.insert(tool_call_signature(name, args)))
+            .count();
+        assert_eq!(executions, 1);
+
+        assert_ne!(
+            tool_call_signature("weather_lookup", r#"{"city": "Paris"}"#),
+            tool_call_signature("weather_lookup", r#"{"city": "Rome"}"#)
+        );
+    }
+
+    #[test]
+    fn test_truncate_content_respects_char_boundaries() {
+        assert_eq!(truncate_content("short", 500), "short");
+        assert_eq!(truncate_content("hello brave new world", 12), "hello brave...");
+        // "é" is two bytes, cutting at byte 3 would split it
+        assert_eq!(truncate_content("caféterias", 4), "caf...");
+    }
+
+    #[test]
+    fn test_extract_tool_calls_with_multibyte_content() {
+        let text = "先查一下 <tool_call>{\"name\": \"search\", \"arguments\": {\"query\": \"天气 ☀️\"}}</tool_call> 然后 \
+            <tool_call>{\"name\": \"time_get_current_time\", \"arguments\": {}}</tool_call>完成 <tool_call>未完";
+
+        let tool_calls = extract_all_tool_calls_from_xml(text);
+
+        assert_eq!(tool_calls.len(), 2);
+        assert_eq!(tool_calls[0].0, "search");
+        assert_eq!(tool_calls[0].1, r#"{"query":"天气 ☀️"}"#);
+        assert_eq!(tool_calls[1].0, "time_get_current_time");
+        assert!(has_incomplete_tool_call(text));
+    }
+
+    #[test]
+    fn test_build_system_message_without_tools() {
+        let message = build_system_message(Some("Be brief.".to_string()), &[]);
+        assert_eq!(message, "Be brief.");
+    }
+
+    #[test]
+    fn test_build_system_message_with_tools() {
+        let message = build_system_message(Some("Be brief.".to_string()), &[sample_tool()]);
+        assert!(message.starts_with("Be brief.\n\n# Tools"));
+        assert!(message.contains("<tools>\ntime_get_current_time("));
+        assert!(message.contains(") - Get the current time\n</tools>"));
+        assert!(message.contains("<tool_call>"));
+    }
+
+    #[test]
+    fn test_build_system_message_default_prompt() {
+        let message = build_system_message(None, &[]);
+        assert_eq!(message, DEFAULT_SYSTEM_PROMPT);
+    }
+
+    #[test]
+    fn test_migrate_legacy_sessions() {
+        let dir = std::env::temp_dir().join(format!("sparrow-sessions-{}", Uuid::new_v4()));
+        let sessions_dir = dir.join("sessions");
+        fs::create_dir_all(&sessions_dir).unwrap();
+
+        let session_id = Uuid::new_v4().to_string();
+        let session = ChatSession {
+            id: session_id.clone(),
+            title: "Old chat".to_string(),
+            created_at: 1,
+            updated_at: 2,
+            model_id: None,
+            messages: vec![message("a", 1), message("b", 2)],
+            pinned: true,
+        };
+        let legacy = ChatSessionsStorage {
+            sessions: HashMap::from([(session_id.clone(), session)]),
+            active_session_id: Some(session_id.clone()),
+        };
+        let legacy_path = dir.join("chat_sessions.json");
+        fs::write(&legacy_path, serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        assert_eq!(migrate_legacy_sessions(&legacy_path, &sessions_dir).unwrap(), 1);
+
+        let index = read_session_index(&sessions_dir).unwrap();
+        assert!(index.session_ids.contains(&session_id));
+        assert_eq!(index.active_session_id.as_deref(), Some(session_id.as_str()));
+
+        let migrated = read_session_file(&session_file_path(&sessions_dir, &session_id).unwrap()).unwrap();
+        assert_eq!(migrated.title, "Old chat");
+        assert_eq!(migrated.messages.len(), 2);
+        assert!(migrated.pinned);
+
+        assert!(!legacy_path.exists());
+        assert!(dir.join("chat_sessions.json.migrated").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_query_paraphrases_keeps_leading_numbers() {
+        let text = "1. 2024 tax rules for freelancers\n2) -5 offset in the config\n- \"tax rules\"\n* tax rules";
+        let paraphrases = parse_query_paraphrases(text, "tax rules 2024");
+        assert_eq!(paraphrases, vec!["2024 tax rules for freelancers", "-5 offset in the config", "tax rules"]);
+
+        assert_eq!(parse_query_paraphrases("2024 tax rules", "query"), vec!["2024 tax rules"]);
+        assert_eq!(parse_query_paraphrases("3.5 release notes", "query"), vec!["3.5 release notes"]);
+    }
+
+    #[test]
+    fn test_check_tool_arguments_against_schema() {
+        let mut tool = sample_tool();
+        tool.function.parameters = Some(
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "timezone": { "type": "string" },
+                    "format": { "type": "string", "enum": ["12h", "24h"] }
+                },
+                "required": ["timezone"],
+                "additionalProperties": false
+            })
+        );
+        let tools = vec![tool];
+        let check = |args: &str| {
+            check_tool_arguments(&tools, "time_get_current_time", parse_tool_arguments(args).as_ref())
+        };
+
+        assert!(check(r#"{"timezone": "Asia/Singapore", "format": "24h"}"#).is_ok());
+
+        let error = check(r#"{"tz": 8}"#).unwrap_err();
+        assert!(error.contains("missing required parameter 'timezone'"));
+        assert!(error.contains("unknown parameter 'tz'"));
+        assert!(error.contains("timezone (string, required)"));
+
+        assert!(check(r#"{"timezone": 8}"#).unwrap_err().contains("must be string"));
+        assert!(check(r#"{"timezone": "UTC", "format": "am"}"#).is_err());
+        assert!(check("not json").unwrap_err().contains("not a valid JSON object"));
+
+        // Tools without a known schema are left to the MCP server
+        assert!(check_tool_arguments(&tools, "other_tool", None).is_ok());
+    }
+}
@@ -0,0 +1,93 @@
+use serde::ser::{ Serialize, SerializeStruct, Serializer };
+use std::fmt;
+
+/// Error returned by Tauri commands, serialized as `{ "code": "...", "message": "..." }`
+/// so the UI can switch on `code` and still show `message` to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum SparrowError {
+    /// A model, file or directory that was asked for doesn't exist
+    NotFound(String),
+    /// A request to Hugging Face, GitHub or OVMS failed or returned an error status
+    Network(String),
+    /// Reading or writing local files failed
+    Io(String),
+    /// A response or file couldn't be parsed
+    Parse(String),
+    /// OVMS is missing, not running or refused the request
+    Ovms(String),
+    /// The arguments passed to the command are invalid
+    InvalidInput(String),
+    /// The user cancelled the operation
+    Cancelled(String),
+}
+
+impl SparrowError {
+    /// Machine-readable code sent to the frontend
+    pub fn code(&self) -> &'static str {
+        match self {
+            SparrowError::NotFound(_) => "not_found",
+            SparrowError::Network(_) => "network",
+            SparrowError::Io(_) => "io",
+            SparrowError::Parse(_) => "parse",
+            SparrowError::Ovms(_) => "ovms",
+            SparrowError::InvalidInput(_) => "invalid_input",
+            SparrowError::Cancelled(_) => "cancelled",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            | SparrowError::NotFound(message)
+            | SparrowError::Network(message)
+            | SparrowError::Io(message)
+            | SparrowError::Parse(message)
+            | SparrowError::Ovms(message)
+            | SparrowError::InvalidInput(message)
+            | SparrowError::Cancelled(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for SparrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for SparrowError {}
+
+// Lets functions that still return Result<_, String> use `?` on SparrowError
+impl From<SparrowError> for String {
+    fn from(error: SparrowError) -> Self {
+        error.to_string()
+    }
+}
+
+impl Serialize for SparrowError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SparrowError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_code_and_message() {
+        let error = SparrowError::NotFound("Model directory not found: /tmp/model".to_string());
+        let value = serde_json::to_value(&error).unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "code": "not_found",
+                "message": "Model directory not found: /tmp/model"
+            })
+        );
+        assert_eq!(error.to_string(), "Model directory not found: /tmp/model");
+    }
+}
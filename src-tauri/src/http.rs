@@ -0,0 +1,38 @@
+use crate::errors::SparrowError;
+use std::time::Duration;
+
+/// Probes and small requests to the local OVMS server
+pub const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+/// Hugging Face API calls: search, model info and file listings
+pub const API_TIMEOUT: Duration = Duration::from_secs(30);
+/// Single model file downloads and the warmup completion, which waits for graph compilation
+pub const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+/// OVMS config reloads, which only answer once every model in the new config has loaded and
+/// compiled. A large LLM or a first compile on GPU/NPU can take minutes
+pub const RELOAD_TIMEOUT: Duration = Duration::from_secs(600);
+/// The OVMS release archive, which is several hundred megabytes
+pub const OVMS_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+const USER_AGENT: &str = "SparrowAI/1.0";
+
+/// Build an HTTP client whose requests fail after `timeout` instead of hanging
+pub fn http_client(timeout: Duration) -> Result<reqwest::Client, SparrowError> {
+    reqwest::Client
+        ::builder()
+        .user_agent(USER_AGENT)
+        .timeout(timeout)
+        .build()
+        .map_err(|e| SparrowError::Network(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Describe a failed request, calling out timeouts and refused connections separately
+/// from other failures so the UI doesn't show the same generic message for all of them
+pub fn request_error_message(context: &str, error: &reqwest::Error) -> String {
+    if error.is_timeout() {
+        format!("{}: request timed out", context)
+    } else if error.is_connect() {
+        format!("{}: could not connect to the server", context)
+    } else {
+        format!("{}: {}", context, error)
+    }
+}
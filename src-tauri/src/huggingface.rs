@@ -1,10 +1,22 @@
+use crate::errors::SparrowError;
 use serde::{ Deserialize, Serialize };
 use tracing::{ info, warn, error };
+use std::collections::{ BTreeMap, HashMap };
 use std::path::PathBuf;
+use std::sync::Mutex;
 use tauri::Emitter;
 use tokio::io::AsyncWriteExt;
 
-#[derive(Debug, Serialize, Deserialize)]
+// How long cached model metadata stays valid
+const HF_CACHE_TTL_SECS: i64 = 3600;
+
+// How many models are checked against the Hub at the same time
+const UPDATE_CHECK_CONCURRENCY: usize = 4;
+
+// Model metadata saved next to the downloaded files so it's available offline
+const LOCAL_MODEL_INFO_FILE: &str = ".sparrow_model_info.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     pub id: String,
     pub author: Option<String>,
@@ -20,7 +32,7 @@ pub struct ModelInfo {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
     pub models: Vec<ModelInfo>,
-    pub total_count: Option<u64>,
+    pub has_more: bool,
 }
 
 // Hugging Face API response structures
@@ -29,7 +41,8 @@ struct HfModelInfo {
     pub id: String,
     pub author: Option<String>,
     pub sha: Option<String>,
-    #[serde(rename = "pipeline-tag")]
+    // The API returns "pipeline_tag"; older responses used "pipeline-tag"
+    #[serde(alias = "pipeline-tag")]
     pub pipeline_tag: Option<String>,
     pub tags: Option<Vec<String>>,
     pub downloads: Option<u64>,
@@ -41,6 +54,95 @@ struct HfModelInfo {
     pub last_modified: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelDownloadResult {
+    pub model_id: String,
+    pub message: String,
+    pub target_dir: String,
+    pub downloaded_files: Vec<String>,
+    pub failed_files: Vec<String>,
+    pub errors: Vec<String>,
+    pub total_bytes: u64,
+}
+
+// Per-file results of downloading a set of repository files
+#[derive(Debug, Default)]
+struct DownloadOutcome {
+    downloaded_files: Vec<String>,
+    failed_files: Vec<String>,
+    errors: Vec<String>,
+    total_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedModelInfo {
+    cached_at: i64,
+    info: ModelInfo,
+}
+
+fn get_hf_cache_dir() -> Result<PathBuf, String> {
+    Ok(crate::paths::sparrow_data_dir()?.join("hf_cache"))
+}
+
+fn hf_cache_path(model_id: &str) -> Result<PathBuf, String> {
+    Ok(get_hf_cache_dir()?.join(format!("{}.json", model_id.replace('/', "--"))))
+}
+
+// Return cached metadata for the model if it is younger than the TTL
+fn read_cached_model_info(model_id: &str) -> Option<ModelInfo> {
+    let path = hf_cache_path(model_id).ok()?;
+    let contents = std::fs::read_to_string(path).ok()?;
+    let cached: CachedModelInfo = serde_json::from_str(&contents).ok()?;
+
+    let age = chrono::Utc::now().timestamp() - cached.cached_at;
+    if (0..HF_CACHE_TTL_SECS).contains(&age) {
+        Some(cached.info)
+    } else {
+        None
+    }
+}
+
+fn write_cached_model_info(info: &ModelInfo) -> Result<(), String> {
+    let cache_dir = get_hf_cache_dir()?;
+    std::fs
+        ::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create HF cache directory: {}", e))?;
+
+    let cached = CachedModelInfo {
+        cached_at: chrono::Utc::now().timestamp(),
+        info: info.clone(),
+    };
+    let contents = serde_json
+        ::to_string(&cached)
+        .map_err(|e| format!("Failed to serialize cached model info: {}", e))?;
+    std::fs
+        ::write(hf_cache_path(&info.id)?, contents)
+        .map_err(|e| format!("Failed to write cached model info: {}", e))
+}
+
+#[tauri::command]
+pub async fn clear_hf_cache() -> Result<usize, SparrowError> {
+    let cache_dir = get_hf_cache_dir().map_err(SparrowError::Io)?;
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    let entries = std::fs
+        ::read_dir(&cache_dir)
+        .map_err(|e| SparrowError::Io(format!("Failed to read HF cache directory: {}", e)))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_cache_file = path.extension().and_then(|ext| ext.to_str()) == Some("json");
+        if is_cache_file && std::fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+
+    info!(removed = removed, "Cleared Hugging Face metadata cache");
+    Ok(removed)
+}
+
 #[derive(Debug, Deserialize)]
 struct HfFileInfo {
     #[serde(rename = "path")]
@@ -48,6 +150,116 @@ struct HfFileInfo {
     #[serde(rename = "type")]
     pub file_type: String,
     pub size: Option<u64>,
+    // Git object id, changes whenever the file content changes
+    pub oid: Option<String>,
+    // Set for files stored in Git LFS, which covers the model weights
+    pub lfs: Option<HfLfsInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HfLfsInfo {
+    // SHA-256 of the file content
+    pub oid: String,
+}
+
+// Files SparrowAI writes into the model directory itself, never reported as extra
+const BOOKKEEPING_FILES: &[&str] = &[
+    ".commit_id",
+    FILE_MANIFEST_NAME,
+    LOCAL_MODEL_INFO_FILE,
+    "graph.pbtxt",
+];
+
+// Compiled model cache OVMS keeps inside the model directory
+const OVMS_CACHE_DIR: &str = ".ovms_cache";
+
+// Whether a path relative to the model directory was written by SparrowAI or OVMS
+// rather than downloaded
+fn is_generated_file(path: &str) -> bool {
+    BOOKKEEPING_FILES.contains(&path) ||
+        path.strip_prefix(OVMS_CACHE_DIR).is_some_and(|rest| rest.starts_with('/'))
+}
+
+// Records the object id of every downloaded file so updates can skip unchanged ones
+const FILE_MANIFEST_NAME: &str = ".file_manifest.json";
+
+fn read_file_manifest(model_dir: &PathBuf) -> HashMap<String, String> {
+    std::fs
+        ::read_to_string(model_dir.join(FILE_MANIFEST_NAME))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn record_downloaded_files(model_dir: &PathBuf, files: &[&HfFileInfo], downloaded: &[String]) {
+    let mut manifest = read_file_manifest(model_dir);
+    for file in files {
+        if let Some(oid) = &file.oid {
+            if downloaded.contains(&file.path) {
+                manifest.insert(file.path.clone(), oid.clone());
+            }
+        }
+    }
+
+    let result = serde_json
+        ::to_string_pretty(&manifest)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::write(model_dir.join(FILE_MANIFEST_NAME), contents).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to write file manifest");
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActiveFileProgress {
+    path: String,
+    downloaded_bytes: u64,
+    total_bytes: u64,
+    progress: u32,
+}
+
+// Byte counts shared by every file of one model download, so each progress event
+// describes all files in flight and not just the one that emitted it
+#[derive(Default)]
+struct DownloadProgress {
+    completed_bytes: u64,
+    active_files: Vec<ActiveFileProgress>,
+}
+
+impl DownloadProgress {
+    fn start_file(&mut self, path: &str, total_bytes: u64) {
+        self.active_files.push(ActiveFileProgress {
+            path: path.to_string(),
+            downloaded_bytes: 0,
+            total_bytes,
+            progress: 0,
+        });
+    }
+
+    fn update_file(&mut self, path: &str, downloaded_bytes: u64, total_bytes: u64) {
+        if let Some(file) = self.active_files.iter_mut().find(|file| file.path == path) {
+            file.downloaded_bytes = downloaded_bytes;
+            file.total_bytes = total_bytes;
+            file.progress = if total_bytes > 0 {
+                ((((downloaded_bytes as f64) / (total_bytes as f64)) * 100.0) as u32).min(100)
+            } else {
+                0
+            };
+        }
+    }
+
+    // Drop the file from the active list, counting its bytes if it finished
+    fn finish_file(&mut self, path: &str, downloaded_bytes: Option<u64>) {
+        self.active_files.retain(|file| file.path != path);
+        self.completed_bytes += downloaded_bytes.unwrap_or(0);
+    }
+
+    fn downloaded_bytes(&self) -> u64 {
+        self.completed_bytes + self.active_files.iter().map(|file| file.downloaded_bytes).sum::<u64>()
+    }
 }
 
 // Memory-efficient streaming file download
@@ -59,7 +271,7 @@ async fn download_single_file(
     model_id: &str,
     file_index: usize,
     total_files: usize,
-    total_downloaded_so_far: u64,
+    progress: &Mutex<DownloadProgress>,
     total_estimated_size: u64,
     app: &tauri::AppHandle
 ) -> Result<u64, String> {
@@ -74,11 +286,8 @@ async fn download_single_file(
     }
 
     // Start the request
-    let response = client
-        .get(file_url)
-        .header("User-Agent", "SparrowAI/1.0")
-        .send().await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_hf_request(client.get(file_url)).await
+        .map_err(|e| crate::http::request_error_message("Request failed", &e))?;
 
     if !response.status().is_success() {
         return Err(format!("HTTP error {}", response.status()));
@@ -98,7 +307,7 @@ async fn download_single_file(
     let mut last_progress_emit = std::time::Instant::now();
 
     while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Failed to read chunk: {}", e))?;
+        let chunk = chunk.map_err(|e| crate::http::request_error_message("Failed to read chunk", &e))?;
 
         // Write chunk to file
         file.write_all(&chunk).await.map_err(|e| format!("Failed to write chunk: {}", e))?;
@@ -114,7 +323,13 @@ async fn download_single_file(
             };
 
             // Calculate overall progress based on total downloaded bytes across all files
-            let total_downloaded_bytes = total_downloaded_so_far + downloaded;
+            let (total_downloaded_bytes, active_files) = match progress.lock() {
+                Ok(mut progress) => {
+                    progress.update_file(&file_info.path, downloaded, content_length);
+                    (progress.downloaded_bytes(), progress.active_files.clone())
+                }
+                Err(_) => (downloaded, Vec::new()),
+            };
             let overall_progress = if total_estimated_size > 0 {
                 (((total_downloaded_bytes as f64) / (total_estimated_size as f64)) * 100.0) as u32
             } else {
@@ -134,7 +349,8 @@ async fn download_single_file(
                 "downloadedBytes": total_downloaded_bytes,
                 "totalBytes": total_estimated_size,
                 "currentFileDownloaded": downloaded,
-                "currentFileTotal": content_length
+                "currentFileTotal": content_length,
+                "activeFiles": active_files
             })
             );
 
@@ -151,10 +367,89 @@ async fn download_single_file(
     Ok(downloaded)
 }
 
+// Retries after a 429 before the rate limit error is returned to the caller
+const HF_RATE_LIMIT_RETRIES: u32 = 3;
+
+// Backoff when Hugging Face doesn't send Retry-After, doubled on every attempt
+const HF_RETRY_BASE_DELAY_SECS: u64 = 1;
+
+// Upper bound on a single wait, so a large Retry-After can't stall the UI for minutes
+const HF_MAX_RETRY_DELAY_SECS: u64 = 30;
+
+// Delay before retry number `attempt` (starting at 0), taken from Retry-After when it holds
+// a number of seconds and from exponential backoff otherwise
+fn rate_limit_delay(retry_after: Option<&str>, attempt: u32) -> std::time::Duration {
+    let secs = retry_after
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(HF_RETRY_BASE_DELAY_SECS << attempt.min(10));
+    std::time::Duration::from_secs(secs.min(HF_MAX_RETRY_DELAY_SECS))
+}
+
+// Send a Hugging Face request, waiting and retrying when the API answers 429 Too Many Requests.
+// The last response is returned as is once the retries run out
+async fn send_hf_request(request: reqwest::RequestBuilder) -> Result<reqwest::Response, reqwest::Error> {
+    let mut attempt = 0;
+    loop {
+        // GET requests always clone, keep the original for the next attempt
+        let Some(attempt_request) = request.try_clone() else {
+            return request.send().await;
+        };
+        let response = attempt_request.send().await?;
+
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS || attempt >= HF_RATE_LIMIT_RETRIES {
+            return Ok(response);
+        }
+
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok());
+        let delay = rate_limit_delay(retry_after, attempt);
+        warn!(url = %response.url(), attempt = attempt + 1, delay_secs = delay.as_secs(), "Hugging Face rate limit hit, retrying");
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// Largest page the model browser may request at once
+const MAX_SEARCH_LIMIT: u32 = 50;
+
+// Hugging Face answers 404 for repositories that don't exist
+fn hf_status_error(status: reqwest::StatusCode, message: String) -> SparrowError {
+    if status == reqwest::StatusCode::NOT_FOUND {
+        SparrowError::NotFound(message)
+    } else {
+        SparrowError::Network(message)
+    }
+}
+
+// Map a user-facing sort key to the Hugging Face API sort field
+fn hf_sort_field(sort: &str) -> Result<&'static str, SparrowError> {
+    match sort {
+        "downloads" => Ok("downloads"),
+        "likes" => Ok("likes"),
+        "modified" => Ok("lastModified"),
+        other =>
+            Err(
+                SparrowError::InvalidInput(
+                    format!("Unsupported sort order: {}. Use downloads, likes or modified.", other)
+                )
+            ),
+    }
+}
+
 #[tauri::command]
-pub async fn search_models(query: String, limit: Option<u32>) -> Result<SearchResult, String> {
-    let client = reqwest::Client::new();
-    let search_limit = limit.unwrap_or(10).min(10);
+pub async fn search_models(
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort: Option<String>,
+    pipeline_tag: Option<String>
+) -> Result<SearchResult, SparrowError> {
+    let client = crate::http::http_client(crate::http::API_TIMEOUT)?;
+    let search_limit = limit.unwrap_or(10).clamp(1, MAX_SEARCH_LIMIT);
+    let search_offset = offset.unwrap_or(0);
 
     // Search specifically under OpenVINO organization
     let search_query = if query.trim().is_empty() {
@@ -163,28 +458,38 @@ pub async fn search_models(query: String, limit: Option<u32>) -> Result<SearchRe
         format!("OpenVINO/{}", query)
     };
 
-    let url = format!(
+    // The API has no offset parameter, so fetch through the end of the requested page and skip ahead
+    let mut url = format!(
         "https://huggingface.co/api/models?search={}&limit={}&author=OpenVINO",
         urlencoding::encode(&search_query),
-        search_limit
+        search_offset + search_limit
     );
+    if let Some(sort) = &sort {
+        url.push_str(&format!("&sort={}&direction=-1", hf_sort_field(sort)?));
+    }
+    let pipeline_tag = pipeline_tag.filter(|tag| !tag.trim().is_empty());
+    if let Some(tag) = &pipeline_tag {
+        url.push_str(&format!("&pipeline_tag={}", urlencoding::encode(tag)));
+    }
 
-    let response = client
-        .get(&url)
-        .header("User-Agent", "SparrowAI/1.0")
-        .send().await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+    let response = send_hf_request(client.get(&url)).await
+        .map_err(|e| SparrowError::Network(crate::http::request_error_message("Failed to send request", &e)))?;
 
     if !response.status().is_success() {
-        return Err(format!("API request failed with status: {}", response.status()));
+        return Err(
+            SparrowError::Network(format!("API request failed with status: {}", response.status()))
+        );
     }
 
     let hf_models: Vec<HfModelInfo> = response
         .json().await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| SparrowError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+    // The API doesn't report a total, a full response means there are probably more results
+    let has_more = hf_models.len() >= ((search_offset + search_limit) as usize);
 
     // Filter to only include OpenVINO models and optionally filter by query
-    let model_ids: Vec<String> = hf_models
+    let matching_ids: Vec<String> = hf_models
         .into_iter()
         .filter(|hf_model| {
             // Ensure the model is from OpenVINO organization
@@ -196,11 +501,24 @@ pub async fn search_models(query: String, limit: Option<u32>) -> Result<SearchRe
         .map(|hf_model| hf_model.id)
         .collect();
 
-    // Get detailed info for each model
+    let model_ids: Vec<String> = matching_ids
+        .into_iter()
+        .skip(search_offset as usize)
+        .take(search_limit as usize)
+        .collect();
+
+    // Get detailed info for each model on this page concurrently
+    let model_infos = futures::future::join_all(
+        model_ids.iter().map(|model_id| get_model_info(model_id.clone()))
+    ).await;
+
+    // The task is filtered by the API, so every page holds up to search_limit matches
     let mut models: Vec<ModelInfo> = Vec::new();
-    for model_id in &model_ids {
-        match get_model_info(model_id.clone()).await {
-            Ok(model_info) => models.push(model_info),
+    for (model_id, model_info) in model_ids.iter().zip(model_infos) {
+        match model_info {
+            Ok(model_info) => {
+                models.push(model_info);
+            }
             Err(e) => {
                 warn!(model_id = %model_id, error = %e, "Failed to get info for model");
                 // Continue with other models instead of failing entirely
@@ -208,55 +526,155 @@ pub async fn search_models(query: String, limit: Option<u32>) -> Result<SearchRe
         }
     }
 
-    let total_count = models.len() as u64;
-
     Ok(SearchResult {
         models,
-        total_count: Some(total_count),
+        has_more,
     })
 }
 
 #[tauri::command]
-pub async fn get_model_info(model_id: String) -> Result<ModelInfo, String> {
-    let client = reqwest::Client::new();
+pub async fn get_model_info(model_id: String) -> Result<ModelInfo, SparrowError> {
+    fetch_model_info(model_id, true).await
+}
+
+/// Metadata of a downloaded model from the file saved at download time. Only goes to the
+/// network when that file is missing, and saves the result for next time
+#[tauri::command]
+pub async fn get_local_model_info(model_id: String) -> Result<ModelInfo, SparrowError> {
+    let normalized_model_id = normalize_model_id(&model_id);
+
+    let model_dir = crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id);
+    if let Some(info) = read_local_model_info(&model_dir) {
+        return Ok(info);
+    }
+
+    let info = fetch_model_info(normalized_model_id.clone(), true).await?;
+    if model_dir.is_dir() {
+        if let Err(e) = write_local_model_info(&model_dir, &info) {
+            warn!(error = %e, model_id = %normalized_model_id, "Failed to save model info");
+        }
+    }
+
+    Ok(info)
+}
+
+/// Sampling parameters recommended by the model repo. Fields the repo doesn't set are None
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationDefaults {
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub top_k: Option<u32>,
+    pub repetition_penalty: Option<f64>,
+    pub max_new_tokens: Option<u32>,
+    pub do_sample: Option<bool>,
+    pub eos_token_id: Vec<u64>,
+    pub bos_token_id: Option<u64>,
+    /// Files the values were read from, in order of precedence
+    pub sources: Vec<String>,
+}
+
+// Token ids may be stored as a single number or a list
+fn token_ids(value: &serde_json::Value) -> Vec<u64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64().into_iter().collect(),
+        serde_json::Value::Array(items) => items.iter().filter_map(|item| item.as_u64()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Fill any unset field of `defaults` from `config`; values already set take precedence
+fn apply_generation_config(defaults: &mut GenerationDefaults, config: &serde_json::Value) {
+    let float = |key: &str| config.get(key).and_then(|v| v.as_f64());
+    let uint = |key: &str| config.get(key).and_then(|v| v.as_u64());
+
+    defaults.temperature = defaults.temperature.or(float("temperature"));
+    defaults.top_p = defaults.top_p.or(float("top_p"));
+    defaults.top_k = defaults.top_k.or(uint("top_k").map(|v| v as u32));
+    defaults.repetition_penalty = defaults.repetition_penalty.or(float("repetition_penalty"));
+    defaults.max_new_tokens = defaults.max_new_tokens.or(uint("max_new_tokens").map(|v| v as u32));
+    defaults.do_sample = defaults.do_sample.or(config.get("do_sample").and_then(|v| v.as_bool()));
+    defaults.bos_token_id = defaults.bos_token_id.or(uint("bos_token_id"));
+    if defaults.eos_token_id.is_empty() {
+        defaults.eos_token_id = config.get("eos_token_id").map(token_ids).unwrap_or_default();
+    }
+}
+
+/// Read the recommended sampling parameters from a downloaded model's generation_config.json,
+/// falling back to config.json for anything it doesn't set
+#[tauri::command]
+pub async fn get_model_generation_defaults(model_id: String) -> Result<GenerationDefaults, SparrowError> {
+    let normalized_model_id = normalize_model_id(&model_id);
+
+    let model_dir = crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id);
+    if !model_dir.is_dir() {
+        return Err(SparrowError::NotFound(format!("Model not downloaded: {}", normalized_model_id)));
+    }
+
+    let mut defaults = GenerationDefaults::default();
+    for file_name in ["generation_config.json", "config.json"] {
+        let path = model_dir.join(file_name);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+        let config: serde_json::Value = serde_json
+            ::from_str(&contents)
+            .map_err(|e| SparrowError::Parse(format!("Failed to parse {}: {}", file_name, e)))?;
+
+        apply_generation_config(&mut defaults, &config);
+        defaults.sources.push(file_name.to_string());
+    }
+
+    Ok(defaults)
+}
+
+// Fetch model metadata, optionally skipping the cache when the latest commit matters
+async fn fetch_model_info(model_id: String, use_cache: bool) -> Result<ModelInfo, SparrowError> {
+    let client = crate::http::http_client(crate::http::API_TIMEOUT)?;
 
     // Ensure we're getting info for an OpenVINO model
-    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
-        model_id
-    } else {
-        format!("OpenVINO/{}", model_id)
-    };
+    let normalized_model_id = normalize_model_id(&model_id);
+
+    if use_cache {
+        if let Some(cached) = read_cached_model_info(&normalized_model_id) {
+            return Ok(cached);
+        }
+    }
 
     let url = format!(
         "https://huggingface.co/api/models/{}",
         urlencoding::encode(&normalized_model_id)
     );
 
-    let response = client
-        .get(&url)
-        .header("User-Agent", "SparrowAI/1.0")
-        .send().await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+    let response = send_hf_request(client.get(&url)).await
+        .map_err(|e| SparrowError::Network(crate::http::request_error_message("Failed to send request", &e)))?;
 
     if !response.status().is_success() {
         return Err(
-            format!(
-                "API request failed with status: {}. Make sure the model exists under OpenVINO organization.",
-                response.status()
+            hf_status_error(
+                response.status(),
+                format!(
+                    "API request failed with status: {}. Make sure the model exists under OpenVINO organization.",
+                    response.status()
+                )
             )
         );
     }
 
     let hf_model: HfModelInfo = response
         .json().await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| SparrowError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
     // Verify this is actually an OpenVINO model
     if !hf_model.id.starts_with("OpenVINO/") {
-        return Err(format!("Model {} is not from OpenVINO organization", hf_model.id));
+        return Err(
+            SparrowError::InvalidInput(
+                format!("Model {} is not from OpenVINO organization", hf_model.id)
+            )
+        );
     }
 
-    Ok(ModelInfo {
+    let model_info = ModelInfo {
         id: hf_model.id,
         author: hf_model.author,
         sha: hf_model.sha,
@@ -266,7 +684,13 @@ pub async fn get_model_info(model_id: String) -> Result<ModelInfo, String> {
         likes: hf_model.likes,
         created_at: hf_model.created_at,
         last_modified: hf_model.last_modified,
-    })
+    };
+
+    if let Err(e) = write_cached_model_info(&model_info) {
+        warn!(model_id = %model_info.id, error = %e, "Failed to cache model info");
+    }
+
+    Ok(model_info)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -276,6 +700,7 @@ pub struct ModelUpdateInfo {
     pub local_commit: Option<String>,
     pub remote_commit: Option<String>,
     pub needs_update: bool,
+    pub error: Option<String>,
 }
 
 // Function to write commit SHA to .commit_id file
@@ -313,32 +738,199 @@ async fn read_commit_id(model_dir: &PathBuf) -> Result<String, String> {
     Ok(commit_sha.trim().to_string())
 }
 
+fn write_local_model_info(model_dir: &PathBuf, info: &ModelInfo) -> Result<(), String> {
+    let contents = serde_json
+        ::to_string_pretty(info)
+        .map_err(|e| format!("Failed to serialize model info: {}", e))?;
+    std::fs
+        ::write(model_dir.join(LOCAL_MODEL_INFO_FILE), contents)
+        .map_err(|e| format!("Failed to write model info: {}", e))
+}
+
+fn read_local_model_info(model_dir: &PathBuf) -> Option<ModelInfo> {
+    let contents = std::fs::read_to_string(model_dir.join(LOCAL_MODEL_INFO_FILE)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+// Case-insensitive match on the model id and on its tags or pipeline tag
+fn model_matches_filter(
+    model_id: &str,
+    info: Option<&ModelInfo>,
+    tag: Option<&str>,
+    name_contains: Option<&str>
+) -> bool {
+    if let Some(name) = name_contains {
+        if !model_id.to_lowercase().contains(&name.to_lowercase()) {
+            return false;
+        }
+    }
+
+    match tag {
+        None => true,
+        Some(tag) => {
+            // Without metadata there is nothing to match a tag against
+            info.map(|info| {
+                info.tags
+                    .iter()
+                    .chain(info.pipeline_tag.iter())
+                    .any(|model_tag| model_tag.eq_ignore_ascii_case(tag))
+            }).unwrap_or(false)
+        }
+    }
+}
+
+/// Downloaded model ids filtered by tag and/or name, using the metadata saved at download time
+#[tauri::command]
+pub async fn filter_downloaded_models(
+    tag: Option<String>,
+    name_contains: Option<String>
+) -> Result<Vec<String>, SparrowError> {
+    let models_dir = crate::paths::models_dir().map_err(SparrowError::Io)?;
+    let downloaded = crate::check_downloaded_models(None).await.map_err(SparrowError::Io)?;
+
+    let tag = tag.map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty());
+    let name_contains = name_contains
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    Ok(
+        downloaded
+            .into_iter()
+            .filter(|model_id| {
+                // Models downloaded before metadata was saved fall back to the HF cache
+                let info = read_local_model_info(&models_dir.join(model_id)).or_else(||
+                    read_cached_model_info(model_id)
+                );
+                model_matches_filter(model_id, info.as_ref(), tag.as_deref(), name_contains.as_deref())
+            })
+            .collect()
+    )
+}
+
+// Display names chosen by the user, keyed by model id. Only the UI uses them,
+// the directory on disk and the name OVMS serves stay the original model id
+fn get_aliases_path() -> Result<PathBuf, String> {
+    Ok(crate::paths::sparrow_data_dir()?.join("aliases.json"))
+}
+
+fn load_model_aliases() -> Result<BTreeMap<String, String>, String> {
+    let path = get_aliases_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let contents = std::fs
+        ::read_to_string(&path)
+        .map_err(|e| format!("Failed to read model aliases: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse model aliases: {}", e))
+}
+
+fn save_model_aliases(aliases: &BTreeMap<String, String>) -> Result<(), String> {
+    let path = get_aliases_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs
+            ::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+
+    let contents = serde_json
+        ::to_string_pretty(aliases)
+        .map_err(|e| format!("Failed to serialize model aliases: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write model aliases: {}", e))
+}
+
+fn normalize_model_id(model_id: &str) -> String {
+    if model_id.starts_with("OpenVINO/") {
+        model_id.to_string()
+    } else {
+        format!("OpenVINO/{}", model_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadedModel {
+    pub model_id: String,
+    pub alias: Option<String>,
+    pub pipeline_tag: Option<String>,
+    pub size_bytes: u64,
+}
+
+/// Downloaded models with their alias, pipeline tag and size on disk
+#[tauri::command]
+pub async fn list_downloaded_models_detailed() -> Result<Vec<DownloadedModel>, SparrowError> {
+    let models_dir = crate::paths::models_dir().map_err(SparrowError::Io)?;
+    let downloaded = crate::check_downloaded_models(None).await.map_err(SparrowError::Io)?;
+    let aliases = load_model_aliases().map_err(SparrowError::Io)?;
+
+    Ok(
+        downloaded
+            .into_iter()
+            .map(|model_id| {
+                let model_dir = models_dir.join(&model_id);
+                let info = read_local_model_info(&model_dir).or_else(|| read_cached_model_info(&model_id));
+                DownloadedModel {
+                    alias: aliases.get(&model_id).cloned(),
+                    pipeline_tag: info.and_then(|info| info.pipeline_tag),
+                    size_bytes: crate::paths::dir_size(&model_dir),
+                    model_id,
+                }
+            })
+            .collect()
+    )
+}
+
+#[tauri::command]
+pub async fn set_model_alias(model_id: String, alias: String) -> Result<(), SparrowError> {
+    let alias = alias.trim();
+    if alias.is_empty() {
+        return Err(SparrowError::InvalidInput("Alias cannot be empty".to_string()));
+    }
+
+    let model_id = normalize_model_id(&model_id);
+    let mut aliases = load_model_aliases().map_err(SparrowError::Io)?;
+    aliases.insert(model_id.clone(), alias.to_string());
+    save_model_aliases(&aliases).map_err(SparrowError::Io)?;
+
+    info!(model_id = %model_id, alias = %alias, "Set model alias");
+    Ok(())
+}
+
+/// Remove a model's alias, returning whether it had one
+#[tauri::command]
+pub async fn clear_model_alias(model_id: String) -> Result<bool, SparrowError> {
+    let model_id = normalize_model_id(&model_id);
+    let mut aliases = load_model_aliases().map_err(SparrowError::Io)?;
+    if aliases.remove(&model_id).is_none() {
+        return Ok(false);
+    }
+
+    save_model_aliases(&aliases).map_err(SparrowError::Io)?;
+    info!(model_id = %model_id, "Cleared model alias");
+    Ok(true)
+}
+
 #[tauri::command]
 pub async fn check_model_update_status(
     model_id: String,
     models_dir: Option<String>
-) -> Result<ModelUpdateInfo, String> {
+) -> Result<ModelUpdateInfo, SparrowError> {
     // Ensure we're checking an OpenVINO model
-    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
-        model_id
-    } else {
-        format!("OpenVINO/{}", model_id)
-    };
+    let normalized_model_id = normalize_model_id(&model_id);
 
     // Determine model directory
     let model_dir = if let Some(dir) = models_dir {
         PathBuf::from(dir).join(&normalized_model_id)
     } else {
-        let home_dir = std::env
-            ::var("USERPROFILE")
-            .or_else(|_| std::env::var("HOME"))
-            .map_err(|e| format!("Failed to get user home directory: {}", e))?;
-        PathBuf::from(home_dir).join(".sparrow").join("models").join(&normalized_model_id)
+        crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id)
     };
 
     // Check if model directory exists
     if !model_dir.exists() {
-        return Err(format!("Model directory not found: {}", model_dir.to_string_lossy()));
+        return Err(
+            SparrowError::NotFound(
+                format!("Model directory not found: {}", model_dir.to_string_lossy())
+            )
+        );
     }
 
     // Read local commit SHA
@@ -373,80 +965,445 @@ pub async fn check_model_update_status(
         local_commit,
         remote_commit,
         needs_update,
+        error: None,
     })
 }
 
+#[tauri::command]
+pub async fn check_all_model_updates(
+    models_dir: Option<String>
+) -> Result<Vec<ModelUpdateInfo>, SparrowError> {
+    use futures::StreamExt;
+
+    let model_ids = crate::check_downloaded_models(models_dir.clone()).await.map_err(SparrowError::Io)?;
+
+    let results = futures::stream
+        ::iter(model_ids)
+        .map(|model_id| {
+            let models_dir = models_dir.clone();
+            async move {
+                match check_model_update_status(model_id.clone(), models_dir).await {
+                    Ok(update_info) => update_info,
+                    Err(e) => {
+                        warn!(model_id = %model_id, error = %e, "Failed to check model for updates");
+                        ModelUpdateInfo {
+                            model_id,
+                            is_latest: false,
+                            local_commit: None,
+                            remote_commit: None,
+                            needs_update: false,
+                            error: Some(e.to_string()),
+                        }
+                    }
+                }
+            }
+        })
+        .buffer_unordered(UPDATE_CHECK_CONCURRENCY)
+        .collect::<Vec<_>>().await;
+
+    Ok(results)
+}
+
 #[tauri::command]
 pub async fn download_entire_model(
     model_id: String,
     download_path: Option<String>,
     app: tauri::AppHandle
-) -> Result<String, String> {
+) -> Result<ModelDownloadResult, SparrowError> {
     // Ensure we're downloading an OpenVINO model
-    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
-        model_id
-    } else {
-        format!("OpenVINO/{}", model_id)
-    };
+    let normalized_model_id = normalize_model_id(&model_id);
 
-    // Get model info first to retrieve commit SHA
-    let model_info = get_model_info(normalized_model_id.clone()).await?;
+    // Get model info first to retrieve commit SHA, bypassing the cache so it matches the files
+    let model_info = fetch_model_info(normalized_model_id.clone(), false).await?;
 
     // Create a client with timeout to prevent hanging
-    let client = reqwest::Client
-        ::builder()
-        .timeout(std::time::Duration::from_secs(300)) // 5 minute timeout per request
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let client = crate::http::http_client(crate::http::DOWNLOAD_TIMEOUT)?;
 
     let target_dir = if let Some(path) = download_path {
         PathBuf::from(path).join(&normalized_model_id)
     } else {
         // Use .sparrow/models as default
-        let home_dir = std::env
-            ::var("USERPROFILE")
-            .or_else(|_| std::env::var("HOME"))
-            .map_err(|e| format!("Failed to get user home directory: {}", e))?;
-        PathBuf::from(home_dir).join(".sparrow").join("models").join(&normalized_model_id)
+        crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id)
     };
 
     // Create target directory
-    std::fs::create_dir_all(&target_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    std::fs
+        ::create_dir_all(&target_dir)
+        .map_err(|e| SparrowError::Io(format!("Failed to create directory: {}", e)))?;
+
+    let files = fetch_model_files(&client, &normalized_model_id).await?;
+    let downloadable_files: Vec<&HfFileInfo> = files
+        .iter()
+        .filter(|file| file.file_type == "file")
+        .collect();
+
+    let outcome = download_files(
+        &client,
+        &normalized_model_id,
+        &target_dir,
+        downloadable_files,
+        &app
+    ).await;
+
+    if outcome.downloaded_files.is_empty() {
+        let error_details = if outcome.errors.is_empty() {
+            "No files could be downloaded from the repository.".to_string()
+        } else {
+            format!("Download errors occurred:\n{}", outcome.errors.join("\n"))
+        };
+        return Err(SparrowError::Network(format!("Failed to download model files. {}", error_details)));
+    }
+
+    // Write commit ID to .commit_id file after successful download
+    if let Some(commit_sha) = &model_info.sha {
+        if let Err(e) = write_commit_id(&target_dir, commit_sha).await {
+            warn!(
+                error = %e,
+                model_id = %normalized_model_id,
+                "Failed to write commit ID file"
+            );
+        }
+    } else {
+        warn!(
+            model_id = %normalized_model_id,
+            "No commit SHA available for model"
+        );
+    }
+
+    if let Err(e) = write_local_model_info(&target_dir, &model_info) {
+        warn!(error = %e, model_id = %normalized_model_id, "Failed to save model info");
+    }
+
+    // Generate graph.pbtxt for OVMS compatibility once every file is in place
+    if outcome.failed_files.is_empty() {
+        generate_graph(&target_dir, &normalized_model_id);
+    }
+
+    Ok(build_download_result(normalized_model_id, &target_dir, outcome))
+}
+
+#[tauri::command]
+pub async fn retry_failed_files(
+    model_id: String,
+    files: Vec<String>,
+    download_path: Option<String>,
+    app: tauri::AppHandle
+) -> Result<ModelDownloadResult, SparrowError> {
+    let normalized_model_id = normalize_model_id(&model_id);
+
+    let target_dir = if let Some(path) = download_path {
+        PathBuf::from(path).join(&normalized_model_id)
+    } else {
+        crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id)
+    };
+
+    if !target_dir.exists() {
+        return Err(
+            SparrowError::NotFound(
+                format!("Model directory not found: {}", target_dir.to_string_lossy())
+            )
+        );
+    }
+
+    let client = crate::http::http_client(crate::http::DOWNLOAD_TIMEOUT)?;
+
+    // Look the files up in the repository again so sizes and paths are authoritative
+    let repo_files = fetch_model_files(&client, &normalized_model_id).await?;
+    let retry_files: Vec<&HfFileInfo> = repo_files
+        .iter()
+        .filter(|file| file.file_type == "file" && files.contains(&file.path))
+        .collect();
+
+    let mut outcome = download_files(
+        &client,
+        &normalized_model_id,
+        &target_dir,
+        retry_files,
+        &app
+    ).await;
+
+    for missing in files.iter().filter(|path| !repo_files.iter().any(|file| &file.path == *path)) {
+        outcome.failed_files.push(missing.clone());
+        outcome.errors.push(format!("Failed to download {}: not found in repository", missing));
+    }
+
+    let all_present = repo_files
+        .iter()
+        .filter(|file| file.file_type == "file")
+        .all(|file| target_dir.join(&file.path).exists());
+
+    if all_present && outcome.failed_files.is_empty() {
+        generate_graph(&target_dir, &normalized_model_id);
+    } else {
+        info!(model_id = %normalized_model_id, "Model still has missing files, skipping graph.pbtxt generation");
+    }
+
+    Ok(build_download_result(normalized_model_id, &target_dir, outcome))
+}
+
+#[tauri::command]
+pub async fn update_model(
+    model_id: String,
+    download_path: Option<String>,
+    app: tauri::AppHandle
+) -> Result<ModelDownloadResult, SparrowError> {
+    let normalized_model_id = normalize_model_id(&model_id);
+
+    let target_dir = if let Some(path) = download_path {
+        PathBuf::from(path).join(&normalized_model_id)
+    } else {
+        crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id)
+    };
+
+    if !target_dir.exists() {
+        return Err(
+            SparrowError::NotFound(
+                format!("Model directory not found: {}", target_dir.to_string_lossy())
+            )
+        );
+    }
+
+    // Always compare against the latest commit, not cached metadata
+    let model_info = fetch_model_info(normalized_model_id.clone(), false).await?;
+    let local_commit = read_commit_id(&target_dir).await.ok();
+    if local_commit.is_some() && local_commit == model_info.sha {
+        info!(model_id = %normalized_model_id, "Model is already up to date");
+        return Ok(ModelDownloadResult {
+            model_id: normalized_model_id,
+            message: "Model is already up to date".to_string(),
+            target_dir: target_dir.to_string_lossy().to_string(),
+            downloaded_files: Vec::new(),
+            failed_files: Vec::new(),
+            errors: Vec::new(),
+            total_bytes: 0,
+        });
+    }
+
+    let client = crate::http::http_client(crate::http::DOWNLOAD_TIMEOUT)?;
+
+    let files = fetch_model_files(&client, &normalized_model_id).await?;
+    let manifest = read_file_manifest(&target_dir);
+
+    // Only fetch files that are missing locally or whose object id changed
+    let changed_files: Vec<&HfFileInfo> = files
+        .iter()
+        .filter(|file| file.file_type == "file")
+        .filter(|file| {
+            let unchanged = match (&file.oid, manifest.get(&file.path)) {
+                (Some(remote_oid), Some(local_oid)) => remote_oid == local_oid,
+                _ => false,
+            };
+            !unchanged || !target_dir.join(&file.path).exists()
+        })
+        .collect();
+
+    info!(
+        model_id = %normalized_model_id,
+        changed_files = changed_files.len(),
+        "Updating model files"
+    );
+
+    let outcome = download_files(
+        &client,
+        &normalized_model_id,
+        &target_dir,
+        changed_files,
+        &app
+    ).await;
+
+    // Only mark the model as current once every changed file was fetched
+    if outcome.failed_files.is_empty() {
+        if let Some(commit_sha) = &model_info.sha {
+            if let Err(e) = write_commit_id(&target_dir, commit_sha).await {
+                warn!(error = %e, model_id = %normalized_model_id, "Failed to write commit ID file");
+            }
+        }
+        if let Err(e) = write_local_model_info(&target_dir, &model_info) {
+            warn!(error = %e, model_id = %normalized_model_id, "Failed to save model info");
+        }
+        generate_graph(&target_dir, &normalized_model_id);
+    }
 
-    // First, get the list of files in the repository
+    Ok(build_download_result(normalized_model_id, &target_dir, outcome))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileMismatch {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelVerificationReport {
+    pub model_id: String,
+    pub target_dir: String,
+    pub checked_files: usize,
+    pub missing_files: Vec<String>,
+    pub mismatched_files: Vec<FileMismatch>,
+    pub extra_files: Vec<String>,
+    pub is_complete: bool,
+}
+
+// Hash a file in chunks so multi-gigabyte weights don't have to fit in memory
+fn sha256_file(path: &PathBuf) -> Result<String, String> {
+    use sha2::{ Digest, Sha256 };
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Compare a downloaded model against the repository listing: every remote file must exist
+/// locally with the same size, and LFS files must also match their SHA-256
+#[tauri::command]
+pub async fn verify_model(
+    model_id: String,
+    download_path: Option<String>
+) -> Result<ModelVerificationReport, SparrowError> {
+    let normalized_model_id = normalize_model_id(&model_id);
+
+    let target_dir = if let Some(path) = download_path {
+        PathBuf::from(path).join(&normalized_model_id)
+    } else {
+        crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id)
+    };
+
+    if !target_dir.exists() {
+        return Err(
+            SparrowError::NotFound(
+                format!("Model directory not found: {}", target_dir.to_string_lossy())
+            )
+        );
+    }
+
+    let client = crate::http::http_client(crate::http::API_TIMEOUT)?;
+
+    let files = fetch_model_files(&client, &normalized_model_id).await?;
+    let remote_files: Vec<&HfFileInfo> = files
+        .iter()
+        .filter(|file| file.file_type == "file")
+        .collect();
+
+    let mut missing_files = Vec::new();
+    let mut mismatched_files = Vec::new();
+
+    for file in &remote_files {
+        let local_path = target_dir.join(&file.path);
+        let metadata = match std::fs::metadata(&local_path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => {
+                missing_files.push(file.path.clone());
+                continue;
+            }
+        };
+
+        if let Some(expected_size) = file.size {
+            if metadata.len() != expected_size {
+                mismatched_files.push(FileMismatch {
+                    path: file.path.clone(),
+                    reason: format!("size is {} bytes, expected {}", metadata.len(), expected_size),
+                });
+                continue;
+            }
+        }
+
+        if let Some(lfs) = &file.lfs {
+            let hash_path = local_path.clone();
+            let actual = tokio::task
+                ::spawn_blocking(move || sha256_file(&hash_path)).await
+                .map_err(|e| SparrowError::Io(format!("Hashing task failed: {}", e)))?
+                .map_err(SparrowError::Io)?;
+            if !actual.eq_ignore_ascii_case(&lfs.oid) {
+                mismatched_files.push(FileMismatch {
+                    path: file.path.clone(),
+                    reason: format!("SHA-256 is {}, expected {}", actual, lfs.oid),
+                });
+            }
+        }
+    }
+
+    let mut extra_files: Vec<String> = walkdir::WalkDir
+        ::new(&target_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(&target_dir).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        .filter(|path| !is_generated_file(path))
+        .filter(|path| !remote_files.iter().any(|file| &file.path == path))
+        .collect();
+    extra_files.sort();
+
+    let is_complete = missing_files.is_empty() && mismatched_files.is_empty();
+    info!(
+        model_id = %normalized_model_id,
+        missing = missing_files.len(),
+        mismatched = mismatched_files.len(),
+        extra = extra_files.len(),
+        "Model verification finished"
+    );
+
+    Ok(ModelVerificationReport {
+        model_id: normalized_model_id,
+        target_dir: target_dir.to_string_lossy().to_string(),
+        checked_files: remote_files.len(),
+        missing_files,
+        mismatched_files,
+        extra_files,
+        is_complete,
+    })
+}
+
+// Fetch the list of files in the model repository
+async fn fetch_model_files(
+    client: &reqwest::Client,
+    model_id: &str
+) -> Result<Vec<HfFileInfo>, SparrowError> {
     let files_url = format!(
         "https://huggingface.co/api/models/{}/tree/main",
-        urlencoding::encode(&normalized_model_id)
+        urlencoding::encode(model_id)
     );
 
-    let files_response = client
-        .get(&files_url)
-        .header("User-Agent", "SparrowAI/1.0")
-        .send().await
-        .map_err(|e| format!("Failed to fetch file list: {}", e))?;
+    let files_response = send_hf_request(client.get(&files_url)).await
+        .map_err(|e| SparrowError::Network(crate::http::request_error_message("Failed to fetch file list", &e)))?;
 
     if !files_response.status().is_success() {
         return Err(
-            format!(
-                "Failed to fetch file list. Status: {}. The model might be private or not exist.",
-                files_response.status()
+            hf_status_error(
+                files_response.status(),
+                format!(
+                    "Failed to fetch file list. Status: {}. The model might be private or not exist.",
+                    files_response.status()
+                )
             )
         );
     }
 
-    let files: Vec<HfFileInfo> = files_response
+    files_response
         .json().await
-        .map_err(|e| format!("Failed to parse file list: {}", e))?;
-
-    let mut downloaded_files = Vec::new();
-    let mut errors = Vec::new();
-    let mut total_downloaded_size = 0u64;
+        .map_err(|e| SparrowError::Parse(format!("Failed to parse file list: {}", e)))
+}
 
-    // Filter to only download actual files (not directories)
-    let mut downloadable_files: Vec<&HfFileInfo> = files
-        .iter()
-        .filter(|file| file.file_type == "file")
-        .collect();
+// Download the given files one by one, collecting failures instead of stopping
+async fn download_files(
+    client: &reqwest::Client,
+    model_id: &str,
+    target_dir: &PathBuf,
+    mut downloadable_files: Vec<&HfFileInfo>,
+    app: &tauri::AppHandle
+) -> DownloadOutcome {
+    let mut outcome = DownloadOutcome::default();
 
     // Calculate total size and warn if very large
     let total_estimated_size: u64 = downloadable_files
@@ -467,37 +1424,47 @@ pub async fn download_entire_model(
     downloadable_files.sort_by_key(|f| f.size.unwrap_or(0));
 
     let total_files = downloadable_files.len();
+    let progress = Mutex::new(DownloadProgress::default());
 
     for (index, file_info) in downloadable_files.iter().enumerate() {
         let file_url = format!(
             "https://huggingface.co/{}/resolve/main/{}",
-            urlencoding::encode(&normalized_model_id),
+            urlencoding::encode(model_id),
             urlencoding::encode(&file_info.path)
         );
 
+        if let Ok(mut progress) = progress.lock() {
+            progress.start_file(&file_info.path, file_info.size.unwrap_or(0));
+        }
+
         // Add error recovery wrapper
         let download_result = download_single_file(
-            &client,
+            client,
             &file_url,
-            &target_dir,
+            target_dir,
             file_info,
-            &normalized_model_id,
+            model_id,
             index + 1,
             total_files,
-            total_downloaded_size,
+            &progress,
             total_estimated_size,
-            &app
+            app
         ).await;
 
+        if let Ok(mut progress) = progress.lock() {
+            progress.finish_file(&file_info.path, download_result.as_ref().ok().copied());
+        }
+
         match download_result {
             Ok(file_size) => {
-                downloaded_files.push(file_info.path.clone());
-                total_downloaded_size += file_size;
+                outcome.downloaded_files.push(file_info.path.clone());
+                outcome.total_bytes += file_size;
             }
             Err(e) => {
                 let error_msg = format!("Failed to download {}: {}", file_info.path, e);
                 error!(error = %error_msg, "Model download failed");
-                errors.push(error_msg);
+                outcome.failed_files.push(file_info.path.clone());
+                outcome.errors.push(error_msg);
 
                 // Continue with other files instead of crashing
                 continue;
@@ -505,57 +1472,51 @@ pub async fn download_entire_model(
         }
     }
 
-    if downloaded_files.is_empty() {
-        let error_details = if errors.is_empty() {
-            "No files could be downloaded from the repository.".to_string()
-        } else {
-            format!("Download errors occurred:\n{}", errors.join("\n"))
-        };
-        return Err(format!("Failed to download model files. {}", error_details));
-    }
+    record_downloaded_files(target_dir, &downloadable_files, &outcome.downloaded_files);
 
-    // Write commit ID to .commit_id file after successful download
-    if let Some(commit_sha) = &model_info.sha {
-        if let Err(e) = write_commit_id(&target_dir, commit_sha).await {
-            warn!(
-                error = %e,
-                model_id = %normalized_model_id,
-                "Failed to write commit ID file"
-            );
-        }
+    outcome
+}
+
+fn generate_graph(target_dir: &PathBuf, model_id: &str) {
+    if let Err(e) = crate::ovms::generate_ovms_graph(target_dir, model_id, &crate::settings::current().ovms_runtime) {
+        warn!(error = %e, "Failed to generate graph.pbtxt");
     } else {
-        warn!(
-            model_id = %normalized_model_id,
-            "No commit SHA available for model"
-        );
+        info!(model_id = %model_id, "graph.pbtxt generated for model");
     }
+}
 
-    let total_size_mb = (total_downloaded_size as f64) / (1024.0 * 1024.0);
+fn build_download_result(
+    model_id: String,
+    target_dir: &PathBuf,
+    outcome: DownloadOutcome
+) -> ModelDownloadResult {
+    let total_size_mb = (outcome.total_bytes as f64) / (1024.0 * 1024.0);
     let success_msg = format!(
         "Successfully downloaded {} files ({:.2} MB) to:\n{}\n\nDownloaded files:\n• {}",
-        downloaded_files.len(),
+        outcome.downloaded_files.len(),
         total_size_mb,
         target_dir.to_string_lossy(),
-        downloaded_files.join("\n• ")
+        outcome.downloaded_files.join("\n• ")
     );
 
-    // Generate graph.pbtxt for OVMS compatibility
-    if let Err(e) = crate::ovms::generate_ovms_graph(&target_dir, &normalized_model_id) {
-        warn!(error = %e, "Failed to generate graph.pbtxt");
-    } else {
-        info!(model_id = %normalized_model_id, "graph.pbtxt generated for model");
-    }
-
-    if !errors.is_empty() {
-        Ok(
-            format!(
-                "{}\n\n⚠️ Some files had issues ({} errors):\n{}",
-                success_msg,
-                errors.len(),
-                errors.join("\n")
-            )
+    let message = if !outcome.errors.is_empty() {
+        format!(
+            "{}\n\n⚠️ Some files had issues ({} errors):\n{}",
+            success_msg,
+            outcome.errors.len(),
+            outcome.errors.join("\n")
         )
     } else {
-        Ok(success_msg)
+        success_msg
+    };
+
+    ModelDownloadResult {
+        model_id,
+        message,
+        target_dir: target_dir.to_string_lossy().to_string(),
+        downloaded_files: outcome.downloaded_files,
+        failed_files: outcome.failed_files,
+        errors: outcome.errors,
+        total_bytes: outcome.total_bytes,
     }
 }
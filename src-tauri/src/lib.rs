@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::sync::{ Arc, Mutex };
 use tauri::Emitter;
-use tracing::{ info, error };
+use tracing::{ info, warn, error };
 
 mod huggingface;
 mod ovms;
@@ -9,20 +9,20 @@ mod chat;
 mod rag;
 mod mcp;
 mod logging;
+mod paths;
+mod settings;
+mod presets;
+mod errors;
+mod http;
+mod local_api;
 
 #[tauri::command]
 async fn check_downloaded_models(download_path: Option<String>) -> Result<Vec<String>, String> {
     let downloads_dir = if let Some(path) = download_path {
         PathBuf::from(path)
     } else {
-        // Use .sparrow/models as default
-        let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-            Ok(home) => home,
-            Err(_) => {
-                return Err("Failed to get user home directory".to_string());
-            }
-        };
-        PathBuf::from(home_dir).join(".sparrow").join("models")
+        // Use the data directory's models folder as default
+        paths::models_dir()?
     };
 
     let mut downloaded_models = Vec::new();
@@ -115,14 +115,8 @@ async fn delete_downloaded_model(
     let base_dir = if let Some(path) = download_path {
         PathBuf::from(path)
     } else {
-        // Use .sparrow/models as default
-        let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-            Ok(home) => home,
-            Err(_) => {
-                return Err("Failed to get user home directory".to_string());
-            }
-        };
-        PathBuf::from(home_dir).join(".sparrow").join("models")
+        // Use the data directory's models folder as default
+        paths::models_dir()?
     };
 
     let model_dir = base_dir.join(&normalized_model_id);
@@ -148,6 +142,11 @@ async fn delete_downloaded_model(
                 }
             }
 
+            // Don't leave an alias behind for a model that may be downloaded again later
+            if let Err(e) = huggingface::clear_model_alias(normalized_model_id.clone()).await {
+                warn!(model_id = %normalized_model_id, error = %e, "Failed to clear model alias");
+            }
+
             Ok(format!("Successfully deleted model: {}", normalized_model_id))
         }
         Err(e) => Err(format!("Failed to delete model {}: {}", normalized_model_id, e)),
@@ -169,14 +168,8 @@ async fn open_model_folder(
     let base_dir = if let Some(path) = download_path {
         PathBuf::from(path)
     } else {
-        // Use .sparrow/models as default
-        let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-            Ok(home) => home,
-            Err(_) => {
-                return Err("Failed to get user home directory".to_string());
-            }
-        };
-        PathBuf::from(home_dir).join(".sparrow").join("models")
+        // Use the data directory's models folder as default
+        paths::models_dir()?
     };
 
     let model_dir = base_dir.join(&normalized_model_id);
@@ -206,15 +199,7 @@ async fn open_model_folder(
 
 #[tauri::command]
 async fn get_default_download_path() -> Result<String, String> {
-    // Get user's Downloads directory
-    let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-        Ok(home) => PathBuf::from(home),
-        Err(_) => {
-            return Err("Failed to get user home directory".to_string());
-        }
-    };
-
-    let default_path = home_dir.join(".sparrow").join("models");
+    let default_path = paths::models_dir()?;
 
     // Create the directory if it doesn't exist
     if let Err(e) = std::fs::create_dir_all(&default_path) {
@@ -231,10 +216,7 @@ async fn get_default_download_path() -> Result<String, String> {
 #[tauri::command]
 async fn get_user_profile_dir() -> Result<String, String> {
     // Get user profile directory without canonicalization to avoid \\?\ prefix
-    match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-        Ok(home) => Ok(home),
-        Err(_) => Err("Failed to get user home directory".to_string()),
-    }
+    paths::home_dir().map(|home| home.to_string_lossy().to_string())
 }
 
 #[tauri::command]
@@ -269,6 +251,235 @@ struct InitializationStatus {
 // Global initialization status
 static INIT_STATUS: std::sync::OnceLock<Arc<Mutex<InitializationStatus>>> = std::sync::OnceLock::new();
 
+#[tauri::command]
+async fn cancel_ovms_init() -> Result<(), String> {
+    if let Some(status_mutex) = INIT_STATUS.get() {
+        let status = status_mutex.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if status.is_complete || status.has_error || status.step == "cancelled" {
+            return Err("OVMS initialization is not running".to_string());
+        }
+    }
+
+    info!("OVMS initialization cancel requested");
+    ovms::request_init_cancel();
+    Ok(())
+}
+
+// Embedding and reranker models RAG relies on, downloaded during first-run setup
+const RAG_MODELS: [&str; 2] = ["OpenVINO/bge-reranker-base-int8-ov", "OpenVINO/bge-base-en-v1.5-int8-ov"];
+
+// RAG models that aren't in the models directory yet
+async fn missing_rag_models() -> Vec<&'static str> {
+    let downloaded_models = match check_downloaded_models(None).await {
+        Ok(models) => models,
+        Err(e) => {
+            error!(error = %e, "Failed to check downloaded models");
+            Vec::new()
+        }
+    };
+
+    RAG_MODELS.into_iter()
+        .filter(|model| !downloaded_models.iter().any(|downloaded| downloaded == model))
+        .collect()
+}
+
+/// Download whichever BGE models RAG needs are missing. Progress is reported through the
+/// usual download-progress events; returns the models that were downloaded
+#[tauri::command]
+async fn ensure_rag_models(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let mut downloaded = Vec::new();
+    let mut errors = Vec::new();
+
+    for model in missing_rag_models().await {
+        info!(model = %model, "Downloading missing RAG model");
+        match huggingface::download_entire_model(model.to_string(), None, app_handle.clone()).await {
+            Ok(result) if result.failed_files.is_empty() => downloaded.push(model.to_string()),
+            Ok(result) => errors.push(format!("{}: {}", model, result.errors.join("; "))),
+            Err(e) => errors.push(format!("{}: {}", model, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(downloaded)
+    } else {
+        Err(format!("Failed to download RAG models: {}", errors.join(", ")))
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SetupProgress {
+    step: String,
+    message: String,
+    progress: u8,
+    error: Option<String>,
+}
+
+fn emit_setup_progress(app_handle: &tauri::AppHandle, step: &str, message: String, progress: u8) {
+    let payload = SetupProgress { step: step.to_string(), message, progress, error: None };
+    app_handle
+        .emit("setup-progress", payload)
+        .unwrap_or_else(|e| error!(error = %e, "Failed to emit setup progress"));
+}
+
+// The first-run steps in order, each skipped when it's already done
+async fn first_time_setup_steps(app_handle: &tauri::AppHandle, chat_model_id: &str) -> Result<String, String> {
+    emit_setup_progress(app_handle, "ovms", "Checking OVMS...".to_string(), 0);
+    if !ovms::is_ovms_present(Some(app_handle)) {
+        emit_setup_progress(app_handle, "ovms", "Downloading OVMS...".to_string(), 5);
+        ovms::download_ovms(app_handle.clone()).await?;
+    }
+
+    emit_setup_progress(app_handle, "rag_models", "Downloading embedding and reranker models...".to_string(), 25);
+    ensure_rag_models(app_handle.clone()).await?;
+
+    let downloaded_models = check_downloaded_models(None).await?;
+    if !downloaded_models.iter().any(|model| model == chat_model_id) {
+        emit_setup_progress(app_handle, "chat_model", format!("Downloading {}...", chat_model_id), 45);
+        let result = huggingface::download_entire_model(chat_model_id.to_string(), None, app_handle.clone()).await?;
+        if !result.failed_files.is_empty() {
+            return Err(format!("Failed to download {}: {}", chat_model_id, result.errors.join("; ")));
+        }
+    }
+
+    // load_model adds the chat model to the config later, this only needs the BGE entries
+    if !ovms::get_ovms_config_path(Some(app_handle)).exists() {
+        emit_setup_progress(app_handle, "config", "Creating OVMS configuration...".to_string(), 75);
+        let bge_model_path = paths
+            ::models_dir()?
+            .join("OpenVINO")
+            .join("bge-base-en-v1.5-int8-ov")
+            .to_string_lossy()
+            .to_string();
+        ovms::create_ovms_config(app_handle.clone(), "bge-base-en-v1.5-int8-ov".to_string(), bge_model_path).await?;
+    }
+
+    emit_setup_progress(app_handle, "starting_server", "Starting OVMS server...".to_string(), 85);
+    ovms::start_ovms_server(app_handle.clone()).await?;
+
+    emit_setup_progress(app_handle, "loading_model", format!("Loading {}...", chat_model_id), 92);
+    ovms::load_model(app_handle.clone(), chat_model_id.to_string(), Some(false)).await?;
+
+    emit_setup_progress(app_handle, "complete", "Setup complete".to_string(), 100);
+    Ok(format!("Setup complete, {} is ready to chat", chat_model_id))
+}
+
+/// Take a new install from nothing to a loaded chat model: download OVMS, the BGE models and
+/// `chat_model_id`, create the OVMS config, start the server and load the model. Each stage
+/// is reported as a `setup-progress` event; a failure is reported with its `error` set
+#[tauri::command]
+async fn run_first_time_setup(app_handle: tauri::AppHandle, chat_model_id: String) -> Result<String, String> {
+    let chat_model_id = if chat_model_id.starts_with("OpenVINO/") {
+        chat_model_id
+    } else {
+        format!("OpenVINO/{}", chat_model_id)
+    };
+
+    info!(model = %chat_model_id, "Starting first-time setup");
+    let result = first_time_setup_steps(&app_handle, &chat_model_id).await;
+    if let Err(e) = &result {
+        error!(error = %e, "First-time setup failed");
+        let payload = SetupProgress {
+            step: "failed".to_string(),
+            message: "Setup failed".to_string(),
+            progress: 0,
+            error: Some(e.clone()),
+        };
+        app_handle
+            .emit("setup-progress", payload)
+            .unwrap_or_else(|e| error!(error = %e, "Failed to emit setup progress"));
+    }
+    result
+}
+
+// Set the initialization step shown by the UI and emit it
+fn update_init_status(
+    app_handle: &tauri::AppHandle,
+    status_mutex: &Mutex<InitializationStatus>,
+    step: &str,
+    message: String,
+    progress: u8
+) {
+    let mut status = status_mutex.lock().unwrap();
+    status.step = step.to_string();
+    status.message = message;
+    status.progress = progress;
+    status.is_complete = progress == 100;
+    status.has_error = false;
+    status.error_message = None;
+    app_handle
+        .emit("ovms-init-status", &*status)
+        .unwrap_or_else(|e| error!(error = %e, "Failed to emit status"));
+}
+
+/// Stop OVMS, start it again and reload the model that was loaded before
+#[tauri::command]
+async fn restart_ovms(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let status_mutex = INIT_STATUS.get_or_init(||
+        Arc::new(
+            Mutex::new(InitializationStatus {
+                step: "restarting".to_string(),
+                message: "Restarting OVMS...".to_string(),
+                progress: 0,
+                is_complete: false,
+                has_error: false,
+                error_message: None,
+            })
+        )
+    );
+    let previous_model = ovms::get_loaded_model().await?;
+
+    info!(model = ?previous_model, "Restarting OVMS");
+    update_init_status(&app_handle, status_mutex, "restarting", "Stopping OVMS server...".to_string(), 10);
+    ovms::stop_ovms().await?;
+    ovms::wait_for_port_release(settings::current().ovms_port).await;
+
+    update_init_status(&app_handle, status_mutex, "starting_server", "Starting OVMS server...".to_string(), 50);
+    if let Err(e) = ovms::start_ovms_server(app_handle.clone()).await {
+        error!(error = %e, "Failed to restart OVMS server");
+        let mut status = status_mutex.lock().unwrap();
+        status.has_error = true;
+        status.error_message = Some(format!("Failed to restart OVMS server: {}", e));
+        status.message = "Server restart failed".to_string();
+        app_handle
+            .emit("ovms-init-status", &*status)
+            .unwrap_or_else(|e| error!(error = %e, "Failed to emit status"));
+        return Err(format!("Failed to restart OVMS server: {}", e));
+    }
+
+    let mut reload_error = None;
+    if let Some(model_id) = &previous_model {
+        update_init_status(&app_handle, status_mutex, "restoring_model", format!("Reloading {}...", model_id), 75);
+        if let Err(e) = ovms::load_model(app_handle.clone(), model_id.clone(), Some(false)).await {
+            warn!(error = %e, model_id = %model_id, "Failed to reload model after OVMS restart");
+            reload_error = Some(format!("OVMS restarted but {} could not be reloaded: {}", model_id, e));
+        }
+    }
+
+    update_init_status(&app_handle, status_mutex, "complete", "OVMS restarted".to_string(), 100);
+    match reload_error {
+        Some(e) => Err(e),
+        None => Ok("OVMS restarted".to_string()),
+    }
+}
+
+// Mark initialization as cancelled if the user asked for it, returns true when it should stop
+fn stop_if_cancelled(app_handle: &tauri::AppHandle, status_mutex: &Mutex<InitializationStatus>) -> bool {
+    if !ovms::is_init_cancelled() {
+        return false;
+    }
+
+    ovms::reset_init_cancel();
+    info!("OVMS initialization cancelled");
+
+    let mut status = status_mutex.lock().unwrap();
+    status.step = "cancelled".to_string();
+    status.message = "OVMS initialization cancelled".to_string();
+    app_handle
+        .emit("ovms-init-status", &*status)
+        .unwrap_or_else(|e| error!(error = %e, "Failed to emit status"));
+    true
+}
+
 async fn initialize_ovms(app_handle: tauri::AppHandle) {
     let status_mutex = INIT_STATUS.get_or_init(||
         Arc::new(
@@ -282,6 +493,7 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
             })
         )
     );
+    ovms::reset_init_cancel();
 
     // Update status: Checking BGE models
     {
@@ -295,21 +507,14 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
     }
 
     // Download BGE models if they don't exist
-    let bge_models = vec![
-        "OpenVINO/bge-reranker-base-int8-ov",
-        "OpenVINO/bge-base-en-v1.5-int8-ov"
-    ];
+    let missing_models = missing_rag_models().await;
 
-    let downloaded_models = match check_downloaded_models(None).await {
-        Ok(models) => models,
-        Err(e) => {
-            error!(error = %e, "Failed to check downloaded models");
-            Vec::new()
+    for bge_model in RAG_MODELS {
+        if stop_if_cancelled(&app_handle, status_mutex) {
+            return;
         }
-    };
 
-    for bge_model in &bge_models {
-        if !downloaded_models.contains(&bge_model.to_string()) {
+        if missing_models.contains(&bge_model) {
             // Update status: Downloading BGE model
             {
                 let mut status = status_mutex.lock().unwrap();
@@ -331,8 +536,8 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
                     app_handle.clone()
                 ).await
             {
-                Ok(msg) => {
-                    info!(message = %msg, "BGE model download");
+                Ok(result) => {
+                    info!(message = %result.message, "BGE model download");
                 }
                 Err(e) => {
                     error!(model = %bge_model, error = %e, "Failed to download BGE model");
@@ -345,6 +550,10 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
         }
     }
 
+    if stop_if_cancelled(&app_handle, status_mutex) {
+        return;
+    }
+
     // Update status: Starting OVMS check
     {
         let mut status = status_mutex.lock().unwrap();
@@ -396,18 +605,19 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
                 }
 
                 // Create initial OVMS config with BGE models
-                let home_dir = match
-                    std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME"))
-                {
-                    Ok(home) => home,
-                    Err(_) => {
-                        error!("Failed to get user home directory for OVMS config");
+                let models_dir = match paths::models_dir() {
+                    Ok(dir) => dir,
+                    Err(e) => {
+                        error!(error = %e, "Failed to get models directory for OVMS config");
                         return;
                     }
                 };
 
-                let bge_model_path =
-                    format!("{}/.sparrow/models/OpenVINO/bge-base-en-v1.5-int8-ov", home_dir);
+                let bge_model_path = models_dir
+                    .join("OpenVINO")
+                    .join("bge-base-en-v1.5-int8-ov")
+                    .to_string_lossy()
+                    .to_string();
 
                 match
                     ovms::create_ovms_config(
@@ -425,6 +635,9 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
                     }
                 }
             }
+            Err(_) if stop_if_cancelled(&app_handle, status_mutex) => {
+                return;
+            }
             Err(e) => {
                 error!(error = %e, "Failed to download OVMS");
                 let mut status = status_mutex.lock().unwrap();
@@ -468,9 +681,12 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
             }
 
             // Create initial OVMS config with BGE models
-            if let Ok(home_dir) = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-                let bge_model_path =
-                    format!("{}/.sparrow/models/OpenVINO/bge-base-en-v1.5-int8-ov", home_dir);
+            if let Ok(models_dir) = paths::models_dir() {
+                let bge_model_path = models_dir
+                    .join("OpenVINO")
+                    .join("bge-base-en-v1.5-int8-ov")
+                    .to_string_lossy()
+                    .to_string();
 
                 match
                     ovms::create_ovms_config(
@@ -488,13 +704,17 @@ async fn initialize_ovms(app_handle: tauri::AppHandle) {
                     }
                 }
             } else {
-                error!("Failed to get user home directory for OVMS config");
+                error!("Failed to get models directory for OVMS config");
             }
         } else {
             info!("OVMS config already exists, skipping config creation");
         }
     }
 
+    if stop_if_cancelled(&app_handle, status_mutex) {
+        return;
+    }
+
     // Start OVMS server
     {
         let mut status = status_mutex.lock().unwrap();
@@ -549,65 +769,136 @@ pub fn run() {
             tauri::generate_handler![
                 huggingface::search_models,
                 huggingface::get_model_info,
+                huggingface::get_local_model_info,
+                huggingface::get_model_generation_defaults,
+                huggingface::filter_downloaded_models,
+                huggingface::list_downloaded_models_detailed,
+                huggingface::set_model_alias,
+                huggingface::clear_model_alias,
+                huggingface::clear_hf_cache,
                 huggingface::download_entire_model,
+                huggingface::retry_failed_files,
                 huggingface::check_model_update_status,
+                huggingface::check_all_model_updates,
+                huggingface::update_model,
+                huggingface::verify_model,
                 check_downloaded_models,
                 delete_downloaded_model,
                 open_model_folder,
                 get_default_download_path,
                 get_user_profile_dir,
                 get_initialization_status,
+                cancel_ovms_init,
+                restart_ovms,
+                ensure_rag_models,
+                run_first_time_setup,
+                local_api::start_local_api,
+                local_api::stop_local_api,
+                local_api::get_local_api_status,
                 ovms::download_ovms,
                 ovms::check_ovms_present,
                 ovms::start_ovms_server,
+                ovms::reclaim_ovms_port,
+                ovms::stop_ovms,
                 ovms::create_ovms_config,
                 ovms::update_ovms_config,
+                ovms::preview_ovms_config,
                 ovms::reload_ovms_config,
+                ovms::get_ovms_config_raw,
+                ovms::set_ovms_config_raw,
                 ovms::load_model,
+                ovms::count_tokens,
+                ovms::detect_model_kind,
+                ovms::is_ovms_process_alive,
+                ovms::set_ovms_runtime_options,
+                ovms::get_ovms_log,
+                ovms::estimate_model_fit,
+                ovms::get_ovms_cache_size,
+                ovms::clear_ovms_cache,
+                ovms::list_available_devices,
                 ovms::unload_model,
                 ovms::get_loaded_model,
+                ovms::get_loaded_model_status,
+                chat::chat_with_loaded_model,
                 chat::chat_with_loaded_model_streaming,
                 ovms::check_ovms_status,
                 ovms::get_ovms_model_metadata,
                 chat::get_chat_sessions,
+                chat::get_chat_session,
                 chat::create_chat_session,
                 chat::create_temporary_chat_session,
                 chat::persist_temporary_session,
                 chat::add_message_to_temporary_session,
+                chat::enable_temp_session_autosave,
+                chat::list_recoverable_temp_sessions,
+                chat::discard_temp_session,
                 chat::update_chat_session,
                 chat::delete_chat_session,
+                chat::set_chat_session_pinned,
+                chat::prune_chat_sessions,
+                chat::archive_chat_session,
+                chat::get_archived_chat_sessions,
+                chat::merge_chat_sessions,
+                chat::fork_chat_session,
                 chat::set_active_chat_session,
                 chat::add_message_to_session,
                 chat::get_session_messages,
                 chat::get_conversation_history,
+                chat::get_session_stats,
                 chat::chat_with_rag_streaming,
+                chat::preview_system_message,
+                presets::list_chat_presets,
+                presets::save_chat_preset,
+                presets::delete_chat_preset,
                 rag::documents::process_document,
+                rag::documents::process_document_streaming,
+                rag::documents::preview_document_chunks,
+                rag::documents::supported_document_types,
                 rag::documents::save_temp_file,
+                rag::documents::cleanup_temp_files,
                 rag::embeddings::create_document_embeddings,
+                rag::embeddings::cancel_embedding_operation,
                 rag::embeddings::create_query_embedding,
+                rag::embeddings::reembed_all_documents,
+                rag::embedding_cache::get_embedding_cache_stats,
                 rag::vector_store::store_documents,
                 rag::vector_store::search_documents,
+                rag::vector_store::rebuild_search_index,
                 rag::vector_store::get_all_documents,
                 rag::vector_store::delete_document_by_id,
                 rag::vector_store::get_document_count,
                 rag::vector_store::clear_all_documents,
+                rag::vector_store::compact_vector_store,
                 rag::vector_store::get_all_files,
+                rag::vector_store::get_file_type_facets,
+                rag::vector_store::get_store_capacity,
                 rag::vector_store::get_file_chunks,
+                rag::vector_store::get_chunk_context,
                 rag::vector_store::delete_file_by_path,
                 rag::reranker::rerank_search_results,
                 rag::reranker::rerank_search_results_simple,
                 rag::search::search_documents_by_query,
+                rag::search::export_search_results,
                 rag::search::get_search_suggestions,
+                rag::watcher::watch_folder,
+                rag::watcher::stop_watching_folder,
                 mcp::get_mcp_servers,
                 mcp::add_mcp_server,
+                mcp::test_mcp_server,
                 mcp::edit_mcp_server,
                 mcp::remove_mcp_server,
+                mcp::set_mcp_server_enabled,
                 mcp::connect_mcp_server,
                 mcp::disconnect_mcp_server,
                 mcp::get_mcp_server_info,
                 mcp::fetch_mcp_server_tools,
                 mcp::get_all_mcp_tools_for_chat,
-                mcp::call_mcp_tool
+                mcp::call_mcp_tool,
+                mcp::list_mcp_prompts,
+                mcp::get_mcp_prompt,
+                settings::get_settings,
+                settings::update_settings,
+                ovms::set_model_device
             ]
         )
         .setup(|app| {
@@ -616,6 +907,12 @@ pub fn run() {
                 initialize_ovms(handle).await;
             });
 
+            // Resume watching folders saved from previous sessions
+            rag::watcher::restore_watched_folders(app.handle().clone());
+
+            // Remove uploads left behind by earlier sessions
+            rag::documents::sweep_old_temp_files();
+
             // Start periodic log cleanup task
             tauri::async_runtime::spawn(async move {
                 logging::periodic_cleanup_task().await;
@@ -0,0 +1,201 @@
+use crate::errors::SparrowError;
+use crate::ovms::LoadedModelStatus;
+use axum::body::Body;
+use axum::http::{ header, StatusCode };
+use axum::response::{ IntoResponse, Response };
+use axum::routing::post;
+use axum::{ Json, Router };
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+use tracing::{ info, error };
+
+// The running server, if any. Dropping the sender would also stop it
+static LOCAL_API: Mutex<Option<LocalApiHandle>> = Mutex::new(None);
+
+struct LocalApiHandle {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalApiStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+}
+
+// OpenAI-style error body, so SDK clients surface the message instead of a parse error
+fn error_response(status: StatusCode, message: String) -> Response {
+    let body = serde_json::json!({
+        "error": {
+            "message": message,
+            "type": "sparrow_error",
+        }
+    });
+    (status, Json(body)).into_response()
+}
+
+// OVMS serves models under their bare name, without the OpenVINO/ prefix
+fn served_model_name() -> Option<String> {
+    match crate::ovms::current_loaded_model() {
+        LoadedModelStatus::Loaded(model_id) => model_id.rsplit('/').next().map(|name| name.to_string()),
+        _ => None,
+    }
+}
+
+// Point the request at `model` whatever the client asked for, there is only one model to serve
+fn with_model(mut body: Value, model: &str) -> Result<Value, String> {
+    match body.as_object_mut() {
+        Some(object) => {
+            object.insert("model".to_string(), Value::String(model.to_string()));
+            Ok(body)
+        }
+        None => Err("Request body must be a JSON object".to_string()),
+    }
+}
+
+// Send the request to OVMS and stream its response back unchanged, which keeps
+// server-sent events from `stream: true` requests flowing as they are generated
+async fn forward_to_ovms(path: &str, body: Value) -> Response {
+    // No overall timeout, a streamed completion can take longer than any fixed limit
+    let client = match
+        reqwest::Client::builder().connect_timeout(crate::http::STATUS_TIMEOUT).build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create HTTP client: {}", e));
+        }
+    };
+
+    let url = format!("{}{}", crate::settings::ovms_base_url(), path);
+    let response = match client.post(&url).json(&body).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return error_response(StatusCode::BAD_GATEWAY, crate::http::request_error_message("OVMS request failed", &e));
+        }
+    };
+
+    let status = StatusCode::from_u16(response.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from_stream(response.bytes_stream()))
+        .unwrap_or_else(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to build response: {}", e)))
+}
+
+async fn chat_completions(Json(body): Json<Value>) -> Response {
+    let Some(model) = served_model_name() else {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, "No model is loaded in SparrowAI".to_string());
+    };
+
+    match with_model(body, &model) {
+        Ok(body) => forward_to_ovms("/v3/chat/completions", body).await,
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+async fn embeddings(Json(body): Json<Value>) -> Response {
+    if let Err(e) = crate::ovms::ensure_embedding_model_loaded().await {
+        return error_response(StatusCode::SERVICE_UNAVAILABLE, e.to_string());
+    }
+
+    let model = crate::settings::current().embedding_model;
+    match with_model(body, &model) {
+        Ok(body) => forward_to_ovms("/v3/embeddings", body).await,
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+fn router() -> Router {
+    Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .route("/v1/embeddings", post(embeddings))
+}
+
+/// Serve an OpenAI-compatible API on 127.0.0.1:`port` so scripts and other apps can use the
+/// loaded model. Chat requests go to the loaded model, embedding requests to the BGE model
+#[tauri::command]
+pub async fn start_local_api(port: u16) -> Result<String, SparrowError> {
+    if port == crate::settings::current().ovms_port {
+        return Err(SparrowError::InvalidInput(format!("Port {} is used by OVMS", port)));
+    }
+    if let Some(running) = LOCAL_API.lock().unwrap().as_ref() {
+        return Err(SparrowError::InvalidInput(format!("Local API is already running on port {}", running.port)));
+    }
+
+    // Localhost only, the API has no authentication
+    let listener = tokio::net::TcpListener
+        ::bind(("127.0.0.1", port)).await
+        .map_err(|e| SparrowError::Io(format!("Failed to bind 127.0.0.1:{}: {}", port, e)))?;
+
+    let (shutdown, shutdown_rx) = oneshot::channel();
+    {
+        let mut running = LOCAL_API.lock().unwrap();
+        if let Some(existing) = running.as_ref() {
+            return Err(SparrowError::InvalidInput(format!("Local API is already running on port {}", existing.port)));
+        }
+        *running = Some(LocalApiHandle { port, shutdown });
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let result = axum
+            ::serve(listener, router())
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            }).await;
+
+        if let Err(e) = result {
+            error!(port, error = %e, "Local API server failed");
+        }
+        info!(port, "Local API server stopped");
+
+        // Clear the handle if the server exited on its own
+        let mut running = LOCAL_API.lock().unwrap();
+        if running.as_ref().is_some_and(|handle| handle.port == port && handle.shutdown.is_closed()) {
+            *running = None;
+        }
+    });
+
+    info!(port, "Local API server started");
+    Ok(format!("Local API listening on http://127.0.0.1:{}/v1", port))
+}
+
+#[tauri::command]
+pub async fn stop_local_api() -> Result<String, SparrowError> {
+    match LOCAL_API.lock().unwrap().take() {
+        Some(handle) => {
+            let _ = handle.shutdown.send(());
+            Ok(format!("Local API on port {} stopped", handle.port))
+        }
+        None => Err(SparrowError::InvalidInput("Local API is not running".to_string())),
+    }
+}
+
+#[tauri::command]
+pub async fn get_local_api_status() -> Result<LocalApiStatus, SparrowError> {
+    let port = LOCAL_API.lock().unwrap().as_ref().map(|handle| handle.port);
+    Ok(LocalApiStatus { running: port.is_some(), port })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_model_overrides_requested_model() {
+        let body = serde_json::json!({ "model": "gpt-4o", "messages": [] });
+        let rewritten = with_model(body, "Qwen3-8B-int4-ov").unwrap();
+        assert_eq!(rewritten["model"], "Qwen3-8B-int4-ov");
+        assert!(rewritten["messages"].is_array());
+
+        assert!(with_model(serde_json::json!(["not", "an", "object"]), "model").is_err());
+    }
+}
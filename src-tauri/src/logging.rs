@@ -74,11 +74,7 @@ pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
 /// Get the application's log directory
 fn get_log_directory() -> Result<PathBuf, Box<dyn std::error::Error>> {
     // Use user data directory for logs
-    let home_dir = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map_err(|_| "Failed to get user home directory")?;
-    
-    let log_dir = PathBuf::from(home_dir).join(".sparrow").join("logs");
+    let log_dir = crate::paths::sparrow_data_dir()?.join("logs");
     Ok(log_dir)
 }
 
@@ -134,7 +130,7 @@ fn extract_date_from_filename(filename: &str) -> Option<String> {
     None
 }
 
-/// Clean up old archived logs (keep last 30 days)
+/// Clean up old archived logs older than the configured retention (30 days by default)
 pub fn cleanup_old_archives() -> Result<(), Box<dyn std::error::Error>> {
     let log_dir = get_log_directory()?;
     let archive_dir = log_dir.join("archive");
@@ -143,7 +139,8 @@ pub fn cleanup_old_archives() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
     
-    let cutoff_date = Local::now().naive_local().date() - chrono::Duration::days(30);
+    let retention_days = crate::settings::current().log_retention_days as i64;
+    let cutoff_date = Local::now().naive_local().date() - chrono::Duration::days(retention_days);
     
     for entry in fs::read_dir(&archive_dir)? {
         let entry = entry?;
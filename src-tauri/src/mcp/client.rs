@@ -6,11 +6,13 @@ use rmcp::{
     service::RunningService,
     RoleClient,
 };
-use rmcp::model::CallToolRequestParam;
+use rmcp::model::{ CallToolRequestParam, GetPromptRequestParam, PromptMessageContent, PromptMessageRole };
+use rmcp::service::ServiceError;
 use serde::{ Deserialize, Serialize };
 use std::collections::HashMap;
 use std::process::Stdio;
-use tokio::process::Command;
+use tokio::process::{ ChildStderr, Command };
+use tokio::io::{ AsyncBufReadExt, AsyncReadExt, BufReader };
 use async_openai::types::{ ChatCompletionTool, ChatCompletionToolType, FunctionObject };
 use serde_json::Value;
 
@@ -20,11 +22,71 @@ pub struct McpServerInfo {
     pub config: McpServerConfig,
     pub status: String, // "connected", "disconnected", "error"
     pub tools: Vec<String>,
+    /// Why the server is in the "error" state
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// A prompt template offered by an MCP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptArgument {
+    pub name: String,
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+/// One message of a rendered prompt template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptMessage {
+    pub role: String, // "user" or "assistant"
+    pub content: String,
 }
 
 pub struct McpManager {
     config: McpConfig,
     pub clients: HashMap<String, RunningService<RoleClient, ()>>,
+    // Last connection error per server, cleared on a successful connect or an explicit disconnect
+    errors: HashMap<String, String>,
+}
+
+// Errors that mean the connection itself is gone, as opposed to the server rejecting a request
+fn is_connection_lost(error: &ServiceError) -> bool {
+    matches!(error, ServiceError::TransportSend(_) | ServiceError::TransportClosed)
+}
+
+// Read whatever the child wrote to stderr before it exited, without waiting forever
+async fn read_available_stderr(stderr: Option<ChildStderr>) -> String {
+    let mut stderr = match stderr {
+        Some(stderr) => stderr,
+        None => {
+            return String::new();
+        }
+    };
+
+    let mut buffer = Vec::new();
+    let _ = tokio::time::timeout(
+        std::time::Duration::from_millis(500),
+        stderr.read_to_end(&mut buffer)
+    ).await;
+
+    String::from_utf8_lossy(&buffer).trim().to_string()
+}
+
+// Keep draining stderr of a running server so the pipe never fills up and blocks it
+fn forward_child_stderr(server_name: String, stderr: ChildStderr) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            debug!(server_name = %server_name, line = %line, "MCP server stderr");
+        }
+    });
 }
 
 impl McpManager {
@@ -32,18 +94,72 @@ impl McpManager {
         Self {
             config,
             clients: HashMap::new(),
+            errors: HashMap::new(),
+        }
+    }
+
+    /// "connected", "disconnected" or "error" when the last connection attempt or request failed
+    pub fn server_status(&self, name: &str) -> &'static str {
+        if self.errors.contains_key(name) {
+            "error"
+        } else if self.clients.contains_key(name) {
+            "connected"
+        } else {
+            "disconnected"
         }
     }
 
+    pub fn last_error(&self, name: &str) -> Option<String> {
+        self.errors.get(name).cloned()
+    }
+
+    // Drop a client whose connection broke so it is no longer offered, and remember why
+    fn mark_connection_error(&mut self, name: &str, error: String) {
+        warn!(server_name = %name, error = %error, "MCP server connection failed");
+        self.clients.remove(name);
+        self.errors.insert(name.to_string(), error);
+    }
+
+    // SSE and HTTP connections can drop while the server keeps running, so they are worth one reconnect
+    fn can_reconnect(&self, name: &str) -> bool {
+        self.config
+            .get_server(name)
+            .map(|config| config.enabled && config.get_transport_type() != TransportType::Stdio)
+            .unwrap_or(false)
+    }
+
     pub async fn connect_to_server(
         &mut self,
         name: &str
     ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.start_client(name).await {
+            Ok(client) => {
+                self.clients.insert(name.to_string(), client);
+                self.errors.remove(name);
+                info!(server_name = %name, "Successfully connected to MCP server");
+                Ok(())
+            }
+            Err(e) => {
+                self.clients.remove(name);
+                self.errors.insert(name.to_string(), e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    async fn start_client(
+        &self,
+        name: &str
+    ) -> Result<RunningService<RoleClient, ()>, Box<dyn std::error::Error>> {
         info!(server_name = %name, "Attempting to connect to MCP server");
         let server_config = self.config
             .get_server(name)
             .ok_or(format!("Server '{}' not found in configuration", name))?;
 
+        if !server_config.enabled {
+            return Err(format!("Server '{}' is disabled", name).into());
+        }
+
         // Validate configuration
         server_config.validate().map_err(|e| format!("Invalid server configuration: {}", e))?;
 
@@ -106,12 +222,41 @@ impl McpManager {
                     }
                 }
 
-                // Create transport and connect
-                let transport = TokioChildProcess::new(cmd).map_err(|e| {
-                    warn!(command = %command, args = ?args, error = %e, "Failed to create TokioChildProcess");
-                    format!("Failed to start command '{}': {}", command, e)
-                })?;
-                ().serve(transport).await?
+                // Create transport with stderr captured so startup failures can be reported
+                let (transport, stderr) = TokioChildProcess::builder(cmd)
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| {
+                        warn!(command = %command, args = ?args, error = %e, "Failed to create TokioChildProcess");
+                        format!("Failed to start command '{}': {}", command, e)
+                    })?;
+
+                let serve_result = ().serve(transport).await.map_err(|e| e.to_string());
+                match serve_result {
+                    Ok(client) => {
+                        if let Some(stderr) = stderr {
+                            forward_child_stderr(name.to_string(), stderr);
+                        }
+                        client
+                    }
+                    Err(error_message) => {
+                        let stderr_output = read_available_stderr(stderr).await;
+                        warn!(command = %command, error = %error_message, stderr = %stderr_output, "MCP server failed to initialize");
+
+                        return Err(
+                            if stderr_output.is_empty() {
+                                format!("Command '{}' failed to initialize: {}", command, error_message)
+                            } else {
+                                format!(
+                                    "Command '{}' failed to initialize: {}\nServer stderr:\n{}",
+                                    command,
+                                    error_message,
+                                    stderr_output
+                                )
+                            }.into()
+                        );
+                    }
+                }
             }
             TransportType::Sse => {
                 let url = server_config.url.as_ref().unwrap();
@@ -129,18 +274,17 @@ impl McpManager {
             }
         };
 
-        self.clients.insert(name.to_string(), client);
-        info!(server_name = %name, "Successfully connected to MCP server");
-        Ok(())
+        Ok(client)
     }
 
     pub fn disconnect_from_server(&mut self, name: &str) {
         info!(server_name = %name, "Disconnecting from MCP server");
         self.clients.remove(name);
+        self.errors.remove(name);
     }
 
     pub async fn fetch_tools(
-        &self,
+        &mut self,
         server_name: &str
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let client = self.clients
@@ -148,7 +292,15 @@ impl McpManager {
             .ok_or(format!("Server '{}' not connected", server_name))?;
 
         debug!(server_name = %server_name, "Fetching tools from MCP server");
-        let tools_response = client.list_tools(Default::default()).await?;
+        let tools_response = match client.list_tools(Default::default()).await {
+            Ok(response) => response,
+            Err(e) => {
+                if is_connection_lost(&e) {
+                    self.mark_connection_error(server_name, e.to_string());
+                }
+                return Err(e.into());
+            }
+        };
 
         let tool_names: Vec<String> = tools_response.tools
             .iter()
@@ -159,6 +311,95 @@ impl McpManager {
         Ok(tool_names)
     }
 
+    // Servers only answer prompts/list and prompts/get if they advertised the capability
+    fn supports_prompts(client: &RunningService<RoleClient, ()>) -> bool {
+        client
+            .peer_info()
+            .map(|info| info.capabilities.prompts.is_some())
+            .unwrap_or(false)
+    }
+
+    pub async fn list_mcp_prompts(
+        &self,
+        server_name: &str
+    ) -> Result<Vec<McpPromptInfo>, Box<dyn std::error::Error>> {
+        let client = self.clients
+            .get(server_name)
+            .ok_or(format!("Server '{}' not connected", server_name))?;
+
+        if !Self::supports_prompts(client) {
+            debug!(server_name = %server_name, "MCP server does not provide prompts");
+            return Ok(Vec::new());
+        }
+
+        let prompts_response = client.list_prompts(Default::default()).await?;
+
+        let prompts: Vec<McpPromptInfo> = prompts_response.prompts
+            .into_iter()
+            .map(|prompt| McpPromptInfo {
+                name: prompt.name.to_string(),
+                description: prompt.description.map(|d| d.to_string()),
+                arguments: prompt.arguments
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|arg| McpPromptArgument {
+                        name: arg.name.to_string(),
+                        description: arg.description.map(|d| d.to_string()),
+                        required: arg.required.unwrap_or(false),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        info!(server_name = %server_name, prompt_count = prompts.len(), "Found prompts from MCP server");
+        Ok(prompts)
+    }
+
+    pub async fn get_mcp_prompt(
+        &self,
+        server_name: &str,
+        name: &str,
+        args: Option<HashMap<String, String>>
+    ) -> Result<Vec<McpPromptMessage>, Box<dyn std::error::Error>> {
+        let client = self.clients
+            .get(server_name)
+            .ok_or(format!("Server '{}' not connected", server_name))?;
+
+        if !Self::supports_prompts(client) {
+            return Err(format!("Server '{}' does not provide prompts", server_name).into());
+        }
+
+        // Prompt arguments are always strings in the MCP spec
+        let arguments = args.map(|args| {
+            args.into_iter()
+                .map(|(key, value)| (key, Value::String(value)))
+                .collect::<serde_json::Map<String, Value>>()
+        });
+
+        info!(server_name = %server_name, prompt_name = %name, "Getting MCP prompt");
+        let result = client.get_prompt(GetPromptRequestParam {
+            name: name.to_string(),
+            arguments,
+        }).await?;
+
+        let messages = result.messages
+            .into_iter()
+            .map(|message| McpPromptMessage {
+                role: (match message.role {
+                    PromptMessageRole::User => "user",
+                    PromptMessageRole::Assistant => "assistant",
+                }).to_string(),
+                content: match message.content {
+                    PromptMessageContent::Text { text } => text,
+                    // Images and embedded resources are passed through as JSON for the UI
+                    other => serde_json::to_string(&other).unwrap_or_default(),
+                },
+            })
+            .collect();
+
+        Ok(messages)
+    }
+
     pub fn add_server(&mut self, name: String, config: McpServerConfig) {
         self.config.add_server(name, config);
     }
@@ -173,11 +414,22 @@ impl McpManager {
     }
 
     pub async fn get_all_tools_for_openai(
-        &self
+        &mut self
     ) -> Result<Vec<ChatCompletionTool>, Box<dyn std::error::Error>> {
         let mut all_tools = Vec::new();
+        let mut lost_connections = Vec::new();
 
         for (server_name, client) in &self.clients {
+            // Skip servers that were disabled while connected
+            let enabled = self.config
+                .get_server(server_name)
+                .map(|config| config.enabled)
+                .unwrap_or(false);
+            if !enabled {
+                debug!(server_name = %server_name, "Skipping disabled server");
+                continue;
+            }
+
             debug!(server_name = %server_name, "Getting tools from server");
 
             // Get actual tools from the MCP server
@@ -211,17 +463,24 @@ impl McpManager {
                 }
                 Err(e) => {
                     warn!(server_name = %server_name, error = %e, "Failed to get tools from server");
+                    if is_connection_lost(&e) {
+                        lost_connections.push((server_name.clone(), e.to_string()));
+                    }
                     continue;
                 }
             }
         }
 
+        for (server_name, error) in lost_connections {
+            self.mark_connection_error(&server_name, error);
+        }
+
         info!(tool_count = all_tools.len(), "Total MCP tools available");
         Ok(all_tools)
     }
 
     pub async fn call_mcp_tool(
-        &self,
+        &mut self,
         tool_name: &str,
         arguments: Option<serde_json::Map<String, Value>>
     ) -> Result<String, Box<dyn std::error::Error>> {
@@ -240,11 +499,44 @@ impl McpManager {
 
         info!(tool_name = %actual_tool_name, server_name = %server_name, arguments = ?arguments, "Calling MCP tool");
 
-        // Call the actual MCP tool
-        let result = client.call_tool(CallToolRequestParam {
+        let request = CallToolRequestParam {
             name: actual_tool_name.to_string().into(),
             arguments,
-        }).await?;
+        };
+
+        // Call the actual MCP tool, reconnecting once if an SSE/HTTP connection dropped
+        let result = match client.call_tool(request.clone()).await {
+            Ok(result) => result,
+            Err(e) if is_connection_lost(&e) && self.can_reconnect(server_name) => {
+                warn!(server_name = %server_name, error = %e, "MCP connection lost, reconnecting");
+                self.connect_to_server(server_name).await.map_err(|reconnect_error| {
+                    format!(
+                        "Lost connection to server '{}' and could not reconnect: {}",
+                        server_name,
+                        reconnect_error
+                    )
+                })?;
+
+                let client = self.clients
+                    .get(server_name)
+                    .ok_or(format!("Server '{}' not connected", server_name))?;
+                match client.call_tool(request).await {
+                    Ok(result) => result,
+                    Err(e) => {
+                        if is_connection_lost(&e) {
+                            self.mark_connection_error(server_name, e.to_string());
+                        }
+                        return Err(e.into());
+                    }
+                }
+            }
+            Err(e) => {
+                if is_connection_lost(&e) {
+                    self.mark_connection_error(server_name, e.to_string());
+                }
+                return Err(e.into());
+            }
+        };
 
         // Convert MCP result to string
         let result_str = if let Some(content_vec) = result.content.as_ref() {
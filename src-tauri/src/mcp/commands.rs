@@ -1,5 +1,5 @@
 use super::config::{McpConfig, McpServerConfig};
-use super::client::{McpManager, McpServerInfo};
+use super::client::{McpManager, McpPromptInfo, McpPromptMessage, McpServerInfo};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -39,17 +39,12 @@ pub async fn get_mcp_servers(app_handle: AppHandle) -> Result<Vec<McpServerInfo>
         // Get basic server info without tools for now
         let mut servers = Vec::new();
         for (name, config) in manager.get_config().list_servers() {
-            let status = if manager.clients.contains_key(name) {
-                "connected"
-            } else {
-                "disconnected"
-            };
-            
             servers.push(McpServerInfo {
                 name: name.clone(),
                 config: config.clone(),
-                status: status.to_string(),
+                status: manager.server_status(name).to_string(),
                 tools: vec![], // Will be populated separately
+                last_error: manager.last_error(name),
             });
         }
         servers
@@ -88,6 +83,7 @@ pub async fn add_mcp_server(
         args: request.args,
         env: request.env,
         url: request.url,
+        enabled: true,
     };
     
     // Validate the configuration
@@ -109,6 +105,42 @@ pub async fn add_mcp_server(
     Ok(format!("MCP server '{}' added successfully", request.name))
 }
 
+// How long test_mcp_server waits for a server to connect and list its tools
+const MCP_TEST_TIMEOUT_SECS: u64 = 15;
+
+#[tauri::command]
+pub async fn test_mcp_server(request: AddServerRequest) -> Result<Vec<String>, String> {
+    let server_config = McpServerConfig {
+        command: request.command,
+        args: request.args,
+        env: request.env,
+        url: request.url,
+        enabled: true,
+    };
+    
+    // Validate the configuration
+    server_config.validate().map_err(|e| format!("Invalid configuration: {}", e))?;
+    
+    // Use a throwaway manager so nothing touches the saved config or live connections
+    let mut config = McpConfig::default();
+    config.add_server(request.name.clone(), server_config);
+    let mut test_manager = McpManager::new(config);
+    
+    let timeout = std::time::Duration::from_secs(MCP_TEST_TIMEOUT_SECS);
+    
+    tokio::time::timeout(timeout, test_manager.connect_to_server(&request.name)).await
+        .map_err(|_| format!("Timed out after {}s connecting to server '{}'", MCP_TEST_TIMEOUT_SECS, request.name))
+        .and_then(|result| result.map_err(|e| format!("Failed to connect to server '{}': {}", request.name, e)))?;
+    
+    let tools_result = tokio::time::timeout(timeout, test_manager.fetch_tools(&request.name)).await
+        .map_err(|_| format!("Timed out after {}s fetching tools from server '{}'", MCP_TEST_TIMEOUT_SECS, request.name))
+        .and_then(|result| result.map_err(|e| format!("Failed to fetch tools from server '{}': {}", request.name, e)));
+    
+    test_manager.disconnect_from_server(&request.name);
+    
+    tools_result
+}
+
 #[tauri::command]
 pub async fn edit_mcp_server(
     app_handle: AppHandle,
@@ -116,11 +148,12 @@ pub async fn edit_mcp_server(
 ) -> Result<String, String> {
     get_or_init_manager(&app_handle).await?;
     
-    let server_config = McpServerConfig {
+    let mut server_config = McpServerConfig {
         command: request.command,
         args: request.args,
         env: request.env,
         url: request.url,
+        enabled: true,
     };
     
     // Validate the configuration
@@ -140,7 +173,11 @@ pub async fn edit_mcp_server(
             return Err(format!("Cannot edit server '{}' while it is connected. Please disconnect first.", request.name));
         }
         
-        // Update the server configuration
+        // Update the server configuration, keeping its enabled state
+        server_config.enabled = manager.get_config()
+            .get_server(&request.name)
+            .map(|existing| existing.enabled)
+            .unwrap_or(true);
         manager.add_server(request.name.clone(), server_config);
         
         // Save config to file
@@ -177,6 +214,42 @@ pub async fn remove_mcp_server(
     Ok(format!("MCP server '{}' removed successfully", server_name))
 }
 
+#[tauri::command]
+pub async fn set_mcp_server_enabled(
+    app_handle: AppHandle,
+    server_name: String,
+    enabled: bool,
+) -> Result<String, String> {
+    get_or_init_manager(&app_handle).await?;
+    
+    {
+        let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let manager = manager_guard.as_mut().ok_or("Manager not initialized")?;
+        
+        let mut server_config = manager.get_config()
+            .get_server(&server_name)
+            .cloned()
+            .ok_or_else(|| format!("Server '{}' not found", server_name))?;
+        
+        server_config.enabled = enabled;
+        manager.add_server(server_name.clone(), server_config);
+        
+        // A disabled server should stop contributing tools immediately
+        if !enabled {
+            manager.disconnect_from_server(&server_name);
+        }
+        
+        // Save config to file
+        let config_path = McpConfig::get_config_path(&app_handle)
+            .map_err(|e| format!("Failed to get config path: {}", e))?;
+        manager.get_config().save_to_file(&config_path)
+            .map_err(|e| format!("Failed to save config: {}", e))?;
+    }
+    
+    let state = if enabled { "enabled" } else { "disabled" };
+    Ok(format!("MCP server '{}' {}", server_name, state))
+}
+
 #[tauri::command]
 pub async fn connect_mcp_server(
     app_handle: AppHandle,
@@ -235,17 +308,12 @@ pub async fn get_mcp_server_info(
         let manager = manager_guard.as_ref().ok_or("Manager not initialized")?;
         
         if let Some(config) = manager.get_config().get_server(&server_name) {
-            let status = if manager.clients.contains_key(&server_name) {
-                "connected"
-            } else {
-                "disconnected"
-            };
-            
             Some(McpServerInfo {
                 name: server_name.clone(),
                 config: config.clone(),
-                status: status.to_string(),
+                status: manager.server_status(&server_name).to_string(),
                 tools: vec![], // Will be populated below if connected
+                last_error: manager.last_error(&server_name),
             })
         } else {
             None
@@ -272,7 +340,7 @@ pub async fn fetch_mcp_server_tools(
     get_or_init_manager(&app_handle).await?;
     
     // Similar pattern - extract manager temporarily
-    let temp_manager = {
+    let mut temp_manager = {
         let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
         manager_guard.take().ok_or("Manager not initialized")?
     };
@@ -297,7 +365,7 @@ pub async fn get_all_mcp_tools_for_chat(
     get_or_init_manager(&app_handle).await?;
     
     // Extract manager temporarily
-    let temp_manager = {
+    let mut temp_manager = {
         let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
         manager_guard.take().ok_or("Manager not initialized")?
     };
@@ -324,7 +392,7 @@ pub async fn call_mcp_tool(
     get_or_init_manager(&app_handle).await?;
     
     // Extract manager temporarily
-    let temp_manager = {
+    let mut temp_manager = {
         let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
         manager_guard.take().ok_or("Manager not initialized")?
     };
@@ -340,4 +408,58 @@ pub async fn call_mcp_tool(
     
     // Handle result
     call_result.map_err(|e| format!("Failed to call MCP tool: {}", e))
+}
+
+#[tauri::command]
+pub async fn list_mcp_prompts(
+    app_handle: AppHandle,
+    server_name: String,
+) -> Result<Vec<McpPromptInfo>, String> {
+    get_or_init_manager(&app_handle).await?;
+    
+    // Extract manager temporarily
+    let temp_manager = {
+        let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
+        manager_guard.take().ok_or("Manager not initialized")?
+    };
+    
+    // List prompts (this is async)
+    let prompts_result = temp_manager.list_mcp_prompts(&server_name).await;
+    
+    // Put the manager back
+    {
+        let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
+        *manager_guard = Some(temp_manager);
+    }
+    
+    // Handle result
+    prompts_result.map_err(|e| format!("Failed to list MCP prompts: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_mcp_prompt(
+    app_handle: AppHandle,
+    server_name: String,
+    name: String,
+    args: Option<HashMap<String, String>>,
+) -> Result<Vec<McpPromptMessage>, String> {
+    get_or_init_manager(&app_handle).await?;
+    
+    // Extract manager temporarily
+    let temp_manager = {
+        let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
+        manager_guard.take().ok_or("Manager not initialized")?
+    };
+    
+    // Render the prompt (this is async)
+    let prompt_result = temp_manager.get_mcp_prompt(&server_name, &name, args).await;
+    
+    // Put the manager back
+    {
+        let mut manager_guard = MCP_MANAGER.lock().map_err(|e| format!("Lock error: {}", e))?;
+        *manager_guard = Some(temp_manager);
+    }
+    
+    // Handle result
+    prompt_result.map_err(|e| format!("Failed to get MCP prompt: {}", e))
 }
\ No newline at end of file
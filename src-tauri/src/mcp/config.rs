@@ -16,6 +16,14 @@ pub struct McpServerConfig {
     // For SSE and HTTP transports
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    
+    // Disabled servers keep their config but are never connected or offered to chat
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 impl McpServerConfig {
@@ -98,13 +106,7 @@ impl McpConfig {
     }
     
     pub fn get_config_path(_app_handle: &tauri::AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-            Ok(home) => home,
-            Err(_) => {
-                return Err("Failed to get user home directory".into());
-            }
-        };
-        let sparrow_dir = PathBuf::from(home_dir).join(".sparrow");
+        let sparrow_dir = crate::paths::sparrow_data_dir()?;
         Ok(sparrow_dir.join("mcp_config.json"))
     }
     
@@ -1,18 +1,22 @@
+use crate::errors::SparrowError;
 use std::fs;
-use std::io::{ Write, Read };
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{ Component, Path, PathBuf };
 use std::process::{ Command, Stdio, Child };
 use std::sync::{ Arc, Mutex };
+use std::sync::atomic::{ AtomicBool, Ordering };
 use zip::ZipArchive;
 use serde_json::{ json, Value };
 use serde::{ Deserialize, Serialize };
-use tauri::AppHandle;
+use tauri::{ AppHandle, Emitter };
 use tracing::{ info, warn, error, debug };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OvmsStatus {
     pub status: String,
     pub loaded_models: Vec<String>,
+    /// Model entries whose status couldn't be read, so they are neither loaded nor absent
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,23 +40,121 @@ struct ModelInfo {
     model_version_status: Vec<ModelVersionStatus>,
 }
 
-const OVMS_DOWNLOAD_URL: &str =
-    "https://github.com/openvinotoolkit/model_server/releases/download/v2025.3/ovms_windows_python_off.zip";
-const OVMS_ZIP_FILE: &str = "ovms_windows_python_off.zip";
+// How long to wait for OVMS to report a newly configured model as available
+const MODEL_AVAILABLE_TIMEOUT_SECS: u64 = 180;
+
+// Rough runtime overhead on top of the IR weights (KV cache, compiled graph)
+const MODEL_MEMORY_OVERHEAD: f64 = 1.2;
+// Share of available memory above which a model is considered a tight fit
+const TIGHT_FIT_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FitLevel {
+    #[serde(rename = "ok")]
+    Fits,
+    Tight,
+    WillNotFit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OvmsCacheEntry {
+    pub model_id: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OvmsCacheInfo {
+    pub total_bytes: u64,
+    pub models: Vec<OvmsCacheEntry>,
+}
+
+/// How a model's estimated memory use compares to the available system RAM. GPU models are
+/// judged against RAM too, since dedicated VRAM can't be queried portably
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FitEstimate {
+    pub model_id: String,
+    pub device: String,
+    pub model_size_bytes: u64,
+    pub estimated_required_bytes: u64,
+    pub total_memory_bytes: u64,
+    pub available_memory_bytes: u64,
+    pub level: FitLevel,
+    pub message: String,
+}
+
+// The OVMS build SparrowAI downloads and runs
+struct OvmsRelease {
+    version: &'static str,
+    url: &'static str,
+    zip_file: &'static str,
+    /// Lowercase hex SHA-256 of the zip, as listed for the asset on the GitHub release page
+    sha256: &'static str,
+}
+
+const OVMS_RELEASE: OvmsRelease = OvmsRelease {
+    version: "2025.3",
+    url: "https://github.com/openvinotoolkit/model_server/releases/download/v2025.3/ovms_windows_python_off.zip",
+    zip_file: "ovms_windows_python_off.zip",
+    // TODO: paste the digest GitHub lists for ovms_windows_python_off.zip in v2025.3.
+    // Until it is filled in, download_ovms refuses to install rather than skip the check
+    sha256: "",
+};
 
 // Global OVMS process management
 static OVMS_PROCESS: std::sync::OnceLock<Arc<Mutex<Option<Child>>>> = std::sync::OnceLock::new();
 
+/// Lifecycle of the model SparrowAI serves through OVMS, serialized as
+/// `{ "state": "loading", "model_id": "OpenVINO/..." }`
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "state", content = "model_id", rename_all = "snake_case")]
+pub enum LoadedModelStatus {
+    #[default]
+    None,
+    /// Config written and OVMS reloading/compiling, not yet available
+    Loading(String),
+    Loaded(String),
+}
+
+impl LoadedModelStatus {
+    /// The model being loaded or loaded, if any
+    pub fn model_id(&self) -> Option<&str> {
+        match self {
+            LoadedModelStatus::None => None,
+            LoadedModelStatus::Loading(model_id) | LoadedModelStatus::Loaded(model_id) => Some(model_id),
+        }
+    }
+}
+
 // Global loaded model state
-pub static LOADED_MODEL: std::sync::OnceLock<Arc<Mutex<Option<String>>>> = std::sync::OnceLock::new();
+pub static LOADED_MODEL: std::sync::OnceLock<Arc<Mutex<LoadedModelStatus>>> = std::sync::OnceLock::new();
+
+fn loaded_model_state() -> &'static Arc<Mutex<LoadedModelStatus>> {
+    LOADED_MODEL.get_or_init(|| Arc::new(Mutex::new(LoadedModelStatus::None)))
+}
+
+pub fn current_loaded_model() -> LoadedModelStatus {
+    loaded_model_state().lock().unwrap().clone()
+}
+
+// Set by cancel_ovms_init, checked between initialization steps and while downloading OVMS
+static INIT_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+pub fn request_init_cancel() {
+    INIT_CANCELLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_init_cancelled() -> bool {
+    INIT_CANCELLED.load(Ordering::SeqCst)
+}
+
+pub fn reset_init_cancel() {
+    INIT_CANCELLED.store(false, Ordering::SeqCst);
+}
 
 pub fn get_sparrow_dir(_app_handle: Option<&AppHandle>) -> PathBuf {
-    // Get the base .sparrow directory
-    let home_dir = std::env
-        ::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home_dir).join(".sparrow")
+    // Get the base data directory (defaults to ~/.sparrow)
+    crate::paths::sparrow_data_dir().unwrap_or_else(|_| PathBuf::from(".sparrow"))
 }
 
 pub fn get_ovms_dir(app_handle: Option<&AppHandle>) -> PathBuf {
@@ -64,6 +166,47 @@ pub fn get_ovms_config_path(app_handle: Option<&AppHandle>) -> PathBuf {
     get_ovms_dir(app_handle).join("models_config.json")
 }
 
+pub fn get_ovms_log_path(app_handle: Option<&AppHandle>) -> PathBuf {
+    get_ovms_dir(app_handle).join("ovms.log")
+}
+
+// OVMS appends to its log file, so the previous one is rotated out once it gets this big
+const OVMS_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+// Keep one previous log as ovms.log.1 when the current one is over the size limit
+fn rotate_ovms_log(log_path: &Path) {
+    let too_big = fs::metadata(log_path).map(|metadata| metadata.len() > OVMS_LOG_MAX_BYTES).unwrap_or(false);
+    if too_big {
+        if let Err(e) = fs::rename(log_path, log_path.with_extension("log.1")) {
+            warn!(error = %e, "Failed to rotate OVMS log");
+        }
+    }
+}
+
+// The last `count` lines of `contents`, oldest first
+fn last_lines(contents: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// The last `lines` lines OVMS wrote to its log file
+#[tauri::command]
+pub async fn get_ovms_log(lines: usize) -> Result<Vec<String>, SparrowError> {
+    let log_path = get_ovms_log_path(None);
+    if !log_path.exists() {
+        return Err(SparrowError::NotFound(format!("No OVMS log at {}", log_path.display())));
+    }
+
+    // Lossy so a partially written multi-byte character at the end doesn't fail the read
+    let bytes = fs
+        ::read(&log_path)
+        .map_err(|e| SparrowError::Io(format!("Failed to read OVMS log: {}", e)))?;
+    Ok(last_lines(&String::from_utf8_lossy(&bytes), lines))
+}
+
 pub fn get_ovms_exe_path(app_handle: Option<&AppHandle>) -> PathBuf {
     // With the new extraction method, ovms.exe is directly in the ovms folder
     get_ovms_dir(app_handle).join("ovms.exe")
@@ -138,7 +281,7 @@ pub fn validate_ovms_config(config_path: &PathBuf) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn download_ovms(app_handle: AppHandle) -> Result<String, String> {
+pub async fn download_ovms(app_handle: AppHandle) -> Result<String, SparrowError> {
     let sparrow_dir = get_sparrow_dir(Some(&app_handle));
     let ovms_dir = get_ovms_dir(Some(&app_handle));
 
@@ -146,16 +289,16 @@ pub async fn download_ovms(app_handle: AppHandle) -> Result<String, String> {
     if !sparrow_dir.exists() {
         fs
             ::create_dir_all(&sparrow_dir)
-            .map_err(|e| format!("Failed to create .sparrow directory: {}", e))?;
+            .map_err(|e| SparrowError::Io(format!("Failed to create .sparrow directory: {}", e)))?;
     }
     if !ovms_dir.exists() {
         fs
             ::create_dir_all(&ovms_dir)
-            .map_err(|e| format!("Failed to create ovms directory: {}", e))?;
+            .map_err(|e| SparrowError::Io(format!("Failed to create ovms directory: {}", e)))?;
     }
 
     // Download zip to .sparrow root directory
-    let zip_path = sparrow_dir.join(OVMS_ZIP_FILE);
+    let zip_path = sparrow_dir.join(OVMS_RELEASE.zip_file);
 
     // Check if OVMS executable already exists
     let ovms_exe = get_ovms_exe_path(Some(&app_handle));
@@ -173,23 +316,32 @@ pub async fn download_ovms(app_handle: AppHandle) -> Result<String, String> {
     }
 
     // Download the file with retry logic and better error handling
-    let client = reqwest::Client
-        ::builder()
-        .user_agent("intel-ai-corebuilder/0.1.0")
-        .timeout(std::time::Duration::from_secs(600)) // 10 minute timeout
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let client = crate::http::http_client(crate::http::OVMS_DOWNLOAD_TIMEOUT)?;
+
+    info!(url = %OVMS_RELEASE.url, version = %OVMS_RELEASE.version, "Starting OVMS download");
 
-    info!(url = %OVMS_DOWNLOAD_URL, "Starting OVMS download");
+    if OVMS_RELEASE.sha256.is_empty() {
+        return Err(
+            SparrowError::Ovms(format!("No checksum pinned for OVMS {}, refusing to install it", OVMS_RELEASE.version))
+        );
+    }
 
     let mut retries = 3;
 
     while retries > 0 {
-        match download_and_validate(&client, &zip_path).await {
-            Ok(_bytes) => {
+        match download_and_validate(&client, &zip_path, OVMS_RELEASE.sha256, &app_handle).await {
+            Ok(()) => {
                 break;
             }
             Err(e) => {
+                if is_init_cancelled() {
+                    if zip_path.exists() {
+                        let _ = fs::remove_file(&zip_path);
+                    }
+                    info!("OVMS download cancelled");
+                    return Err(SparrowError::Cancelled("OVMS download cancelled".to_string()));
+                }
+
                 retries -= 1;
                 warn!(error = %e, attempts_left = retries, "Download attempt failed");
 
@@ -199,7 +351,9 @@ pub async fn download_ovms(app_handle: AppHandle) -> Result<String, String> {
                 }
 
                 if retries == 0 {
-                    return Err(format!("Failed to download OVMS after 3 attempts: {}", e));
+                    return Err(
+                        SparrowError::Network(format!("Failed to download OVMS after 3 attempts: {}", e))
+                    );
                 }
 
                 tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
@@ -207,10 +361,15 @@ pub async fn download_ovms(app_handle: AppHandle) -> Result<String, String> {
         }
     }
 
+    if is_init_cancelled() {
+        let _ = fs::remove_file(&zip_path);
+        return Err(SparrowError::Cancelled("OVMS download cancelled".to_string()));
+    }
+
     info!("Download completed successfully, extracting...");
 
     // Extract the zip file to ovms directory
-    extract_ovms(&zip_path, &ovms_dir)?;
+    extract_ovms(&zip_path, &ovms_dir).map_err(SparrowError::Io)?;
 
     // Clean up the zip file after successful extraction
     if zip_path.exists() {
@@ -224,73 +383,119 @@ pub async fn download_ovms(app_handle: AppHandle) -> Result<String, String> {
     Ok("OVMS downloaded and extracted successfully".to_string())
 }
 
+// Stream the release archive to disk, hashing as it goes, then check the ZIP structure
 async fn download_and_validate(
     client: &reqwest::Client,
-    zip_path: &PathBuf
-) -> Result<Vec<u8>, String> {
+    zip_path: &PathBuf,
+    expected_sha256: &str,
+    app_handle: &AppHandle
+) -> Result<(), String> {
+    use futures::StreamExt;
+    use sha2::{ Digest, Sha256 };
+    use tokio::io::AsyncWriteExt;
+
     let response = client
-        .get(OVMS_DOWNLOAD_URL)
+        .get(OVMS_RELEASE.url)
         .send().await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+        .map_err(|e| crate::http::request_error_message("Failed to send request", &e))?;
 
     if !response.status().is_success() {
         return Err(format!("Download failed with status: {}", response.status()));
     }
 
-    // Get content length for validation
+    // Get content length for validation and progress
     let expected_length = response.content_length();
     if let Some(length) = expected_length {
         info!(size_mb = length / 1024 / 1024, "Downloading OVMS");
     }
 
-    let bytes = response
-        .bytes().await
-        .map_err(|e| format!("Failed to read response bytes: {}", e))?;
+    let mut file = tokio::fs::File
+        ::create(zip_path).await
+        .map_err(|e| format!("Failed to create zip file: {}", e))?;
+
+    // Write each chunk straight to disk so the archive never has to fit in memory
+    let mut stream = response.bytes_stream();
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0u64;
+    let mut last_progress_emit = std::time::Instant::now();
+    while let Some(chunk) = stream.next().await {
+        if is_init_cancelled() {
+            return Err("Download cancelled".to_string());
+        }
+        let chunk = chunk.map_err(|e| crate::http::request_error_message("Failed to read response bytes", &e))?;
+        file.write_all(&chunk).await.map_err(|e| format!("Failed to write zip file: {}", e))?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+
+        if last_progress_emit.elapsed().as_millis() > 100 || Some(downloaded) == expected_length {
+            emit_ovms_download_progress(app_handle, downloaded, expected_length);
+            last_progress_emit = std::time::Instant::now();
+        }
+    }
+
+    file.flush().await.map_err(|e| format!("Failed to flush zip file: {}", e))?;
+    drop(file);
+    emit_ovms_download_progress(app_handle, downloaded, expected_length);
 
     // Validate content length if provided
     if let Some(expected) = expected_length {
-        if (bytes.len() as u64) != expected {
+        if downloaded != expected {
             return Err(
-                format!(
-                    "Downloaded size mismatch: expected {} bytes, got {} bytes",
-                    expected,
-                    bytes.len()
-                )
+                format!("Downloaded size mismatch: expected {} bytes, got {} bytes", expected, downloaded)
             );
         }
     }
 
-    // Validate that it's a valid ZIP file before writing
-    validate_zip_bytes(&bytes)?;
+    // A truncated or corrupted zip can still have a valid structure, so check the digest too
+    verify_sha256(&format!("{:x}", hasher.finalize()), expected_sha256)?;
 
-    info!("Download validation passed, writing to file...");
+    validate_zip_file(zip_path)?;
 
-    // Write to file
-    let mut file = fs::File
-        ::create(zip_path)
-        .map_err(|e| format!("Failed to create zip file: {}", e))?;
+    info!("Download validation passed");
+    Ok(())
+}
 
-    file.write_all(&bytes).map_err(|e| format!("Failed to write zip file: {}", e))?;
+fn emit_ovms_download_progress(app_handle: &AppHandle, downloaded: u64, total: Option<u64>) {
+    let progress = match total {
+        Some(total) if total > 0 => ((((downloaded as f64) / (total as f64)) * 100.0) as u32).min(100),
+        _ => 0,
+    };
 
-    Ok(bytes.into())
+    let _ = app_handle.emit(
+        "ovms-download-progress",
+        json!({
+            "downloadedBytes": downloaded,
+            "totalBytes": total,
+            "progress": progress
+        })
+    );
 }
 
-fn validate_zip_bytes(bytes: &[u8]) -> Result<(), String> {
-    use std::io::Cursor;
-
-    // Check if it starts with ZIP magic number
-    if bytes.len() < 4 {
-        return Err("File too small to be a valid ZIP".to_string());
+fn verify_sha256(actual: &str, expected: &str) -> Result<(), String> {
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(
+            format!("Checksum mismatch for OVMS download: expected {}, got {}", expected, actual)
+        );
     }
 
+    info!("OVMS download checksum verified");
+    Ok(())
+}
+
+fn validate_zip_file(zip_path: &PathBuf) -> Result<(), String> {
+    let mut file = fs::File::open(zip_path).map_err(|e| format!("Failed to open zip file: {}", e))?;
+
     // ZIP files start with "PK" (0x504B)
-    if &bytes[0..2] != b"PK" {
+    let mut signature = [0u8; 4];
+    file
+        .read_exact(&mut signature)
+        .map_err(|_| "File too small to be a valid ZIP".to_string())?;
+    if &signature[0..2] != b"PK" {
         return Err("Invalid ZIP file signature".to_string());
     }
 
     // Try to open as ZIP archive to validate structure
-    let cursor = Cursor::new(bytes);
-    match zip::ZipArchive::new(cursor) {
+    match zip::ZipArchive::new(file) {
         Ok(archive) => {
             if archive.len() == 0 {
                 return Err("ZIP file is empty".to_string());
@@ -302,9 +507,42 @@ fn validate_zip_bytes(bytes: &[u8]) -> Result<(), String> {
     }
 }
 
+// Join an archive entry onto the extraction root, or None if it would land outside of it
+fn safe_extract_path(extract_root: &Path, relative_path: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative_path);
+    let stays_inside = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+    if stays_inside {
+        Some(extract_root.join(relative))
+    } else {
+        None
+    }
+}
+
+// Permission bits for an extracted file, forcing the ovms binary to be executable
+// even when the archive was built on Windows and carries no unix mode
+#[cfg(not(target_os = "windows"))]
+fn extracted_file_mode(unix_mode: Option<u32>, path: &Path) -> Option<u32> {
+    let is_ovms_binary = path.file_name().and_then(|name| name.to_str()) == Some("ovms");
+    match (unix_mode.map(|mode| mode & 0o7777), is_ovms_binary) {
+        (Some(mode), true) => Some(mode | 0o755),
+        (None, true) => Some(0o755),
+        (mode, false) => mode,
+    }
+}
+
 pub fn extract_ovms(zip_path: &PathBuf, extract_to: &PathBuf) -> Result<(), String> {
     let file = fs::File::open(zip_path).map_err(|e| format!("Failed to open zip file: {}", e))?;
 
+    fs
+        ::create_dir_all(extract_to)
+        .map_err(|e| format!("Failed to create {}: {}", extract_to.display(), e))?;
+    let extract_root = extract_to
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", extract_to.display(), e))?;
+
     let mut archive = ZipArchive::new(file).map_err(|e|
         format!("Failed to read zip archive: {}", e)
     )?;
@@ -336,7 +574,14 @@ pub fn extract_ovms(zip_path: &PathBuf, extract_to: &PathBuf) -> Result<(), Stri
             continue;
         }
 
-        let outpath = extract_to.join(relative_path);
+        // Reject entries like "../x" or absolute paths that would escape the target (zip-slip)
+        let outpath = match safe_extract_path(&extract_root, relative_path) {
+            Some(outpath) => outpath,
+            None => {
+                warn!(file_name = %file_name, "Skipping archive entry outside the extraction directory");
+                continue;
+            }
+        };
 
         // Create parent directories if needed
         if let Some(p) = outpath.parent() {
@@ -347,6 +592,15 @@ pub fn extract_ovms(zip_path: &PathBuf, extract_to: &PathBuf) -> Result<(), Stri
                         format!("Failed to create parent directory {}: {}", p.display(), e)
                     )?;
             }
+
+            // A symlinked directory already inside the target could still point elsewhere
+            let resolved_parent = p
+                .canonicalize()
+                .map_err(|e| format!("Failed to resolve {}: {}", p.display(), e))?;
+            if !resolved_parent.starts_with(&extract_root) {
+                warn!(file_name = %file_name, "Skipping archive entry outside the extraction directory");
+                continue;
+            }
         }
 
         // Extract the file
@@ -358,6 +612,18 @@ pub fn extract_ovms(zip_path: &PathBuf, extract_to: &PathBuf) -> Result<(), Stri
             ::copy(&mut file, &mut outfile)
             .map_err(|e| format!("Failed to extract file {}: {}", outpath.display(), e))?;
 
+        // Zip extraction drops mode bits, restore them so binaries stay executable
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if let Some(mode) = extracted_file_mode(file.unix_mode(), &outpath) {
+                fs
+                    ::set_permissions(&outpath, fs::Permissions::from_mode(mode))
+                    .map_err(|e| format!("Failed to set permissions on {}: {}", outpath.display(), e))?;
+            }
+        }
+
         debug!(output_path = %outpath.display(), "File extracted");
     }
 
@@ -365,123 +631,77 @@ pub fn extract_ovms(zip_path: &PathBuf, extract_to: &PathBuf) -> Result<(), Stri
     Ok(())
 }
 
-#[tauri::command]
-pub async fn create_ovms_config(
-    app_handle: AppHandle,
-    model_name: String,
-    model_path: String
-) -> Result<String, String> {
-    // Always include both BGE models as the first entries
-    let home_dir = std::env
-        ::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .unwrap_or_else(|_| ".".to_string());
-    let bge_reranker_path = PathBuf::from(&home_dir)
-        .join(".sparrow")
-        .join("models")
-        .join("OpenVINO")
-        .join("bge-reranker-base-int8-ov");
-    let bge_base_path = PathBuf::from(&home_dir)
-        .join(".sparrow")
-        .join("models")
-        .join("OpenVINO")
-        .join("bge-base-en-v1.5-int8-ov");
-
-    let mut mediapipe_configs = vec![
-        json!({
-            "name": "bge-reranker-base-int8-ov",
-            "base_path": bge_reranker_path.to_string_lossy().replace('\\', "/")
-        }),
-        json!({
-            "name": "bge-base-en-v1.5-int8-ov",
-            "base_path": bge_base_path.to_string_lossy().replace('\\', "/")
-        })
-    ];
+const BGE_RERANKER_MODEL: &str = "bge-reranker-base-int8-ov";
+const BGE_EMBEDDING_MODEL: &str = "bge-base-en-v1.5-int8-ov";
 
-    // Add the provided model if it's not one of the BGE models
-    if model_name != "bge-reranker-base-int8-ov" && model_name != "bge-base-en-v1.5-int8-ov" {
-        mediapipe_configs.push(
-            json!({
-            "name": model_name,
-            "base_path": model_path.replace('\\', "/")
-        })
-        );
+// Read the current OVMS config file, None if it doesn't exist yet
+fn read_ovms_config(config_path: &PathBuf) -> Result<Option<Value>, SparrowError> {
+    if !config_path.exists() {
+        return Ok(None);
     }
 
-    let config =
-        json!({
-        "mediapipe_config_list": mediapipe_configs,
-        "model_config_list": []
-    });
-
-    let config_str = serde_json
-        ::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-
-    let config_path = get_ovms_config_path(Some(&app_handle));
-    fs::write(&config_path, config_str).map_err(|e| format!("Failed to write config file: {}", e))?;
-
-    Ok("OVMS configuration file created successfully".to_string())
+    let config_str = fs
+        ::read_to_string(config_path)
+        .map_err(|e| SparrowError::Io(format!("Failed to read config file: {}", e)))?;
+    serde_json
+        ::from_str(&config_str)
+        .map(Some)
+        .map_err(|e| SparrowError::Parse(format!("Failed to parse config file: {}", e)))
 }
 
-#[tauri::command]
-pub async fn update_ovms_config(
-    app_handle: AppHandle,
-    model_name: String,
-    model_path: String
-) -> Result<String, String> {
-    let config_path = get_ovms_config_path(Some(&app_handle));
-
-    // Read existing config or create new one
-    let mut config: Value = if config_path.exists() {
-        let config_str = fs
-            ::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config file: {}", e))?;
-        serde_json
-            ::from_str(&config_str)
-            .map_err(|e| format!("Failed to parse config file: {}", e))?
-    } else {
+/// Build the OVMS config that serves `model_name` from `model_path`, starting from `existing`.
+/// Both BGE models are always kept as the first entries. OVMS serves one chat model at a time,
+/// so any other non-BGE model is removed; their names are returned alongside the config.
+/// Nothing is written to disk.
+fn build_ovms_config(
+    existing: Option<Value>,
+    model_name: &str,
+    model_path: &str,
+    models_dir: &Path
+) -> (Value, Vec<String>) {
+    let mut config = existing.unwrap_or_else(|| {
         json!({
             "mediapipe_config_list": [],
             "model_config_list": []
         })
-    };
+    });
 
     // Normalize the model_path to use forward slashes for OVMS
     let normalized_model_path = model_path.replace('\\', "/");
 
     // Always ensure both BGE models are present
-    let home_dir = std::env
-        ::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .unwrap_or_else(|_| ".".to_string());
-    let bge_reranker_path = PathBuf::from(&home_dir)
-        .join(".sparrow")
-        .join("models")
-        .join("OpenVINO")
-        .join("bge-reranker-base-int8-ov");
-    let bge_base_path = PathBuf::from(&home_dir)
-        .join(".sparrow")
-        .join("models")
-        .join("OpenVINO")
-        .join("bge-base-en-v1.5-int8-ov");
+    let bge_reranker_path = models_dir.join("OpenVINO").join(BGE_RERANKER_MODEL);
+    let bge_base_path = models_dir.join("OpenVINO").join(BGE_EMBEDDING_MODEL);
+
+    let mut replaced_models = Vec::new();
 
     if let Some(model_list) = config["mediapipe_config_list"].as_array_mut() {
-        // Check which BGE models already exist and find the third model index
+        // Drop every other chat model up front so indexes can't go stale below
+        let is_bge = |name: &str| name == BGE_RERANKER_MODEL || name == BGE_EMBEDDING_MODEL;
+        model_list.retain(|model| {
+            match model["name"].as_str() {
+                Some(name) if !is_bge(name) && name != model_name => {
+                    replaced_models.push(name.to_string());
+                    false
+                }
+                _ => true,
+            }
+        });
+
+        // Check which BGE models already exist
         let mut has_bge_reranker = false;
         let mut has_bge_base = false;
-        let mut third_model_index = None;
         let mut found_target_model = false;
 
-        for (index, model) in model_list.iter_mut().enumerate() {
+        for model in model_list.iter_mut() {
             if let Some(name) = model["name"].as_str() {
-                if name == "bge-reranker-base-int8-ov" {
+                if name == BGE_RERANKER_MODEL {
                     has_bge_reranker = true;
                     // Update the path in case it changed
                     model["base_path"] = json!(
                         bge_reranker_path.to_string_lossy().replace('\\', "/")
                     );
-                } else if name == "bge-base-en-v1.5-int8-ov" {
+                } else if name == BGE_EMBEDDING_MODEL {
                     has_bge_base = true;
                     // Update the path in case it changed
                     model["base_path"] = json!(bge_base_path.to_string_lossy().replace('\\', "/"));
@@ -489,11 +709,6 @@ pub async fn update_ovms_config(
                     // Target model already exists, just update its path
                     model["base_path"] = json!(normalized_model_path);
                     found_target_model = true;
-                } else {
-                    // This is a third model (not BGE models)
-                    if third_model_index.is_none() {
-                        third_model_index = Some(index);
-                    }
                 }
             }
         }
@@ -504,7 +719,7 @@ pub async fn update_ovms_config(
             model_list.insert(
                 insert_index,
                 json!({
-                "name": "bge-reranker-base-int8-ov",
+                "name": BGE_RERANKER_MODEL,
                 "base_path": bge_reranker_path.to_string_lossy().replace('\\', "/")
             })
             );
@@ -514,63 +729,174 @@ pub async fn update_ovms_config(
             model_list.insert(
                 insert_index,
                 json!({
-                "name": "bge-base-en-v1.5-int8-ov",
+                "name": BGE_EMBEDDING_MODEL,
                 "base_path": bge_base_path.to_string_lossy().replace('\\', "/")
             })
             );
         }
 
-        // Handle the third model if the target model is not one of the BGE models
-        if
-            !found_target_model &&
-            model_name != "bge-reranker-base-int8-ov" &&
-            model_name != "bge-base-en-v1.5-int8-ov"
-        {
-            let new_model_config =
+        // Add the target model after the BGE models if it isn't configured yet
+        if !found_target_model && !is_bge(model_name) {
+            model_list.push(
                 json!({
                 "name": model_name,
                 "base_path": normalized_model_path
-            });
-
-            if let Some(third_index) = third_model_index {
-                // Replace the existing third model
-                model_list[third_index] = new_model_config;
-            } else {
-                // No third model exists, add it
-                model_list.push(new_model_config);
-            }
+            })
+            );
         }
     }
 
-    let config_str = serde_json
-        ::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    (config, replaced_models)
+}
+
+// Describe the outcome of a config update, naming any chat model that was replaced
+fn config_update_message(replaced_models: &[String]) -> String {
+    if replaced_models.is_empty() {
+        "OVMS configuration updated successfully".to_string()
+    } else {
+        format!(
+            "OVMS configuration updated successfully; replaced previously configured model(s): {}",
+            replaced_models.join(", ")
+        )
+    }
+}
+
+// Serialize the config the way it is written to disk
+fn ovms_config_string(config: &Value) -> Result<String, SparrowError> {
+    serde_json
+        ::to_string_pretty(config)
+        .map_err(|e| SparrowError::Parse(format!("Failed to serialize config: {}", e)))
+}
+
+#[tauri::command]
+pub async fn create_ovms_config(
+    app_handle: AppHandle,
+    model_name: String,
+    model_path: String
+) -> Result<String, SparrowError> {
+    let models_dir = crate::paths::models_dir().unwrap_or_else(|_| get_sparrow_dir(Some(&app_handle)).join("models"));
+    let (config, _) = build_ovms_config(None, &model_name, &model_path, &models_dir);
+
+    let config_path = get_ovms_config_path(Some(&app_handle));
+    fs
+        ::write(&config_path, ovms_config_string(&config)?)
+        .map_err(|e| SparrowError::Io(format!("Failed to write config file: {}", e)))?;
+
+    Ok("OVMS configuration file created successfully".to_string())
+}
+
+/// Point the OVMS config at `model_name`. The BGE models stay; any other chat model already
+/// in the config is replaced, and the returned message names it
+#[tauri::command]
+pub async fn update_ovms_config(
+    app_handle: AppHandle,
+    model_name: String,
+    model_path: String
+) -> Result<String, SparrowError> {
+    let config_path = get_ovms_config_path(Some(&app_handle));
+    let models_dir = crate::paths::models_dir().unwrap_or_else(|_| get_sparrow_dir(Some(&app_handle)).join("models"));
+
+    let (config, replaced_models) = build_ovms_config(
+        read_ovms_config(&config_path)?,
+        &model_name,
+        &model_path,
+        &models_dir
+    );
+
+    fs
+        ::write(&config_path, ovms_config_string(&config)?)
+        .map_err(|e| SparrowError::Io(format!("Failed to write config file: {}", e)))?;
 
-    fs::write(&config_path, config_str).map_err(|e| format!("Failed to write config file: {}", e))?;
+    if !replaced_models.is_empty() {
+        info!(model = %model_name, replaced = ?replaced_models, "Replaced configured chat model");
+    }
+    Ok(config_update_message(&replaced_models))
+}
 
-    Ok("OVMS configuration updated successfully".to_string())
+/// The config update_ovms_config would write for this model, without writing it
+#[tauri::command]
+pub async fn preview_ovms_config(
+    app_handle: AppHandle,
+    model_name: String,
+    model_path: String
+) -> Result<String, SparrowError> {
+    let config_path = get_ovms_config_path(Some(&app_handle));
+    let models_dir = crate::paths::models_dir().unwrap_or_else(|_| get_sparrow_dir(Some(&app_handle)).join("models"));
+
+    let (config, _) = build_ovms_config(
+        read_ovms_config(&config_path)?,
+        &model_name,
+        &model_path,
+        &models_dir
+    );
+    ovms_config_string(&config)
 }
 
 #[tauri::command]
-pub async fn reload_ovms_config() -> Result<String, String> {
-    let client = reqwest::Client::new();
+pub async fn reload_ovms_config() -> Result<String, SparrowError> {
+    let client = crate::http::http_client(crate::http::RELOAD_TIMEOUT)?;
 
     let response = client
-        .post("http://localhost:1114/v1/config/reload")
+        .post(format!("{}/v1/config/reload", crate::settings::ovms_base_url()))
         .send().await
-        .map_err(|e| format!("Failed to send reload request: {}", e))?;
+        .map_err(|e| SparrowError::Ovms(crate::http::request_error_message("Failed to send reload request", &e)))?;
 
     if response.status().is_success() {
-        let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+        let body = response
+            .text().await
+            .map_err(|e| SparrowError::Ovms(format!("Failed to read response: {}", e)))?;
         Ok(format!("Config reloaded successfully: {}", body))
     } else {
-        Err(format!("Config reload failed with status: {}", response.status()))
+        Err(SparrowError::Ovms(format!("Config reload failed with status: {}", response.status())))
+    }
+}
+
+/// The OVMS config file as it is on disk, for hand editing
+#[tauri::command]
+pub async fn get_ovms_config_raw(app_handle: AppHandle) -> Result<String, SparrowError> {
+    let config_path = get_ovms_config_path(Some(&app_handle));
+    if !config_path.exists() {
+        return Err(SparrowError::NotFound(format!("No OVMS config at {}", config_path.display())));
+    }
+
+    fs
+        ::read_to_string(&config_path)
+        .map_err(|e| SparrowError::Io(format!("Failed to read config file: {}", e)))
+}
+
+/// Replace the OVMS config with hand-edited `content` and reload it if OVMS is running.
+/// Unlike update_ovms_config the content is written as is, without adding the BGE models,
+/// but it must still pass validate_ovms_config
+#[tauri::command]
+pub async fn set_ovms_config_raw(app_handle: AppHandle, content: String) -> Result<String, SparrowError> {
+    let config_path = get_ovms_config_path(Some(&app_handle));
+
+    // Validate a copy first so a rejected edit never replaces the working config
+    let staged_path = config_path.with_extension("json.tmp");
+    fs
+        ::write(&staged_path, &content)
+        .map_err(|e| SparrowError::Io(format!("Failed to write config file: {}", e)))?;
+    if let Err(e) = validate_ovms_config(&staged_path) {
+        let _ = fs::remove_file(&staged_path);
+        return Err(SparrowError::InvalidInput(e));
+    }
+    fs
+        ::rename(&staged_path, &config_path)
+        .map_err(|e| SparrowError::Io(format!("Failed to replace config file: {}", e)))?;
+    info!(config_path = %config_path.display(), "OVMS config replaced by hand-edited content");
+
+    if !is_ovms_process_alive().await.unwrap_or(false) {
+        return Ok("OVMS configuration saved; it will be used the next time OVMS starts".to_string());
     }
+
+    reload_ovms_config().await.map_err(|e| {
+        SparrowError::Ovms(format!("OVMS configuration saved but reloading it failed: {}", e))
+    })
 }
 
 // Check if OVMS is present on the system (Tauri command)
 #[tauri::command]
-pub async fn check_ovms_present(app_handle: AppHandle) -> Result<bool, String> {
+pub async fn check_ovms_present(app_handle: AppHandle) -> Result<bool, SparrowError> {
     Ok(is_ovms_present(Some(&app_handle)))
 }
 
@@ -660,20 +986,66 @@ fn check_ovms_version(ovms_exe: &PathBuf) -> Result<bool, String> {
     Err("Could not parse OVMS version from output".to_string())
 }
 
+// True if the OVMS process this app spawned is still alive
+fn managed_ovms_running() -> bool {
+    let process_mutex = OVMS_PROCESS.get_or_init(|| Arc::new(Mutex::new(None)));
+    let mut process_guard = process_mutex.lock().unwrap();
+
+    match process_guard.as_mut().map(|child| child.try_wait()) {
+        Some(Ok(None)) => true,
+        Some(_) => {
+            // Exited or unqueryable, forget it so a new one can be started
+            *process_guard = None;
+            false
+        }
+        None => false,
+    }
+}
+
+// True if nothing is listening on the port yet. Probed by connecting rather than binding:
+// on Windows a 127.0.0.1 bind succeeds even while OVMS listens on 0.0.0.0
+fn port_is_free(port: u16) -> bool {
+    let address = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    std::net::TcpStream::connect_timeout(&address, std::time::Duration::from_millis(500)).is_err()
+}
+
 #[tauri::command]
-pub async fn start_ovms_server(app_handle: AppHandle) -> Result<String, String> {
+pub async fn start_ovms_server(app_handle: AppHandle) -> Result<String, SparrowError> {
     info!("OVMS server start command initiated");
-    // Check if OVMS is already running
-    match check_ovms_status().await {
-        Ok(ovms_status) => {
-            info!(loaded_models = ?ovms_status.loaded_models, "OVMS server is already running");
-            return Ok("OVMS server is already running".to_string());
-        }
-        Err(_) => {
-            info!("OVMS not running, starting server...");
-        }
+    let port = crate::settings::current().ovms_port;
+
+    // Only our own process counts as "already running"; anything else on the port would be
+    // mistaken for it and never get shut down with the app
+    if managed_ovms_running() {
+        info!("Managed OVMS server is already running");
+        return Ok("OVMS server is already running".to_string());
+    }
+
+    if !port_is_free(port) {
+        return Err(match check_ovms_status().await {
+            Ok(_) => {
+                warn!(port, "An OVMS instance not started by SparrowAI is using the port");
+                SparrowError::Ovms(
+                    format!(
+                        "Port {} is in use by an OVMS instance SparrowAI didn't start. Reclaim the port to stop it and start a managed server, or change the OVMS port in settings.",
+                        port
+                    )
+                )
+            }
+            Err(_) => {
+                warn!(port, "Another process is using the OVMS port");
+                SparrowError::Ovms(
+                    format!(
+                        "Port {} is in use by another application. Close it or change the OVMS port in settings.",
+                        port
+                    )
+                )
+            }
+        });
     }
 
+    info!("OVMS not running, starting server...");
+
     let ovms_exe = get_ovms_exe_path(Some(&app_handle));
     let config_path = get_ovms_config_path(Some(&app_handle));
 
@@ -683,19 +1055,27 @@ pub async fn start_ovms_server(app_handle: AppHandle) -> Result<String, String>
     // }
 
     // Validate config
-    validate_ovms_config(&config_path)?;
+    validate_ovms_config(&config_path).map_err(SparrowError::Ovms)?;
+    regenerate_rag_graphs();
 
     info!("Starting OVMS server...");
 
+    // OVMS also writes its log to a file, which outlives the pipes read at startup
+    let log_path = get_ovms_log_path(Some(&app_handle));
+    rotate_ovms_log(&log_path);
+
     // Start OVMS process
+    let ovms_port = port.to_string();
     let mut cmd = Command::new(&ovms_exe);
     cmd.args([
         "--config_path",
         &config_path.to_string_lossy(),
         "--rest_port",
-        "1114",
+        &ovms_port,
         "--log_level",
         "INFO",
+        "--log_path",
+        &log_path.to_string_lossy(),
     ])
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -707,7 +1087,9 @@ pub async fn start_ovms_server(app_handle: AppHandle) -> Result<String, String>
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    let mut child = cmd.spawn().map_err(|e| format!("Failed to start OVMS: {}", e))?;
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| SparrowError::Ovms(format!("Failed to start OVMS: {}", e)))?;
 
     // Wait a moment for server to start
     tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
@@ -737,7 +1119,7 @@ pub async fn start_ovms_server(app_handle: AppHandle) -> Result<String, String>
             );
 
             error!(error = %error_msg, "OVMS startup failed");
-            Err(error_msg)
+            Err(SparrowError::Ovms(error_msg))
         }
         Ok(None) => {
             // Process is still running, store it globally
@@ -748,12 +1130,64 @@ pub async fn start_ovms_server(app_handle: AppHandle) -> Result<String, String>
                 *process_guard = Some(child);
             } // Guard is dropped here
 
-            info!("OVMS server started on port 1114");
+            info!("OVMS server started on port {}", ovms_port);
 
             Ok("OVMS server started successfully.".to_string())
         }
-        Err(e) => { Err(format!("Failed to check OVMS status: {}", e)) }
+        Err(e) => { Err(SparrowError::Ovms(format!("Failed to check OVMS status: {}", e))) }
+    }
+}
+
+/// Stop an OVMS instance SparrowAI didn't start that holds the port, then start a managed one
+#[tauri::command]
+pub async fn reclaim_ovms_port(app_handle: AppHandle) -> Result<String, SparrowError> {
+    let port = crate::settings::current().ovms_port;
+
+    if !port_is_free(port) && check_ovms_status().await.is_err() {
+        return Err(
+            SparrowError::Ovms(
+                format!("Port {} is held by an application other than OVMS and can't be reclaimed", port)
+            )
+        );
+    }
+
+    info!(port, "Reclaiming OVMS port");
+    stop_ovms_server().map_err(SparrowError::Ovms)?;
+    wait_for_port_release(port).await;
+
+    start_ovms_server(app_handle).await
+}
+
+/// Give a stopped OVMS process up to five seconds to release its port
+pub async fn wait_for_port_release(port: u16) {
+    for _ in 0..10 {
+        if port_is_free(port) {
+            break;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    }
+}
+
+/// Stop the OVMS server and forget the loaded model, since nothing serves it anymore
+#[tauri::command]
+pub async fn stop_ovms() -> Result<String, SparrowError> {
+    if let LoadedModelStatus::Loading(model_id) = &*loaded_model_state().lock().unwrap() {
+        return Err(
+            SparrowError::Ovms(format!("Model '{}' is still loading, try again once it finishes.", model_id))
+        );
     }
+
+    stop_ovms_server().map_err(SparrowError::Ovms)?;
+    *loaded_model_state().lock().unwrap() = LoadedModelStatus::None;
+
+    Ok("OVMS server stopped".to_string())
+}
+
+/// Whether the OVMS process this app started is still running. Unlike check_ovms_status,
+/// which probes the port, this is false when OVMS was never started by us or has crashed
+#[tauri::command]
+pub async fn is_ovms_process_alive() -> Result<bool, SparrowError> {
+    Ok(managed_ovms_running())
 }
 
 // Stop OVMS server
@@ -800,59 +1234,563 @@ pub fn stop_ovms_server() -> Result<(), String> {
     Ok(())
 }
 
-// Load a model into OVMS
-#[tauri::command]
-pub async fn load_model(app_handle: AppHandle, model_id: String) -> Result<String, String> {
-    // Check if a model is already loaded
-    let loaded_model_mutex = LOADED_MODEL.get_or_init(|| Arc::new(Mutex::new(None)));
-
-    // Check current state and release lock immediately
-    {
-        let loaded_model_guard = loaded_model_mutex.lock().unwrap();
-        if loaded_model_guard.is_some() {
-            return Err("A model is already loaded. Please unload it first.".to_string());
+// Sum the sizes of the OpenVINO IR files (.xml/.bin) in a model directory
+fn ir_files_size(model_dir: &PathBuf) -> u64 {
+    let entries = match fs::read_dir(model_dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            return 0;
         }
-    }
+    };
 
-    // Ensure we're working with an OpenVINO model
-    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
-        model_id.clone()
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(path.extension().and_then(|ext| ext.to_str()), Some("xml") | Some("bin"))
+        })
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn classify_fit(required_bytes: u64, budget_bytes: u64) -> FitLevel {
+    if required_bytes > budget_bytes {
+        FitLevel::WillNotFit
+    } else if (required_bytes as f64) > (budget_bytes as f64) * TIGHT_FIT_RATIO {
+        FitLevel::Tight
+    } else {
+        FitLevel::Fits
+    }
+}
+
+#[tauri::command]
+pub async fn estimate_model_fit(model_id: String) -> Result<FitEstimate, SparrowError> {
+    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
+        model_id
     } else {
         format!("OpenVINO/{}", model_id)
     };
 
-    // Get the model path using .sparrow/models as default
-    // Use the original model_id for path construction to preserve backslashes
-    let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-        Ok(home) => home,
+    let model_path = crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id);
+    if !model_path.exists() {
+        return Err(SparrowError::NotFound(format!("Model not found at: {}", model_path.display())));
+    }
+
+    let model_size_bytes = ir_files_size(&model_path);
+    let estimated_required_bytes = ((model_size_bytes as f64) * MODEL_MEMORY_OVERHEAD) as u64;
+
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let total_memory_bytes = system.total_memory();
+    let available_memory_bytes = system.available_memory();
+
+    // There is no portable way to query dedicated VRAM, and the integrated GPUs OpenVINO
+    // usually targets share system memory, so system RAM is the budget for every device
+    let budget_bytes = available_memory_bytes;
+
+    let level = classify_fit(estimated_required_bytes, budget_bytes);
+    let to_gb = |bytes: u64| (bytes as f64) / (1024.0 * 1024.0 * 1024.0);
+    let message = match level {
+        FitLevel::Fits =>
+            format!(
+                "Model needs about {:.1} GB, {:.1} GB available",
+                to_gb(estimated_required_bytes),
+                to_gb(budget_bytes)
+            ),
+        FitLevel::Tight =>
+            format!(
+                "Model needs about {:.1} GB and only {:.1} GB is available; loading may be slow or fail",
+                to_gb(estimated_required_bytes),
+                to_gb(budget_bytes)
+            ),
+        FitLevel::WillNotFit =>
+            format!(
+                "Model needs about {:.1} GB but only {:.1} GB is available; it will likely fail to load",
+                to_gb(estimated_required_bytes),
+                to_gb(budget_bytes)
+            ),
+    };
+
+    Ok(FitEstimate {
+        model_id: normalized_model_id,
+        device: crate::settings::device_for_model(&normalized_model_id),
+        model_size_bytes,
+        estimated_required_bytes,
+        total_memory_bytes,
+        available_memory_bytes,
+        level,
+        message,
+    })
+}
+
+// OpenVINO device names for the detected hardware. A single GPU is addressed as "GPU",
+// several as "GPU.0", "GPU.1", ...
+fn device_names(gpu_count: usize, has_npu: bool) -> Vec<String> {
+    let mut devices = vec!["CPU".to_string()];
+    match gpu_count {
+        0 => {}
+        1 => devices.push("GPU".to_string()),
+        _ => devices.extend((0..gpu_count).map(|i| format!("GPU.{}", i))),
+    }
+    if has_npu {
+        devices.push("NPU".to_string());
+    }
+    devices
+}
+
+// Count Intel GPUs and look for an NPU, the accelerators OpenVINO's plugins can target.
+// OVMS has no device-listing flag, so this asks the OS for the devices instead
+#[cfg(target_os = "windows")]
+fn detect_accelerators() -> Result<(usize, bool), String> {
+    use std::os::windows::process::CommandExt;
+
+    let script =
+        "@(Get-CimInstance Win32_VideoController | Where-Object { $_.AdapterCompatibility -like '*Intel*' }).Count; \
+         @(Get-PnpDevice -PresentOnly -ErrorAction SilentlyContinue | Where-Object { $_.FriendlyName -like '*AI Boost*' -or $_.FriendlyName -like '*NPU*' }).Count";
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", script])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .map_err(|e| format!("Failed to run powershell: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Device query failed with status: {}", output.status));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let counts: Vec<usize> = stdout
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect();
+    match counts.as_slice() {
+        [gpus, npus] => Ok((*gpus, *npus > 0)),
+        _ => Err(format!("Unexpected device query output: {}", stdout.trim())),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect_accelerators() -> Result<(usize, bool), String> {
+    let count_entries = |dir: &str, prefix: &str| -> usize {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+                    .count()
+            })
+            .unwrap_or(0)
+    };
+
+    // Render nodes for GPUs, the accel class for NPUs
+    Ok((count_entries("/dev/dri", "renderD"), count_entries("/dev/accel", "accel") > 0))
+}
+
+/// Devices OpenVINO can target on this machine, for the inference device setting
+#[tauri::command]
+pub async fn list_available_devices() -> Result<Vec<String>, SparrowError> {
+    match tokio::task::spawn_blocking(detect_accelerators).await {
+        Ok(Ok((gpu_count, has_npu))) => {
+            let devices = device_names(gpu_count, has_npu);
+            info!(devices = ?devices, "Detected inference devices");
+            Ok(devices)
+        }
+        Ok(Err(e)) => {
+            warn!(error = %e, "Device detection failed, falling back to CPU");
+            Ok(device_names(0, false))
+        }
+        Err(e) => {
+            warn!(error = %e, "Device detection task failed, falling back to CPU");
+            Ok(device_names(0, false))
+        }
+    }
+}
+
+// Find the .ovms_cache directory of every downloaded model as (model_id, cache_dir)
+fn find_ovms_cache_dirs() -> Result<Vec<(String, PathBuf)>, String> {
+    let models_dir = crate::paths::models_dir()?;
+    let mut cache_dirs = Vec::new();
+
+    // Models live at <models_dir>/<org>/<model>
+    let orgs = match fs::read_dir(&models_dir) {
+        Ok(orgs) => orgs,
+        Err(_) => {
+            return Ok(cache_dirs);
+        }
+    };
+    for org in orgs.filter_map(|entry| entry.ok()).filter(|entry| entry.path().is_dir()) {
+        let org_name = org.file_name().to_string_lossy().to_string();
+        let models = match fs::read_dir(org.path()) {
+            Ok(models) => models,
+            Err(_) => {
+                continue;
+            }
+        };
+        for model in models.filter_map(|entry| entry.ok()) {
+            let cache_dir = model.path().join(".ovms_cache");
+            if cache_dir.is_dir() {
+                let model_id = format!("{}/{}", org_name, model.file_name().to_string_lossy());
+                cache_dirs.push((model_id, cache_dir));
+            }
+        }
+    }
+
+    Ok(cache_dirs)
+}
+
+#[tauri::command]
+pub async fn get_ovms_cache_size() -> Result<OvmsCacheInfo, SparrowError> {
+    let models: Vec<OvmsCacheEntry> = find_ovms_cache_dirs()
+        .map_err(SparrowError::Io)?
+        .into_iter()
+        .map(|(model_id, cache_dir)| OvmsCacheEntry {
+            model_id,
+            size_bytes: crate::paths::dir_size(&cache_dir),
+        })
+        .collect();
+
+    Ok(OvmsCacheInfo {
+        total_bytes: models
+            .iter()
+            .map(|entry| entry.size_bytes)
+            .sum(),
+        models,
+    })
+}
+
+#[tauri::command]
+pub async fn clear_ovms_cache(model_id: Option<String>) -> Result<u64, SparrowError> {
+    // A model that is still loading is compiling into the cache, so protect it too
+    let loaded_model = loaded_model_state()
+        .lock()
+        .map_err(|e| SparrowError::Ovms(format!("Lock error: {}", e)))?
+        .model_id()
+        .map(|model_id| model_id.to_string());
+
+    let target_model = model_id.map(|id| {
+        let id = id.replace('\\', "/");
+        if id.starts_with("OpenVINO/") { id } else { format!("OpenVINO/{}", id) }
+    });
+    if target_model.is_some() && target_model == loaded_model {
+        return Err(
+            SparrowError::Ovms(
+                format!(
+                    "Cannot clear the cache of {} while it is loaded. OVMS is using the compiled files; unload the model first.",
+                    target_model.unwrap_or_default()
+                )
+            )
+        );
+    }
+
+    let mut freed = 0;
+    for (cache_model_id, cache_dir) in find_ovms_cache_dirs().map_err(SparrowError::Io)? {
+        if let Some(target) = &target_model {
+            if &cache_model_id != target {
+                continue;
+            }
+        }
+        // Clearing everything still leaves the loaded model's cache alone
+        if loaded_model.as_ref() == Some(&cache_model_id) {
+            warn!(model = %cache_model_id, "Skipping OVMS cache of loaded model");
+            continue;
+        }
+
+        let size = crate::paths::dir_size(&cache_dir);
+        fs
+            ::remove_dir_all(&cache_dir)
+            .map_err(|e| SparrowError::Io(format!("Failed to remove cache for {}: {}", cache_model_id, e)))?;
+        info!(model = %cache_model_id, bytes = size, "Cleared OVMS cache");
+        freed += size;
+    }
+
+    Ok(freed)
+}
+
+// How long the readiness probe waits for OVMS before giving up
+const READY_PROBE_TIMEOUT_SECS: u64 = 3;
+
+/// Quick /v1/config probe so callers can fail with actionable guidance instead of a raw
+/// connection error when the server is down
+pub async fn ensure_ovms_ready() -> Result<(), SparrowError> {
+    let base_url = crate::settings::ovms_base_url();
+    let client = crate::http::http_client(std::time::Duration::from_secs(READY_PROBE_TIMEOUT_SECS))?;
+
+    match client.get(format!("{}/v1/config", base_url)).send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => {
+            Err(
+                SparrowError::Ovms(
+                    format!(
+                        "OVMS at {} is not ready (status {}). Try (re)loading a model.",
+                        base_url,
+                        response.status()
+                    )
+                )
+            )
+        }
+        Err(e) => {
+            debug!(error = %e, "OVMS readiness probe failed");
+            Err(
+                SparrowError::Ovms(
+                    format!(
+                        "OVMS server is not running at {}. Try (re)loading a model or restarting SparrowAI.",
+                        base_url
+                    )
+                )
+            )
+        }
+    }
+}
+
+/// Fail with setup guidance when OVMS isn't serving the configured embedding model, which
+/// happens when the BGE models were never downloaded or added to the OVMS config
+pub async fn ensure_embedding_model_loaded() -> Result<(), SparrowError> {
+    ensure_ovms_ready().await?;
+
+    let embedding_model = crate::settings::current().embedding_model;
+    let client = crate::http::http_client(crate::http::STATUS_TIMEOUT)?;
+    if is_model_available(&client, &embedding_model).await {
+        Ok(())
+    } else {
+        Err(
+            SparrowError::Ovms(
+                format!(
+                    "Embedding model {} is not loaded in OVMS; run setup to download it and add it to the OVMS configuration.",
+                    embedding_model
+                )
+            )
+        )
+    }
+}
+
+// Check /v1/config for an AVAILABLE version of the model
+async fn is_model_available(client: &reqwest::Client, model_name: &str) -> bool {
+    let config: Value = match client.get(format!("{}/v1/config", crate::settings::ovms_base_url())).send().await {
+        Ok(response) => {
+            match response.json().await {
+                Ok(config) => config,
+                Err(_) => {
+                    return false;
+                }
+            }
+        }
         Err(_) => {
-            return Err("Failed to get user home directory".to_string());
+            return false;
         }
     };
 
+    config
+        .get(model_name)
+        .and_then(|entry| serde_json::from_value::<ModelInfo>(entry.clone()).ok())
+        .map(|info| info.model_version_status.iter().any(|version| version.state == "AVAILABLE"))
+        .unwrap_or(false)
+}
+
+/// Poll OVMS until the model is available or the timeout expires
+pub async fn wait_for_model_available(
+    model_name: &str,
+    timeout: std::time::Duration
+) -> Result<(), String> {
+    let client = crate::http::http_client(crate::http::STATUS_TIMEOUT)?;
+    let deadline = std::time::Instant::now() + timeout;
+
+    while std::time::Instant::now() < deadline {
+        if is_model_available(&client, model_name).await {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+
+    Err(format!("Model {} did not become available within {} seconds", model_name, timeout.as_secs()))
+}
+
+// Send a one token completion so OVMS compiles the graph before the first real message
+async fn warmup_model(model_name: &str) -> Result<(), String> {
+    let client = crate::http::http_client(crate::http::DOWNLOAD_TIMEOUT)?;
+    let response = client
+        .post(format!("{}/v3/chat/completions", crate::settings::ovms_base_url()))
+        .json(
+            &json!({
+            "model": model_name,
+            "messages": [{ "role": "user", "content": "hello" }],
+            "max_tokens": 1,
+            "stream": false
+        })
+        )
+        .send().await
+        .map_err(|e| crate::http::request_error_message("Warmup request failed", &e))?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Warmup request failed with status: {}", response.status()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenCount {
+    pub count: usize,
+    /// True when the model's tokenizer couldn't be reached and the count is an estimate
+    pub approximate: bool,
+}
+
+// Tokenize through the served model: a raw completion reports the prompt length in usage
+async fn count_prompt_tokens(model_name: &str, text: &str) -> Result<usize, String> {
+    let client = crate::http::http_client(crate::http::STATUS_TIMEOUT)?;
+    let response = client
+        .post(format!("{}/v3/completions", crate::settings::ovms_base_url()))
+        .json(
+            &json!({
+            "model": model_name,
+            "prompt": text,
+            "max_tokens": 1,
+            "stream": false
+        })
+        )
+        .send().await
+        .map_err(|e| crate::http::request_error_message("Token count request failed", &e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Token count request failed with status: {}", response.status()));
+    }
+
+    let body: Value = response
+        .json().await
+        .map_err(|e| format!("Failed to parse token count response: {}", e))?;
+    body["usage"]["prompt_tokens"]
+        .as_u64()
+        .map(|count| count as usize)
+        .ok_or_else(|| "OVMS response has no usage.prompt_tokens".to_string())
+}
+
+/// Count the tokens of `text` with the model's own tokenizer via OVMS. When the model isn't
+/// served the count falls back to a character-based estimate and is marked approximate
+#[tauri::command]
+pub async fn count_tokens(model_name: String, text: String) -> Result<TokenCount, SparrowError> {
+    if text.is_empty() {
+        return Ok(TokenCount { count: 0, approximate: false });
+    }
+
+    // OVMS serves models under their bare name, without the OpenVINO/ prefix
+    let served_name = model_name.rsplit('/').next().unwrap_or(&model_name);
+    match count_prompt_tokens(served_name, &text).await {
+        Ok(count) => Ok(TokenCount { count, approximate: false }),
+        Err(e) => {
+            debug!(error = %e, model = %model_name, "Falling back to approximate token count");
+            Ok(TokenCount {
+                count: crate::chat::approximate_token_count(&text),
+                approximate: true,
+            })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn load_model(
+    app_handle: AppHandle,
+    model_id: String,
+    warmup: Option<bool>
+) -> Result<String, SparrowError> {
+    // Ensure we're working with an OpenVINO model
+    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
+        model_id.clone()
+    } else {
+        format!("OpenVINO/{}", model_id)
+    };
+
+    // Claim the slot while holding the lock so two loads can't race
+    {
+        let mut status = loaded_model_state().lock().unwrap();
+        match &*status {
+            LoadedModelStatus::None => {
+                *status = LoadedModelStatus::Loading(normalized_model_id.clone());
+            }
+            LoadedModelStatus::Loading(loading) => {
+                return Err(SparrowError::Ovms(format!("Model '{}' is still loading.", loading)));
+            }
+            LoadedModelStatus::Loaded(_) => {
+                return Err(
+                    SparrowError::Ovms("A model is already loaded. Please unload it first.".to_string())
+                );
+            }
+        }
+    }
+
+    let result = load_model_inner(&app_handle, &model_id, &normalized_model_id, warmup).await;
+    if result.is_err() {
+        *loaded_model_state().lock().unwrap() = LoadedModelStatus::None;
+    }
+    result
+}
+
+async fn load_model_inner(
+    app_handle: &AppHandle,
+    model_id: &str,
+    normalized_model_id: &str,
+    warmup: Option<bool>
+) -> Result<String, SparrowError> {
+    // Get the model path using the data directory's models folder as default
+    // Use the original model_id for path construction to preserve backslashes
+    let models_dir = crate::paths::models_dir().map_err(SparrowError::Io)?;
+
     // Build the path using the original model_id structure (with backslashes on Windows)
     let original_model_id = if model_id.starts_with("OpenVINO") {
-        model_id.clone()
+        model_id.to_string()
     } else {
         format!("OpenVINO/{}", model_id)
     };
 
-    let model_path = PathBuf::from(home_dir)
-        .join(".sparrow")
-        .join("models")
-        .join(&original_model_id);
+    let model_path = models_dir.join(&original_model_id);
 
     if !model_path.exists() {
         return Err(
-            format!(
-                "Model not found at: {}. Please download the model first.",
-                model_path.display()
+            SparrowError::NotFound(
+                format!(
+                    "Model not found at: {}. Please download the model first.",
+                    model_path.display()
+                )
             )
         );
     }
 
+    // Warn the UI before loading a model that probably doesn't fit in memory
+    match estimate_model_fit(normalized_model_id.to_string()).await {
+        Ok(estimate) if estimate.level != FitLevel::Fits => {
+            warn!(model = %normalized_model_id, message = %estimate.message, "Model may not fit in memory");
+            let _ = app_handle.emit("model-fit-warning", &estimate);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            debug!(error = %e, "Could not estimate model memory fit");
+        }
+    }
+
     // Extract model name from the full ID (use forward slash version for model name)
-    let model_name = normalized_model_id.split('/').next_back().unwrap_or(&normalized_model_id);
+    let model_name = normalized_model_id.split('/').next_back().unwrap_or(normalized_model_id);
+
+    // Regenerate the graph so it targets the device currently configured for this model
+    let runtime_options = crate::settings::current().ovms_runtime;
+    if let Err(e) = generate_ovms_graph(&model_path, normalized_model_id, &runtime_options) {
+        warn!(model = %normalized_model_id, error = %e, "Failed to regenerate graph, using the existing one");
+    }
+
+    if
+        runtime_options.enable_prefix_caching &&
+        !model_name.ends_with("cw-ov") &&
+        detect_model_kind_in_dir(&model_path, normalized_model_id) == ModelKind::Chat
+    {
+        info!(model = %model_name, cache_size_gb = runtime_options.cache_size, "Prefix caching enabled");
+        let _ = app_handle.emit(
+            "model-load-note",
+            json!({
+                "model": model_name,
+                "message": format!(
+                    "Prefix caching is on: follow-up turns reuse the cached prompt and history, which makes them faster but keeps more of the {} GB KV cache in use. It can be turned off in the OVMS runtime options.",
+                    runtime_options.cache_size
+                )
+            })
+        );
+    }
 
     // Update OVMS config with the model (use the actual Windows path)
     update_ovms_config(
@@ -864,10 +1802,51 @@ pub async fn load_model(app_handle: AppHandle, model_id: String) -> Result<Strin
     // Reload OVMS config
     reload_ovms_config().await?;
 
-    // Mark the model as loaded (use the forward slash version for consistency)
+    // The model only counts as loaded once OVMS reports it available
+    let warmup = warmup.unwrap_or(true);
+    if warmup {
+        let _ = app_handle.emit("model-warming", json!({ "model": model_name, "status": "waiting" }));
+    }
+    if
+        let Err(e) = wait_for_model_available(
+            model_name,
+            std::time::Duration::from_secs(MODEL_AVAILABLE_TIMEOUT_SECS)
+        ).await
     {
-        let mut loaded_model_guard = loaded_model_mutex.lock().unwrap();
-        *loaded_model_guard = Some(normalized_model_id.clone());
+        if warmup {
+            let _ = app_handle.emit(
+                "model-warming",
+                json!({ "model": model_name, "status": "failed", "error": &e })
+            );
+        }
+        return Err(SparrowError::Ovms(e));
+    }
+
+    // Mark the model as loaded (use the forward slash version for consistency)
+    *loaded_model_state().lock().unwrap() = LoadedModelStatus::Loaded(normalized_model_id.to_string());
+
+    // Optionally run a warmup inference so the first chat doesn't pay the compile cost
+    if warmup {
+        let _ = app_handle.emit("model-warming", json!({ "model": model_name, "status": "warming" }));
+        let warmup_result = warmup_model(model_name).await;
+
+        match warmup_result {
+            Ok(()) => {
+                info!(model = %model_name, "Model warmed up");
+                let _ = app_handle.emit(
+                    "model-warming",
+                    json!({ "model": model_name, "status": "ready" })
+                );
+            }
+            Err(e) => {
+                // The model may still work, warmup is only an optimization
+                warn!(model = %model_name, error = %e, "Model warmup failed");
+                let _ = app_handle.emit(
+                    "model-warming",
+                    json!({ "model": model_name, "status": "failed", "error": e })
+                );
+            }
+        }
     }
 
     Ok(format!("Model '{}' loaded successfully", normalized_model_id))
@@ -875,13 +1854,14 @@ pub async fn load_model(app_handle: AppHandle, model_id: String) -> Result<Strin
 
 // Unload the currently loaded model
 #[tauri::command]
-pub async fn unload_model(_app_handle: AppHandle) -> Result<String, String> {
-    let loaded_model_mutex = LOADED_MODEL.get_or_init(|| Arc::new(Mutex::new(None)));
-
+pub async fn unload_model(_app_handle: AppHandle) -> Result<String, SparrowError> {
     // Get the model ID and clear it
     let model_id = {
-        let mut loaded_model_guard = loaded_model_mutex.lock().unwrap();
-        loaded_model_guard.take()
+        let mut status = loaded_model_state().lock().unwrap();
+        if let LoadedModelStatus::Loading(model_id) = &*status {
+            return Err(SparrowError::Ovms(format!("Model '{}' is still loading.", model_id)));
+        }
+        std::mem::take(&mut *status).model_id().map(|model_id| model_id.to_string())
     };
 
     if let Some(model_id) = model_id {
@@ -893,120 +1873,308 @@ pub async fn unload_model(_app_handle: AppHandle) -> Result<String, String> {
 
         Ok(format!("Model '{}' unloaded successfully", model_id))
     } else {
-        Err("No model is currently loaded".to_string())
+        Err(SparrowError::Ovms("No model is currently loaded".to_string()))
     }
 }
 
-// Get the currently loaded model
+// Get the currently loaded model, None while a model is still loading
 #[tauri::command]
-pub async fn get_loaded_model() -> Result<Option<String>, String> {
-    let loaded_model_mutex = LOADED_MODEL.get_or_init(|| Arc::new(Mutex::new(None)));
-    let loaded_model_guard = loaded_model_mutex.lock().unwrap();
-    Ok(loaded_model_guard.clone())
+pub async fn get_loaded_model() -> Result<Option<String>, SparrowError> {
+    match &*loaded_model_state().lock().unwrap() {
+        LoadedModelStatus::Loaded(model_id) => Ok(Some(model_id.clone())),
+        _ => Ok(None),
+    }
 }
 
+// Get the model status including whether a model is still loading
 #[tauri::command]
-pub async fn check_ovms_status() -> Result<OvmsStatus, String> {
-    let client = reqwest::Client::new();
+pub async fn get_loaded_model_status() -> Result<LoadedModelStatus, SparrowError> {
+    Ok(loaded_model_state().lock().unwrap().clone())
+}
 
-    let response = client
-        .get("http://localhost:1114/v1/config")
-        .send().await
-        .map_err(|e| format!("Failed to connect to OVMS server: {}", e))?;
+// Read the states of one model entry from /v1/config
+fn model_version_states(entry: &Value) -> Result<Vec<&str>, String> {
+    let versions = entry
+        .get("model_version_status")
+        .ok_or("missing model_version_status")?
+        .as_array()
+        .ok_or("model_version_status is not an array")?;
 
-    if response.status().is_success() {
-        let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
+    versions
+        .iter()
+        .map(|version| {
+            version
+                .get("state")
+                .and_then(|state| state.as_str())
+                .ok_or_else(|| "version entry has no state".to_string())
+        })
+        .collect()
+}
 
-        // Parse the JSON response to extract loaded models
-        let json_value: Value = serde_json
-            ::from_str(&body)
-            .map_err(|e| format!("Failed to parse OVMS response JSON: {}", e))?;
+// Split the /v1/config response into AVAILABLE models and warnings for entries that couldn't be read
+fn parse_ovms_config_status(config: &Value) -> Result<(Vec<String>, Vec<String>), SparrowError> {
+    let config_obj = config
+        .as_object()
+        .ok_or_else(|| SparrowError::Parse("OVMS config status is not a JSON object".to_string()))?;
 
-        let mut loaded_models = Vec::new();
+    let mut loaded_models = Vec::new();
+    let mut warnings = Vec::new();
 
-        // Extract model names from the JSON structure
-        if let Some(config_obj) = json_value.as_object() {
-            for (key, value) in config_obj {
-                // Skip metadata keys
-                if key.starts_with("_") {
-                    continue;
-                }
+    for (key, value) in config_obj {
+        // Skip metadata keys
+        if key.starts_with("_") {
+            continue;
+        }
 
-                // Skip BGE models (embedding and reranker models)
-                if key.starts_with("bge") {
-                    continue;
-                }
+        // Skip BGE models (embedding and reranker models)
+        if key.starts_with("bge") {
+            continue;
+        }
 
-                // Check if this is a model entry with model_version_status
-                if let Some(model_info) = value.as_object() {
-                    if let Some(version_status) = model_info.get("model_version_status") {
-                        if let Some(status_array) = version_status.as_array() {
-                            // Check if any version is AVAILABLE
-                            let has_available = status_array.iter().any(|status| {
-                                if let Some(status_obj) = status.as_object() {
-                                    if let Some(state) = status_obj.get("state") {
-                                        return state.as_str() == Some("AVAILABLE");
-                                    }
-                                }
-                                false
-                            });
-
-                            if has_available {
-                                loaded_models.push(key.clone());
-                            }
-                        }
-                    }
+        match model_version_states(value) {
+            Ok(states) => {
+                if states.contains(&"AVAILABLE") {
+                    loaded_models.push(key.clone());
                 }
             }
+            Err(e) => warnings.push(format!("Could not read status of model {}: {}", key, e)),
+        }
+    }
+
+    Ok((loaded_models, warnings))
+}
+
+#[tauri::command]
+pub async fn check_ovms_status() -> Result<OvmsStatus, SparrowError> {
+    let client = crate::http::http_client(crate::http::STATUS_TIMEOUT)?;
+
+    let response = client
+        .get(format!("{}/v1/config", crate::settings::ovms_base_url()))
+        .send().await
+        .map_err(|e| SparrowError::Ovms(crate::http::request_error_message("Failed to reach OVMS server", &e)))?;
+
+    if response.status().is_success() {
+        // Deserialize straight from the response instead of buffering the body as a string first
+        let json_value: Value = response
+            .json().await
+            .map_err(|e| SparrowError::Parse(format!("Failed to parse OVMS response JSON: {}", e)))?;
+
+        let (loaded_models, warnings) = parse_ovms_config_status(&json_value)?;
+        for warning in &warnings {
+            warn!(warning = %warning, "Malformed model entry in OVMS config status");
         }
 
         Ok(OvmsStatus {
             status: "healthy".to_string(),
             loaded_models,
+            warnings,
         })
     } else {
-        Err(format!("OVMS status check failed with status: {}", response.status()))
+        Err(SparrowError::Ovms(format!("OVMS status check failed with status: {}", response.status())))
     }
 }
 
 #[tauri::command]
-pub async fn get_ovms_model_metadata(model_name: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
+pub async fn get_ovms_model_metadata(model_name: String) -> Result<String, SparrowError> {
+    let client = crate::http::http_client(crate::http::STATUS_TIMEOUT)?;
 
     // Try to get model metadata for more detailed error information
-    let metadata_url = format!("http://localhost:1114/v1/models/{}/metadata", model_name);
+    let metadata_url = format!("{}/v1/models/{}/metadata", crate::settings::ovms_base_url(), model_name);
     let response = client
         .get(&metadata_url)
         .send().await
-        .map_err(|e| format!("Failed to get model metadata: {}", e))?;
+        .map_err(|e| SparrowError::Ovms(crate::http::request_error_message("Failed to get model metadata", &e)))?;
 
     if response.status().is_success() {
         let body = response
             .text().await
-            .map_err(|e| format!("Failed to read metadata response: {}", e))?;
+            .map_err(|e| SparrowError::Ovms(format!("Failed to read metadata response: {}", e)))?;
         Ok(body)
     } else {
         // If metadata fails, try the model status endpoint
-        let status_url = format!("http://localhost:1114/v1/models/{}", model_name);
+        let status_url = format!("{}/v1/models/{}", crate::settings::ovms_base_url(), model_name);
         let status_response = client
             .get(&status_url)
             .send().await
-            .map_err(|e| format!("Failed to get model status: {}", e))?;
+            .map_err(|e| SparrowError::Ovms(crate::http::request_error_message("Failed to get model status", &e)))?;
 
         let status_code = status_response.status();
         let status_body = status_response
             .text().await
-            .map_err(|e| format!("Failed to read status response: {}", e))?;
+            .map_err(|e| SparrowError::Ovms(format!("Failed to read status response: {}", e)))?;
 
         if status_code.is_success() {
             Ok(status_body)
+        } else if status_code == reqwest::StatusCode::NOT_FOUND {
+            Err(SparrowError::NotFound(format!("Model {} is not served by OVMS: {}", model_name, status_body)))
         } else {
-            Err(format!("Model {} status check failed: {}", model_name, status_body))
+            Err(SparrowError::Ovms(format!("Model {} status check failed: {}", model_name, status_body)))
         }
     }
 }
 
-pub fn generate_ovms_graph(model_dir: &PathBuf, model_id: &str) -> Result<(), String> {
+/// What a model is served as, which decides the OVMS graph and where the UI routes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    Chat,
+    Embedding,
+    Reranker,
+    Unknown,
+}
+
+// The bundled BGE models are recognized by name, like the graphs written for them
+fn kind_from_name(model_name: &str) -> Option<ModelKind> {
+    match model_name {
+        BGE_RERANKER_MODEL => Some(ModelKind::Reranker),
+        BGE_EMBEDDING_MODEL => Some(ModelKind::Embedding),
+        _ => None,
+    }
+}
+
+// The calculator used by an existing graph.pbtxt
+fn kind_from_graph(graph: &str) -> Option<ModelKind> {
+    if graph.contains("RerankCalculator") {
+        Some(ModelKind::Reranker)
+    } else if graph.contains("EmbeddingsCalculator") {
+        Some(ModelKind::Embedding)
+    } else if graph.contains("LLMCalculator") {
+        Some(ModelKind::Chat)
+    } else {
+        None
+    }
+}
+
+// Transformers architecture names from config.json, e.g. "Qwen2ForCausalLM" or "XLMRobertaForSequenceClassification"
+fn kind_from_architectures(architectures: &[String]) -> Option<ModelKind> {
+    architectures.iter().find_map(|architecture| {
+        if architecture.ends_with("ForSequenceClassification") {
+            Some(ModelKind::Reranker)
+        } else if architecture.ends_with("ForCausalLM") || architecture.ends_with("ForConditionalGeneration") {
+            Some(ModelKind::Chat)
+        } else if architecture.ends_with("Model") {
+            Some(ModelKind::Embedding)
+        } else {
+            None
+        }
+    })
+}
+
+/// Work out the kind of a downloaded model from its name, graph.pbtxt, config.json
+/// architectures and, as a last resort, the tokenizer/detokenizer IR files chat models ship with
+pub fn detect_model_kind_in_dir(model_dir: &Path, model_id: &str) -> ModelKind {
+    let model_name = model_id.rsplit('/').next().unwrap_or(model_id);
+    if let Some(kind) = kind_from_name(model_name) {
+        return kind;
+    }
+
+    if let Ok(graph) = std::fs::read_to_string(model_dir.join("graph.pbtxt")) {
+        if let Some(kind) = kind_from_graph(&graph) {
+            return kind;
+        }
+    }
+
+    let architectures: Vec<String> = std::fs
+        ::read_to_string(model_dir.join("config.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        .and_then(|config| config.get("architectures").cloned())
+        .and_then(|architectures| serde_json::from_value(architectures).ok())
+        .unwrap_or_default();
+    if let Some(kind) = kind_from_architectures(&architectures) {
+        return kind;
+    }
+
+    if model_dir.join("openvino_detokenizer.xml").exists() {
+        ModelKind::Chat
+    } else {
+        ModelKind::Unknown
+    }
+}
+
+#[tauri::command]
+pub async fn detect_model_kind(model_id: String) -> Result<ModelKind, SparrowError> {
+    let normalized_model_id = if model_id.starts_with("OpenVINO/") {
+        model_id
+    } else {
+        format!("OpenVINO/{}", model_id)
+    };
+
+    let model_dir = crate::paths::models_dir().map_err(SparrowError::Io)?.join(&normalized_model_id);
+    if !model_dir.is_dir() {
+        return Err(SparrowError::NotFound(format!("Model is not downloaded: {}", normalized_model_id)));
+    }
+
+    Ok(detect_model_kind_in_dir(&model_dir, &normalized_model_id))
+}
+
+/// Save new LLM scheduler options and regenerate the loaded model's graph with them.
+/// They take effect the next time that model is loaded
+#[tauri::command]
+pub async fn set_ovms_runtime_options(
+    options: crate::settings::OvmsRuntimeOptions
+) -> Result<crate::settings::OvmsRuntimeOptions, SparrowError> {
+    let settings = crate::settings::set_ovms_runtime_options(options).map_err(SparrowError::InvalidInput)?;
+
+    if let Some(model_id) = current_loaded_model().model_id() {
+        let model_dir = crate::paths::models_dir().map_err(SparrowError::Io)?.join(model_id);
+        generate_ovms_graph(&model_dir, model_id, &settings.ovms_runtime).map_err(SparrowError::Io)?;
+        info!(model = %model_id, "Regenerated graph with new runtime options");
+    }
+
+    Ok(settings.ovms_runtime)
+}
+
+// The BGE graphs are written when the models are downloaded. Rewrite them from the current
+// settings so device changes reach the embedding and reranker models too
+fn regenerate_rag_graphs() {
+    let models_dir = match crate::paths::models_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!(error = %e, "Failed to get models directory for BGE graphs");
+            return;
+        }
+    };
+    let options = crate::settings::current().ovms_runtime;
+
+    for model_name in [BGE_EMBEDDING_MODEL, BGE_RERANKER_MODEL] {
+        let model_id = format!("OpenVINO/{}", model_name);
+        let model_dir = models_dir.join(&model_id);
+        if !model_dir.is_dir() {
+            continue;
+        }
+        if let Err(e) = generate_ovms_graph(&model_dir, &model_id, &options) {
+            warn!(model = %model_id, error = %e, "Failed to regenerate BGE graph");
+        }
+    }
+}
+
+/// Set the device one model runs on, or remove its override with `device: None`. Chat models
+/// use it from their next load; the BGE graphs are rewritten and reloaded right away
+#[tauri::command]
+pub async fn set_model_device(
+    model_id: String,
+    device: Option<String>
+) -> Result<crate::settings::Settings, SparrowError> {
+    let model_name = model_id.rsplit('/').next().unwrap_or(&model_id);
+    let is_rag_model = [BGE_EMBEDDING_MODEL, BGE_RERANKER_MODEL].contains(&model_name);
+    let settings = crate::settings::set_model_device(model_id, device).map_err(SparrowError::InvalidInput)?;
+
+    if is_rag_model {
+        regenerate_rag_graphs();
+        if is_ovms_process_alive().await.unwrap_or(false) {
+            reload_ovms_config().await?;
+        }
+    }
+
+    Ok(settings)
+}
+
+pub fn generate_ovms_graph(
+    model_dir: &PathBuf,
+    model_id: &str,
+    options: &crate::settings::OvmsRuntimeOptions
+) -> Result<(), String> {
     // Extract model name from ID (e.g., "OpenVINO/Phi-3.5-mini-instruct-int4-ov" -> "Phi-3.5-mini-instruct-int4-ov")
     let model_name = model_id.split('/').last().unwrap_or(model_id);
 
@@ -1040,8 +2208,15 @@ pub fn generate_ovms_graph(model_dir: &PathBuf, model_id: &str) -> Result<(), St
 
     // Generate graph.pbtxt content based on model type
     let cache_dir = format!("{}/.ovms_cache", model_dir.to_string_lossy().replace('\\', "/"));
+    let device = crate::settings::device_for_model(model_id);
+    let crate::settings::OvmsRuntimeOptions {
+        max_num_seqs,
+        max_num_batched_tokens,
+        cache_size,
+        enable_prefix_caching,
+    } = options;
     let graph_content = if tokenizer_name.is_some() && detokenizer_name.is_some() {
-        if model_name == "bge-reranker-base-int8-ov" {
+        if kind_from_name(model_name) == Some(ModelKind::Reranker) {
             format!(
                 r#"input_stream: "REQUEST_PAYLOAD:input"
 output_stream: "RESPONSE_PAYLOAD:output"
@@ -1073,7 +2248,7 @@ node {{
     output_stream: "RESPONSE_PAYLOAD:output"
             }}"#
             )
-        } else if model_name == "bge-base-en-v1.5-int8-ov" {
+        } else if kind_from_name(model_name) == Some(ModelKind::Embedding) {
             format!(
                 r#"input_stream: "REQUEST_PAYLOAD:input"
 output_stream: "RESPONSE_PAYLOAD:output"
@@ -1087,7 +2262,7 @@ node {{
     [type.googleapis.com / mediapipe.EmbeddingsCalculatorOVOptions]: {{
       models_path: "./",
       normalize_embeddings: true,
-      target_device: "GPU"
+      target_device: "{device}"
     }}
   }}
             }}"#
@@ -1113,8 +2288,8 @@ node {{
                         models_path: "./",
                         plugin_config: '{{"CACHE_DIR": "{}"}}',
                         enable_prefix_caching: false,
-                        cache_size: 2,
-                        max_num_seqs: 256,
+                        cache_size: {cache_size},
+                        max_num_seqs: {max_num_seqs},
                         device: "NPU",
                     }}
                 }}
@@ -1150,11 +2325,11 @@ node {{
                     [type.googleapis.com / mediapipe.LLMCalculatorOptions]: {{
                         models_path: "./",
                         plugin_config: '{{"CACHE_DIR": "{}"}}',
-                        enable_prefix_caching: false,
-                        cache_size: 2,
-                        max_num_seqs: 256,
-                        max_num_batched_tokens: 8192,
-                        device: "GPU",
+                        enable_prefix_caching: {enable_prefix_caching},
+                        cache_size: {cache_size},
+                        max_num_seqs: {max_num_seqs},
+                        max_num_batched_tokens: {max_num_batched_tokens},
+                        device: "{device}",
                     }}
                 }}
                 input_stream_handler {{
@@ -1183,7 +2358,7 @@ node {{
     node_options: {{
         [type.googleapis.com / mediapipe.LLMCalculatorOptions]: {{
             models_path: "./",
-            target_device: "GPU"
+            target_device: "{device}"
         }}
     }}
 }}"#
@@ -1200,3 +2375,170 @@ node {{
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_verify_sha256() {
+        use sha2::{ Digest, Sha256 };
+
+        let digest = format!("{:x}", Sha256::digest(b"ovms"));
+        assert!(verify_sha256(&digest, &digest).is_ok());
+        assert!(verify_sha256(&digest, &digest.to_uppercase()).is_ok());
+
+        let other = format!("{:x}", Sha256::digest(b"not ovms"));
+        let error = verify_sha256(&digest, &other).unwrap_err();
+        assert!(error.contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn test_last_lines() {
+        let contents = "one\ntwo\nthree\n";
+        assert_eq!(last_lines(contents, 2), vec!["two", "three"]);
+        assert_eq!(last_lines(contents, 10), vec!["one", "two", "three"]);
+        assert!(last_lines(contents, 0).is_empty());
+    }
+
+    #[test]
+    fn test_kind_from_architectures() {
+        assert_eq!(kind_from_architectures(&["Qwen2ForCausalLM".to_string()]), Some(ModelKind::Chat));
+        assert_eq!(
+            kind_from_architectures(&["XLMRobertaForSequenceClassification".to_string()]),
+            Some(ModelKind::Reranker)
+        );
+        assert_eq!(kind_from_architectures(&["BertModel".to_string()]), Some(ModelKind::Embedding));
+        assert_eq!(kind_from_architectures(&[]), None);
+    }
+
+    #[test]
+    fn test_build_ovms_config_adds_bge_models_first() {
+        let models_dir = Path::new("/models");
+        let (config, replaced) = build_ovms_config(
+            None,
+            "Qwen3-8B-int4-ov",
+            "C:\\models\\Qwen3-8B-int4-ov",
+            models_dir
+        );
+        assert!(replaced.is_empty());
+
+        let names: Vec<&str> = config["mediapipe_config_list"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|model| model["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec![BGE_RERANKER_MODEL, BGE_EMBEDDING_MODEL, "Qwen3-8B-int4-ov"]);
+        assert_eq!(config["mediapipe_config_list"][2]["base_path"], "C:/models/Qwen3-8B-int4-ov");
+    }
+
+    #[test]
+    fn test_build_ovms_config_replaces_other_chat_model() {
+        let existing = json!({
+            "mediapipe_config_list": [
+                { "name": "Phi-4-mini-int4-ov", "base_path": "/models/OpenVINO/Phi-4-mini-int4-ov" },
+                { "name": BGE_EMBEDDING_MODEL, "base_path": "/old/bge" }
+            ],
+            "model_config_list": []
+        });
+
+        let (config, replaced) = build_ovms_config(
+            Some(existing),
+            "Qwen3-8B-int4-ov",
+            "/models/OpenVINO/Qwen3-8B-int4-ov",
+            Path::new("/models")
+        );
+
+        assert_eq!(replaced, vec!["Phi-4-mini-int4-ov"]);
+        let names: Vec<&str> = config["mediapipe_config_list"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|model| model["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec![BGE_RERANKER_MODEL, BGE_EMBEDDING_MODEL, "Qwen3-8B-int4-ov"]);
+        assert!(config_update_message(&replaced).contains("Phi-4-mini-int4-ov"));
+    }
+
+    #[test]
+    fn test_loaded_model_status_serialization() {
+        let loading = LoadedModelStatus::Loading("OpenVINO/Qwen3-8B-int4-ov".to_string());
+        assert_eq!(
+            serde_json::to_value(&loading).unwrap(),
+            json!({ "state": "loading", "model_id": "OpenVINO/Qwen3-8B-int4-ov" })
+        );
+        assert_eq!(serde_json::to_value(LoadedModelStatus::None).unwrap(), json!({ "state": "none" }));
+        assert_eq!(loading.model_id(), Some("OpenVINO/Qwen3-8B-int4-ov"));
+    }
+
+    #[test]
+    fn test_device_names() {
+        assert_eq!(device_names(0, false), vec!["CPU"]);
+        assert_eq!(device_names(1, true), vec!["CPU", "GPU", "NPU"]);
+        assert_eq!(device_names(2, false), vec!["CPU", "GPU.0", "GPU.1"]);
+    }
+
+    #[test]
+    fn test_extract_ovms_skips_path_traversal_entries() {
+        let base = std::env::temp_dir().join(format!("sparrow-zip-slip-{}", uuid::Uuid::new_v4()));
+        let extract_to = base.join("nested").join("ovms");
+        fs::create_dir_all(&base).unwrap();
+
+        // The first path component is the archive root and gets stripped on extraction
+        let zip_path = base.join("crafted.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        writer.start_file("ovms/ovms.exe", options).unwrap();
+        writer.write_all(b"binary").unwrap();
+        writer.start_file("ovms/../../escaped.txt", options).unwrap();
+        writer.write_all(b"outside").unwrap();
+        writer.finish().unwrap();
+
+        extract_ovms(&zip_path, &extract_to).unwrap();
+
+        assert!(extract_to.join("ovms.exe").exists());
+        assert!(!base.join("escaped.txt").exists());
+        assert!(!base.join("nested").join("escaped.txt").exists());
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_extracted_file_mode() {
+        assert_eq!(extracted_file_mode(Some(0o100644), Path::new("/x/lib.so")), Some(0o644));
+        assert_eq!(extracted_file_mode(None, Path::new("/x/readme.txt")), None);
+        assert_eq!(extracted_file_mode(None, Path::new("/x/ovms")), Some(0o755));
+        assert_eq!(extracted_file_mode(Some(0o100600), Path::new("/x/ovms")), Some(0o755));
+    }
+
+    #[test]
+    fn test_parse_ovms_config_status_reports_malformed_entries() {
+        let config = json!({
+            "llama": { "model_version_status": [{ "version": "1", "state": "AVAILABLE" }] },
+            "phi": { "model_version_status": [{ "version": "1", "state": "LOADING" }] },
+            "broken": { "model_version_status": "oops" },
+            "no_state": { "model_version_status": [{ "version": "1" }] },
+            "bge-base-en-v1.5-int8-ov": { "model_version_status": [{ "state": "AVAILABLE" }] }
+        });
+
+        let (loaded, warnings) = parse_ovms_config_status(&config).unwrap();
+        assert_eq!(loaded, vec!["llama".to_string()]);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|w| w.contains("broken")));
+        assert!(warnings.iter().any(|w| w.contains("no_state")));
+
+        assert!(parse_ovms_config_status(&json!([])).is_err());
+    }
+
+    #[test]
+    fn test_safe_extract_path() {
+        let root = Path::new("/tmp/ovms");
+        assert_eq!(safe_extract_path(root, "bin/ovms.exe"), Some(root.join("bin/ovms.exe")));
+        assert_eq!(safe_extract_path(root, "../escaped.txt"), None);
+        assert_eq!(safe_extract_path(root, "bin/../../escaped.txt"), None);
+        assert_eq!(safe_extract_path(root, "/etc/passwd"), None);
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::{ Path, PathBuf };
+
+/// Environment variable that overrides where SparrowAI keeps its data
+pub const DATA_DIR_ENV_VAR: &str = "SPARROW_DATA_DIR";
+
+/// Get the user's home directory
+pub fn home_dir() -> Result<PathBuf, String> {
+    std::env
+        ::var("USERPROFILE")
+        .or_else(|_| std::env::var("HOME"))
+        .map(PathBuf::from)
+        .map_err(|_| "Failed to get user home directory".to_string())
+}
+
+/// Settings file that can redirect the data directory. It always lives in ~/.sparrow
+/// so it can be found before the data directory is known.
+pub fn bootstrap_settings_path() -> Result<PathBuf, String> {
+    Ok(home_dir()?.join(".sparrow").join("settings.json"))
+}
+
+// The first non-blank of the environment override and the settings entry, else ~/.sparrow.
+// `home` is only called when neither is set
+fn resolve_data_dir(
+    env_dir: Option<&str>,
+    settings_dir: Option<&str>,
+    home: impl FnOnce() -> Result<PathBuf, String>
+) -> Result<PathBuf, String> {
+    let configured = [env_dir, settings_dir]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|dir| !dir.is_empty());
+
+    match configured {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(home()?.join(".sparrow")),
+    }
+}
+
+/// Resolve the SparrowAI data directory.
+///
+/// Checks the `SPARROW_DATA_DIR` environment variable, then the `data_dir` entry of
+/// `~/.sparrow/settings.json`, and falls back to `~/.sparrow`.
+pub fn sparrow_data_dir() -> Result<PathBuf, String> {
+    let env_dir = std::env::var(DATA_DIR_ENV_VAR).ok();
+    let settings_dir = crate::settings::current().data_dir;
+    resolve_data_dir(env_dir.as_deref(), settings_dir.as_deref(), home_dir)
+}
+
+/// Directory that downloaded models are stored in, `default_download_path` if set
+pub fn models_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = crate::settings::current().default_download_path {
+        if !dir.trim().is_empty() {
+            return Ok(PathBuf::from(dir.trim()));
+        }
+    }
+
+    Ok(sparrow_data_dir()?.join("models"))
+}
+
+/// Total size of all files under a directory, 0 if it doesn't exist
+pub fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => {
+            return 0;
+        }
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let entry_path = entry.path();
+            if entry_path.is_dir() {
+                dir_size(&entry_path)
+            } else {
+                entry
+                    .metadata()
+                    .map(|m| m.len())
+                    .unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_size() {
+        let dir = std::env::temp_dir().join(format!("sparrow-dir-size-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.bin"), [0u8; 10]).unwrap();
+        std::fs::write(dir.join("nested").join("b.bin"), [0u8; 5]).unwrap();
+
+        assert_eq!(dir_size(&dir), 15);
+        assert_eq!(dir_size(&dir.join("missing")), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_data_dir_env_override() {
+        let home = || Ok(PathBuf::from("/home/user"));
+
+        assert_eq!(resolve_data_dir(Some("/data/env"), Some("/data/settings"), home).unwrap(), PathBuf::from("/data/env"));
+        assert_eq!(resolve_data_dir(Some("  "), Some(" /data/settings "), home).unwrap(), PathBuf::from("/data/settings"));
+        assert_eq!(resolve_data_dir(None, Some(""), home).unwrap(), PathBuf::from("/home/user/.sparrow"));
+
+        // The home directory isn't needed when an override is set
+        let no_home = || Err("no home".to_string());
+        assert_eq!(resolve_data_dir(Some("/data/env"), None, no_home).unwrap(), PathBuf::from("/data/env"));
+        assert!(resolve_data_dir(None, None, no_home).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{ Deserialize, Serialize };
+use std::fs;
+use std::path::PathBuf;
+use tracing::{ info, warn };
+
+use crate::chat::ToolMode;
+
+/// Named set of chat parameters stored in <data_dir>/presets.json. Fields left as None
+/// don't override anything when the preset is applied.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatPreset {
+    pub name: String,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub seed: Option<i64>,
+    pub max_tokens: Option<u32>,
+    pub max_completion_tokens: Option<u32>,
+    pub system_prompt: Option<String>,
+    pub tool_mode: Option<ToolMode>,
+}
+
+fn get_presets_path() -> Result<PathBuf, String> {
+    Ok(crate::paths::sparrow_data_dir()?.join("presets.json"))
+}
+
+fn load_presets() -> Result<Vec<ChatPreset>, String> {
+    let path = get_presets_path()?;
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs
+        ::read_to_string(&path)
+        .map_err(|e| format!("Failed to read presets file: {}", e))?;
+
+    serde_json
+        ::from_str::<Vec<ChatPreset>>(&contents)
+        .map_err(|e| format!("Failed to parse presets: {}", e))
+}
+
+fn save_presets(presets: &[ChatPreset]) -> Result<(), String> {
+    let path = get_presets_path()?;
+    if let Some(parent) = path.parent() {
+        fs
+            ::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+
+    let contents = serde_json
+        ::to_string_pretty(presets)
+        .map_err(|e| format!("Failed to serialize presets: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write presets file: {}", e))
+}
+
+// Replace the preset with the same name, or add it, keeping the list sorted by name
+fn upsert_preset(presets: &mut Vec<ChatPreset>, preset: ChatPreset) {
+    presets.retain(|existing| existing.name != preset.name);
+    presets.push(preset);
+    presets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+}
+
+/// Look up a preset by name
+pub fn find_preset(name: &str) -> Result<ChatPreset, String> {
+    load_presets()?
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| format!("Chat preset not found: {}", name))
+}
+
+#[tauri::command]
+pub async fn list_chat_presets() -> Result<Vec<ChatPreset>, String> {
+    load_presets()
+}
+
+#[tauri::command]
+pub async fn save_chat_preset(mut preset: ChatPreset) -> Result<ChatPreset, String> {
+    preset.name = preset.name.trim().to_string();
+    if preset.name.is_empty() {
+        return Err("Preset name must not be empty".to_string());
+    }
+
+    let mut presets = load_presets().unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load presets, starting a new list");
+        Vec::new()
+    });
+    upsert_preset(&mut presets, preset.clone());
+    save_presets(&presets)?;
+
+    info!(preset = %preset.name, "Chat preset saved");
+    Ok(preset)
+}
+
+#[tauri::command]
+pub async fn delete_chat_preset(name: String) -> Result<(), String> {
+    let mut presets = load_presets()?;
+
+    let before = presets.len();
+    presets.retain(|preset| preset.name != name);
+    if presets.len() == before {
+        return Err(format!("Chat preset not found: {}", name));
+    }
+
+    save_presets(&presets)?;
+    info!(preset = %name, "Chat preset deleted");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(name: &str, temperature: f64) -> ChatPreset {
+        ChatPreset {
+            name: name.to_string(),
+            temperature: Some(temperature),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_upsert_preset_replaces_by_name() {
+        let mut presets = vec![preset("precise", 0.1)];
+
+        upsert_preset(&mut presets, preset("creative", 1.2));
+        upsert_preset(&mut presets, preset("precise", 0.2));
+
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "creative");
+        assert_eq!(presets[1].temperature, Some(0.2));
+    }
+
+    #[test]
+    fn test_preset_missing_fields_are_none() {
+        let preset: ChatPreset = serde_json::from_str(r#"{ "name": "short", "max_tokens": 200 }"#).unwrap();
+        assert_eq!(preset.max_tokens, Some(200));
+        assert_eq!(preset.temperature, None);
+        assert_eq!(preset.tool_mode, None);
+    }
+}
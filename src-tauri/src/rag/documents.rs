@@ -1,29 +1,222 @@
-use super::Document;
-use pdf_extract::extract_text;
-use calamine::{Reader, Xlsx, open_workbook};
-use std::path::Path;
+use super::{ChunkPreview, Document, TempCleanupStats};
+use calamine::{Reader, open_workbook_auto};
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+use chardetng::EncodingDetector;
+use encoding_rs::Encoding;
+use tauri::{AppHandle, Emitter};
 
+/// File extensions that process_document knows how to ingest. This is the single source of
+/// truth for the watcher, upload validation and the UI (via supported_document_types).
+pub const SUPPORTED_EXTENSIONS: [&str; 6] = ["pdf", "docx", "xlsx", "xls", "txt", "md"];
+
+// PDFs with less readable text than this per page are treated as scanned images
+const MIN_PDF_CHARS_PER_PAGE: usize = 25;
+
+// Uploads older than this are removed by the startup sweep
+const TEMP_FILE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Characters shown per chunk by preview_document_chunks
+const CHUNK_PREVIEW_CHARS: usize = 120;
+
+/// Chunk length and the characters consecutive chunks share, both counted in chars
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ChunkOptions {
+    chunk_size: usize,
+    overlap: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self { chunk_size: 1000, overlap: 200 }
+    }
+}
+
+impl ChunkOptions {
+    fn new(chunk_size: usize, overlap: usize) -> Result<Self, String> {
+        if chunk_size == 0 {
+            return Err("Chunk size must be at least 1".to_string());
+        }
+        // Each chunk starts chunk_size - overlap after the previous one
+        if overlap >= chunk_size {
+            return Err(format!("Overlap ({}) must be smaller than the chunk size ({})", overlap, chunk_size));
+        }
+        Ok(Self { chunk_size, overlap })
+    }
+
+    /// Options for an ingest command, missing values fall back to the defaults. A custom
+    /// chunk_size without an overlap keeps the default 1/5 ratio
+    fn from_request(chunk_size: Option<usize>, overlap: Option<usize>) -> Result<Self, String> {
+        let chunk_size = chunk_size.unwrap_or(Self::default().chunk_size);
+        Self::new(chunk_size, overlap.unwrap_or(chunk_size / 5))
+    }
+}
+
+#[tauri::command]
+pub async fn supported_document_types() -> Vec<String> {
+    SUPPORTED_EXTENSIONS.iter().map(|ext| ext.to_string()).collect()
+}
+
+#[tauri::command]
+pub async fn process_document(
+    file_path: String,
+    chunk_size: Option<usize>,
+    overlap: Option<usize>
+) -> Result<Vec<Document>, String> {
+    let chunking = ChunkOptions::from_request(chunk_size, overlap)?;
+    process_document_with_progress(&file_path, chunking, |_, _, _| {}).await
+}
+
+/// Split a file the way process_document would with the given chunk size and overlap,
+/// without embedding or storing anything, so chunking can be tuned before ingesting
+#[tauri::command]
+pub async fn preview_document_chunks(
+    file_path: String,
+    chunk_size: usize,
+    overlap: usize
+) -> Result<Vec<ChunkPreview>, String> {
+    let chunking = ChunkOptions::new(chunk_size, overlap)?;
+    let documents = process_document_with_progress(&file_path, chunking, |_, _, _| {}).await?;
+
+    Ok(
+        documents
+            .into_iter()
+            .enumerate()
+            .map(|(index, document)| ChunkPreview {
+                index,
+                char_length: document.content.chars().count(),
+                preview: document.content.chars().take(CHUNK_PREVIEW_CHARS).collect(),
+                title: document.title,
+            })
+            .collect()
+    )
+}
+
+/// Same as process_document, but emits `document-processing-progress` events as pages or
+/// sheets are processed so the UI can show progress on large files
 #[tauri::command]
-pub async fn process_document(file_path: String) -> Result<Vec<Document>, String> {
-    let path = Path::new(&file_path);
+pub async fn process_document_streaming(
+    app: AppHandle,
+    file_path: String,
+    chunk_size: Option<usize>,
+    overlap: Option<usize>
+) -> Result<Vec<Document>, String> {
+    let chunking = ChunkOptions::from_request(chunk_size, overlap)?;
+    let documents = process_document_with_progress(&file_path, chunking, |stage, processed, total| {
+        let _ = app.emit(
+            "document-processing-progress",
+            serde_json::json!({
+                "filePath": file_path,
+                "stage": stage,
+                "processed": processed,
+                "total": total
+            })
+        );
+    }).await?;
+
+    let _ = app.emit(
+        "document-processing-progress",
+        serde_json::json!({
+            "filePath": file_path,
+            "stage": "complete",
+            "processed": documents.len(),
+            "total": documents.len()
+        })
+    );
+
+    Ok(documents)
+}
+
+/// Process a document, reporting (stage, processed, total) as its pages or sheets are handled.
+/// A total of 0 means the amount of work isn't known yet.
+async fn process_document_with_progress<F>(
+    file_path: &str,
+    chunking: ChunkOptions,
+    mut on_progress: F
+) -> Result<Vec<Document>, String>
+    where F: FnMut(&'static str, usize, usize)
+{
+    let path = Path::new(file_path);
     let extension = path.extension()
         .and_then(|s| s.to_str())
         .unwrap_or("")
         .to_lowercase();
 
+    if !SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported file type: .{}. Supported types: {}",
+            extension,
+            SUPPORTED_EXTENSIONS.join(", ")
+        ));
+    }
+
     match extension.as_str() {
-        "pdf" => process_pdf(&file_path).await,
-        "docx" => process_docx(&file_path).await,
-        "xlsx" | "xls" => process_excel(&file_path).await,
-        _ => Err("Unsupported file type".to_string()),
+        "pdf" => process_pdf(file_path, chunking, &mut on_progress).await,
+        "docx" => process_docx(file_path, chunking).await,
+        "xlsx" | "xls" => process_excel(file_path, chunking, &mut on_progress).await,
+        "txt" | "md" => process_text(file_path, &extension, chunking).await,
+        other => Err(format!("No processor registered for .{} files", other)),
     }
 }
 
+/// Directory that holds uploaded files until they are ingested. Everything in it belongs
+/// to SparrowAI, so the cleanup routines can remove entries without extra bookkeeping.
+fn get_temp_upload_dir() -> Result<PathBuf, String> {
+    Ok(crate::paths::sparrow_data_dir()?.join("tmp"))
+}
+
+/// Check that an upload has a plain supported file name and fits the size limit
+fn validate_upload(file_name: &str, size: u64, max_size: u64) -> Result<(), String> {
+    let trimmed = file_name.trim();
+    if trimmed.is_empty() || trimmed == "." || trimmed == ".." {
+        return Err("Invalid file name".to_string());
+    }
+    if trimmed.contains('/') || trimmed.contains('\\') || trimmed.contains('\0') {
+        return Err(format!("File name must not contain path separators: {}", file_name));
+    }
+    
+    let extension = Path::new(trimmed)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if !SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported file type: {}. Supported types: {}",
+            file_name,
+            SUPPORTED_EXTENSIONS.join(", ")
+        ));
+    }
+    
+    if size > max_size {
+        return Err(format!(
+            "File is too large: {:.1} MB (limit {:.1} MB)",
+            size as f64 / (1024.0 * 1024.0),
+            max_size as f64 / (1024.0 * 1024.0)
+        ));
+    }
+    
+    Ok(())
+}
+
 #[tauri::command]
-pub async fn save_temp_file(file_name: String, file_data: Vec<u8>) -> Result<String, String> {
-    let temp_dir = std::env::temp_dir();
-    let file_path = temp_dir.join(&file_name);
+pub async fn save_temp_file(
+    file_name: String,
+    file_data: Vec<u8>
+) -> Result<String, String> {
+    // Read from settings rather than taken from the caller, so the UI can't raise it
+    let max_bytes = crate::settings::current().max_upload_mb.saturating_mul(1024 * 1024);
+    validate_upload(&file_name, file_data.len() as u64, max_bytes)?;
+    let file_name = file_name.trim();
+    
+    // Each upload gets its own directory so identical names don't overwrite each other
+    // while the original file name (used as the document title) is preserved
+    let upload_dir = get_temp_upload_dir()?.join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&upload_dir)
+        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let file_path = upload_dir.join(file_name);
     
     fs::write(&file_path, file_data)
         .map_err(|e| format!("Failed to save temp file: {}", e))?;
@@ -31,11 +224,141 @@ pub async fn save_temp_file(file_name: String, file_data: Vec<u8>) -> Result<Str
     Ok(file_path.to_string_lossy().to_string())
 }
 
-async fn process_pdf(file_path: &str) -> Result<Vec<Document>, String> {
-    let text = extract_text(file_path)
+// Count the files and bytes under a path
+fn measure_path(path: &Path) -> (usize, u64) {
+    if path.is_dir() {
+        fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| measure_path(&entry.path()))
+                    .fold((0, 0), |(files, bytes), (f, b)| (files + f, bytes + b))
+            })
+            .unwrap_or((0, 0))
+    } else {
+        (1, fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+    }
+}
+
+/// Remove uploads from the temp directory, optionally only those older than `max_age`
+fn remove_temp_files(temp_dir: &Path, max_age: Option<Duration>) -> Result<TempCleanupStats, String> {
+    let mut stats = TempCleanupStats::default();
+    if !temp_dir.exists() {
+        return Ok(stats);
+    }
+    
+    let entries = fs::read_dir(temp_dir)
+        .map_err(|e| format!("Failed to read temp directory: {}", e))?;
+    let now = SystemTime::now();
+    
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        
+        if let Some(max_age) = max_age {
+            let age = entry.metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok());
+            match age {
+                Some(age) if age >= max_age => {}
+                _ => continue,
+            }
+        }
+        
+        let (files, bytes) = measure_path(&path);
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        
+        match removed {
+            Ok(()) => {
+                stats.files_removed += files;
+                stats.bytes_reclaimed += bytes;
+            }
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to remove temp upload");
+            }
+        }
+    }
+    
+    Ok(stats)
+}
+
+#[tauri::command]
+pub async fn cleanup_temp_files() -> Result<TempCleanupStats, String> {
+    let stats = remove_temp_files(&get_temp_upload_dir()?, None)?;
+    info!(files_removed = stats.files_removed, bytes_reclaimed = stats.bytes_reclaimed, "Cleaned up temp uploads");
+    Ok(stats)
+}
+
+/// Remove uploads left over from previous sessions, called once on startup
+pub fn sweep_old_temp_files() {
+    let result = get_temp_upload_dir()
+        .and_then(|temp_dir| remove_temp_files(&temp_dir, Some(TEMP_FILE_MAX_AGE)));
+    
+    match result {
+        Ok(stats) if stats.files_removed > 0 => {
+            info!(files_removed = stats.files_removed, bytes_reclaimed = stats.bytes_reclaimed, "Swept old temp uploads");
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!(error = %e, "Failed to sweep old temp uploads");
+        }
+    }
+}
+
+async fn process_pdf<F>(
+    file_path: &str,
+    chunking: ChunkOptions,
+    on_progress: &mut F
+) -> Result<Vec<Document>, String>
+    where F: FnMut(&'static str, usize, usize)
+{
+    on_progress("extracting", 0, 0);
+
+    // Extract page by page so every chunk can cite the pages it came from. Extraction is CPU
+    // bound, so it runs off the async runtime and sends each page back as soon as it's done
+    let owned_path = file_path.to_string();
+    let (page_tx, mut page_rx) = tokio::sync::mpsc::unbounded_channel();
+    let extraction = tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let pdf = lopdf::Document::load(&owned_path).map_err(|e| e.to_string())?;
+        let page_numbers: Vec<u32> = pdf.get_pages().keys().copied().collect();
+        let page_count = page_numbers.len();
+
+        for page_number in page_numbers {
+            // One unreadable page shouldn't cost the rest of the document
+            let text = pdf.extract_text(&[page_number]).unwrap_or_else(|e| {
+                warn!(page = page_number, error = %e, "Failed to extract text from PDF page");
+                String::new()
+            });
+            if page_tx.send((text, page_count)).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    });
+
+    let mut pages: Vec<String> = Vec::new();
+    while let Some((text, page_count)) = page_rx.recv().await {
+        pages.push(normalize_extracted_text(&text));
+        on_progress("pages", pages.len(), page_count);
+    }
+    extraction.await
+        .map_err(|e| format!("PDF extraction task failed: {}", e))?
         .map_err(|e| format!("Failed to extract PDF text: {}", e))?;
+    let page_count = pages.len();
+
+    // Image-only PDFs extract to nothing useful, so fail loudly instead of storing blank chunks
+    if looks_scanned(&pages.concat(), page_count) {
+        return Err(format!(
+            "This PDF appears to be scanned ({} pages with little or no text); OCR is not supported",
+            page_count
+        ));
+    }
     
-    let chunks = chunk_text(&text, 1000, 200); // 1000 chars with 200 overlap
+    let chunks = chunk_pages(&pages, chunking.chunk_size, chunking.overlap);
     
     let mut documents = Vec::new();
     let file_name = Path::new(file_path)
@@ -46,32 +369,218 @@ async fn process_pdf(file_path: &str) -> Result<Vec<Document>, String> {
         .to_string();
     
     for (i, chunk) in chunks.iter().enumerate() {
-        if chunk.trim().is_empty() {
+        if chunk.text.trim().is_empty() {
             continue; // Skip empty chunks
         }
         
-        documents.push(Document::new(
+        let page_label = if chunk.start_page == chunk.end_page {
+            format!("p. {}", chunk.start_page)
+        } else {
+            format!("pp. {}-{}", chunk.start_page, chunk.end_page)
+        };
+        
+        let mut document = Document::new(
+            format!("{} - Part {} ({})", file_name, i + 1, page_label),
+            chunk.text.clone(),
+            "pdf".to_string(),
+            file_path.to_string(),
+            Some(i),
+        );
+        document.metadata.insert("encoding".to_string(), "UTF-8".to_string());
+        document.metadata.insert("page_start".to_string(), chunk.start_page.to_string());
+        document.metadata.insert("page_end".to_string(), chunk.end_page.to_string());
+        documents.push(document);
+    }
+    
+    Ok(documents)
+}
+
+/// A chunk of PDF text with the 1-based range of pages it spans
+struct PageChunk {
+    text: String,
+    start_page: usize,
+    end_page: usize,
+}
+
+/// Chunk the pages as one continuous text while tracking which pages each chunk covers
+fn chunk_pages(pages: &[String], chunk_size: usize, overlap: usize) -> Vec<PageChunk> {
+    let mut chars: Vec<char> = Vec::new();
+    let mut page_starts: Vec<usize> = Vec::with_capacity(pages.len());
+    for page in pages {
+        if !chars.is_empty() {
+            chars.extend("\n\n".chars());
+        }
+        page_starts.push(chars.len());
+        chars.extend(page.chars());
+    }
+    
+    // Page number (1-based) of the character at the given offset
+    let page_at = |offset: usize| page_starts.partition_point(|&start| start <= offset).max(1);
+    
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let end = std::cmp::min(start + chunk_size, chars.len());
+        let text: String = chars[start..end].iter().collect();
+        
+        if !text.trim().is_empty() {
+            chunks.push(PageChunk {
+                text,
+                start_page: page_at(start),
+                end_page: page_at(end - 1),
+            });
+        }
+        
+        if end == chars.len() {
+            break;
+        }
+        start += chunk_size - overlap;
+    }
+    
+    chunks
+}
+
+/// Whether the extracted text is too sparse for the page count to be a text PDF
+fn looks_scanned(text: &str, page_count: usize) -> bool {
+    let readable_chars = text.chars().filter(|c| c.is_alphanumeric()).count();
+    readable_chars < MIN_PDF_CHARS_PER_PAGE * page_count.max(1)
+}
+
+async fn process_text(file_path: &str, file_type: &str, chunking: ChunkOptions) -> Result<Vec<Document>, String> {
+    let bytes = fs::read(file_path)
+        .map_err(|e| format!("Failed to read text file: {}", e))?;
+    
+    let (decoded, encoding) = decode_text_bytes(&bytes);
+    let text = normalize_extracted_text(&decoded);
+    
+    let chunks = chunk_text(&text, chunking.chunk_size, chunking.overlap);
+    
+    let mut documents = Vec::new();
+    let file_name = Path::new(file_path)
+        .file_stem()
+        .unwrap_or_default()
+        .to_str()
+        .unwrap_or("Unknown")
+        .to_string();
+    
+    for (i, chunk) in chunks.iter().enumerate() {
+        if chunk.trim().is_empty() {
+            continue;
+        }
+        
+        let mut document = Document::new(
             format!("{} - Part {}", file_name, i + 1),
             chunk.clone(),
-            "pdf".to_string(),
+            file_type.to_string(),
             file_path.to_string(),
             Some(i),
-        ));
+        );
+        document.metadata.insert("encoding".to_string(), encoding.to_string());
+        documents.push(document);
     }
     
     Ok(documents)
 }
 
-async fn process_docx(file_path: &str) -> Result<Vec<Document>, String> {
-    // For now, we'll use a simple text extraction approach
-    // You may want to use a more sophisticated DOCX parser
-    let _file = fs::File::open(file_path)
-        .map_err(|e| format!("Failed to open DOCX: {}", e))?;
+/// Decode raw bytes to UTF-8, honouring a BOM if present and otherwise guessing the encoding
+fn decode_text_bytes(bytes: &[u8]) -> (String, &'static str) {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(None, true)
+        }
+    };
     
-    // Simple DOCX processing - you might want to use docx-rs properly
-    let text = format!("DOCX content from: {}", file_path);
+    // decode() strips the BOM and replaces invalid sequences
+    let (text, _, _) = encoding.decode(bytes);
+    (text.into_owned(), encoding.name())
+}
+
+/// Normalize line endings and strip control characters that poison embeddings
+fn normalize_extracted_text(text: &str) -> String {
+    let unified = text.replace("\r\n", "\n").replace('\r', "\n");
     
-    let chunks = chunk_text(&text, 1000, 200);
+    let mut normalized = String::with_capacity(unified.len());
+    let mut consecutive_newlines = 0;
+    for c in unified.chars() {
+        if c == '\n' {
+            consecutive_newlines += 1;
+            // Collapse runs of blank lines left behind by page breaks
+            if consecutive_newlines > 2 {
+                continue;
+            }
+            normalized.push(c);
+        } else if (c.is_control() && c != '\t') || c == '\u{FFFD}' || c == '\u{FEFF}' {
+            continue;
+        } else {
+            consecutive_newlines = 0;
+            normalized.push(c);
+        }
+    }
+    
+    normalized
+}
+
+// Text of a paragraph's runs, with tabs and line breaks kept as whitespace
+fn docx_paragraph_text(paragraph: &docx_rs::Paragraph) -> String {
+    let mut text = String::new();
+    for child in &paragraph.children {
+        if let docx_rs::ParagraphChild::Run(run) = child {
+            for run_child in &run.children {
+                match run_child {
+                    docx_rs::RunChild::Text(t) => text.push_str(&t.text),
+                    docx_rs::RunChild::Tab(_) => text.push('\t'),
+                    docx_rs::RunChild::Break(_) => text.push('\n'),
+                    _ => {}
+                }
+            }
+        }
+    }
+    text
+}
+
+// Body text of a document, one line per paragraph and one tab-separated line per table row
+fn docx_text(docx: &docx_rs::Docx) -> String {
+    let mut lines = Vec::new();
+    for child in &docx.document.children {
+        match child {
+            docx_rs::DocumentChild::Paragraph(paragraph) => lines.push(docx_paragraph_text(paragraph)),
+            docx_rs::DocumentChild::Table(table) => {
+                for row in &table.rows {
+                    let docx_rs::TableChild::TableRow(row) = row;
+                    let cells: Vec<String> = row.cells
+                        .iter()
+                        .map(|cell| {
+                            let docx_rs::TableRowChild::TableCell(cell) = cell;
+                            cell.children
+                                .iter()
+                                .filter_map(|content| match content {
+                                    docx_rs::TableCellContent::Paragraph(paragraph) => Some(docx_paragraph_text(paragraph)),
+                                    _ => None,
+                                })
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        })
+                        .collect();
+                    lines.push(cells.join("\t"));
+                }
+            }
+            _ => {}
+        }
+    }
+    lines.join("\n")
+}
+
+async fn process_docx(file_path: &str, chunking: ChunkOptions) -> Result<Vec<Document>, String> {
+    let bytes = fs::read(file_path)
+        .map_err(|e| format!("Failed to open DOCX: {}", e))?;
+    let docx = docx_rs::read_docx(&bytes)
+        .map_err(|e| format!("Failed to parse DOCX: {}", e))?;
+    let text = docx_text(&docx);
+    
+    let chunks = chunk_text(&text, chunking.chunk_size, chunking.overlap);
     
     let mut documents = Vec::new();
     let file_name = Path::new(file_path)
@@ -98,8 +607,15 @@ async fn process_docx(file_path: &str) -> Result<Vec<Document>, String> {
     Ok(documents)
 }
 
-async fn process_excel(file_path: &str) -> Result<Vec<Document>, String> {
-    let mut workbook: Xlsx<_> = open_workbook(file_path)
+async fn process_excel<F>(
+    file_path: &str,
+    chunking: ChunkOptions,
+    on_progress: &mut F
+) -> Result<Vec<Document>, String>
+    where F: FnMut(&'static str, usize, usize)
+{
+    // Picks the xlsx or legacy xls reader from the file itself
+    let mut workbook = open_workbook_auto(file_path)
         .map_err(|e| format!("Failed to open Excel: {}", e))?;
     
     let mut documents = Vec::new();
@@ -110,7 +626,10 @@ async fn process_excel(file_path: &str) -> Result<Vec<Document>, String> {
         .unwrap_or("Unknown")
         .to_string();
     
-    for sheet_name in workbook.sheet_names().to_vec() {
+    let sheet_names = workbook.sheet_names().to_vec();
+    let sheet_count = sheet_names.len();
+    for (sheet_index, sheet_name) in sheet_names.into_iter().enumerate() {
+        on_progress("sheets", sheet_index, sheet_count);
         if let Ok(range) = workbook.worksheet_range(&sheet_name) {
             let mut text = String::new();
             text.push_str(&format!("Sheet: {}\n", sheet_name));
@@ -122,7 +641,7 @@ async fn process_excel(file_path: &str) -> Result<Vec<Document>, String> {
                 text.push('\n');
             }
             
-            let chunks = chunk_text(&text, 1000, 200);
+            let chunks = chunk_text(&text, chunking.chunk_size, chunking.overlap);
             
             for (i, chunk) in chunks.iter().enumerate() {
                 if chunk.trim().is_empty() {
@@ -139,6 +658,7 @@ async fn process_excel(file_path: &str) -> Result<Vec<Document>, String> {
             }
         }
     }
+    on_progress("sheets", sheet_count, sheet_count);
     
     Ok(documents)
 }
@@ -173,6 +693,20 @@ fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_chunk_options_rejects_overlap_past_chunk_size() {
+        assert_eq!(ChunkOptions::new(500, 50).unwrap(), ChunkOptions { chunk_size: 500, overlap: 50 });
+        assert!(ChunkOptions::new(0, 0).is_err());
+        assert!(ChunkOptions::new(100, 100).is_err());
+    }
+
+    #[test]
+    fn test_chunk_options_from_request() {
+        assert_eq!(ChunkOptions::from_request(None, None).unwrap(), ChunkOptions::default());
+        assert_eq!(ChunkOptions::from_request(Some(500), None).unwrap(), ChunkOptions { chunk_size: 500, overlap: 100 });
+        assert!(ChunkOptions::from_request(Some(100), Some(100)).is_err());
+    }
+
     #[test]
     fn test_chunk_text() {
         let text = "This is a test text that should be chunked properly.";
@@ -180,4 +714,85 @@ mod tests {
         assert!(!chunks.is_empty());
         assert!(chunks[0].len() <= 20);
     }
+
+    #[test]
+    fn test_decode_text_bytes_windows_1252() {
+        // "café résumé" encoded as Windows-1252
+        let bytes = b"caf\xe9 r\xe9sum\xe9 and more latin text to help detection";
+        let (text, _encoding) = decode_text_bytes(bytes);
+        assert!(text.starts_with("café résumé"));
+    }
+
+    #[test]
+    fn test_decode_text_bytes_utf8_bom() {
+        let bytes = b"\xEF\xBB\xBFhello";
+        let (text, encoding) = decode_text_bytes(bytes);
+        assert_eq!(text, "hello");
+        assert_eq!(encoding, "UTF-8");
+    }
+
+    #[test]
+    fn test_validate_upload_rejects_path_traversal() {
+        assert!(validate_upload("../../evil.txt", 10, 1024).is_err());
+        assert!(validate_upload("..\\evil.pdf", 10, 1024).is_err());
+        assert!(validate_upload("/etc/passwd.txt", 10, 1024).is_err());
+        assert!(validate_upload("..", 10, 1024).is_err());
+        assert!(validate_upload("report.pdf", 10, 1024).is_ok());
+    }
+
+    #[test]
+    fn test_validate_upload_rejects_oversize_and_unsupported() {
+        assert!(validate_upload("notes.md", 2048, 1024).is_err());
+        assert!(validate_upload("notes.md", 1024, 1024).is_ok());
+        assert!(validate_upload("script.exe", 10, 1024).is_err());
+        assert!(validate_upload("no_extension", 10, 1024).is_err());
+    }
+
+    #[test]
+    fn test_chunk_pages_tracks_page_ranges() {
+        let pages = vec!["a".repeat(30), "b".repeat(30), "c".repeat(30)];
+        let chunks = chunk_pages(&pages, 40, 10);
+
+        assert_eq!(chunks[0].start_page, 1);
+        assert_eq!(chunks[0].end_page, 2);
+        assert!(chunks[0].text.starts_with("aaa"));
+
+        let last = chunks.last().unwrap();
+        assert_eq!(last.end_page, 3);
+        assert!(last.text.ends_with("ccc"));
+    }
+
+    #[test]
+    fn test_looks_scanned() {
+        assert!(looks_scanned("", 3));
+        assert!(looks_scanned("  \n 12 \n", 1));
+        assert!(looks_scanned(&"word ".repeat(10), 5));
+        assert!(!looks_scanned(&"word ".repeat(100), 5));
+    }
+
+    #[test]
+    fn test_remove_temp_files() {
+        let temp_dir = std::env::temp_dir().join(format!("sparrow-tmp-test-{}", uuid::Uuid::new_v4()));
+        let upload_dir = temp_dir.join("upload");
+        fs::create_dir_all(&upload_dir).unwrap();
+        fs::write(upload_dir.join("notes.txt"), b"hello").unwrap();
+
+        // Fresh uploads survive the age-based sweep
+        let stats = remove_temp_files(&temp_dir, Some(TEMP_FILE_MAX_AGE)).unwrap();
+        assert_eq!(stats.files_removed, 0);
+        assert!(upload_dir.exists());
+
+        let stats = remove_temp_files(&temp_dir, None).unwrap();
+        assert_eq!(stats.files_removed, 1);
+        assert_eq!(stats.bytes_reclaimed, 5);
+        assert!(!upload_dir.exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_normalize_extracted_text() {
+        let text = "line one\r\nline\u{0}two\u{c}\n\n\n\nline three";
+        assert_eq!(normalize_extracted_text(text), "line one\nlinetwo\n\nline three");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use sled::Db;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+static CACHE_DB: OnceLock<Db> = OnceLock::new();
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+
+// Access times are only rewritten once they are this old, so repeated hits don't rewrite entries
+const ACCESS_REFRESH_MS: i64 = 60 * 60 * 1000;
+
+/// Embedding stored under the hash of the model name and the embedded text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEmbedding {
+    embedding: Vec<f32>,
+    last_access: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCacheStats {
+    pub entry_count: usize,
+    pub total_bytes: u64,
+    pub max_entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Hits divided by lookups since the app started, 0 when nothing was looked up yet
+    pub hit_rate: f64,
+}
+
+// The cache is opened once and shared, sled only allows one handle per path
+fn cache_db() -> Result<&'static Db, String> {
+    if let Some(db) = CACHE_DB.get() {
+        return Ok(db);
+    }
+
+    let path = crate::paths::sparrow_data_dir()?.join("embedding_cache");
+    let db = sled::open(&path).map_err(|e| format!("Failed to open embedding cache: {}", e))?;
+    Ok(CACHE_DB.get_or_init(|| db))
+}
+
+fn cache_key(model: &str, text: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Look up cached embeddings for `texts`, returning None for every text that isn't cached.
+/// Hits not used within the last hour have their access time refreshed in one batch so
+/// they survive eviction
+pub fn get_many(model: &str, texts: &[String]) -> Vec<Option<Vec<f32>>> {
+    let db = match cache_db() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!(error = %e, "Embedding cache unavailable");
+            return vec![None; texts.len()];
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut refreshed = sled::Batch::default();
+    let embeddings = texts
+        .iter()
+        .map(|text| {
+            let key = cache_key(model, text);
+            let cached = db
+                .get(&key)
+                .ok()
+                .flatten()
+                .and_then(|bytes| bincode::deserialize::<CachedEmbedding>(&bytes).ok());
+
+            match cached {
+                Some(mut entry) => {
+                    CACHE_HITS.fetch_add(1, Ordering::Relaxed);
+                    if now - entry.last_access >= ACCESS_REFRESH_MS {
+                        entry.last_access = now;
+                        if let Ok(bytes) = bincode::serialize(&entry) {
+                            refreshed.insert(key.as_bytes(), bytes);
+                        }
+                    }
+                    Some(entry.embedding)
+                }
+                None => {
+                    CACHE_MISSES.fetch_add(1, Ordering::Relaxed);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    if let Err(e) = db.apply_batch(refreshed) {
+        warn!(error = %e, "Failed to refresh embedding cache access times");
+    }
+    embeddings
+}
+
+/// Store freshly computed embeddings, then evict the least recently used entries
+/// if the cache grew past `embedding_cache_max_entries`. Eviction goes down to 90% of the
+/// limit so the cache isn't scanned again on every following insert
+pub fn insert_many(model: &str, texts: &[String], embeddings: &[Vec<f32>]) {
+    let max_entries = crate::settings::current().embedding_cache_max_entries;
+    if max_entries == 0 {
+        return;
+    }
+
+    let db = match cache_db() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!(error = %e, "Embedding cache unavailable");
+            return;
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut batch = sled::Batch::default();
+    for (text, embedding) in texts.iter().zip(embeddings) {
+        let entry = CachedEmbedding {
+            embedding: embedding.clone(),
+            last_access: now,
+        };
+        if let Ok(bytes) = bincode::serialize(&entry) {
+            batch.insert(cache_key(model, text).as_bytes(), bytes);
+        }
+    }
+    if let Err(e) = db.apply_batch(batch) {
+        warn!(error = %e, "Failed to write embedding cache");
+        return;
+    }
+
+    if db.len() > max_entries {
+        evict_lru(db, eviction_target(max_entries));
+    }
+}
+
+// Entries kept after an eviction pass
+fn eviction_target(max_entries: usize) -> usize {
+    max_entries - max_entries / 10
+}
+
+// Keys to remove so that only the `max_entries` most recently used entries remain
+fn lru_victims(mut entries: Vec<(i64, sled::IVec)>, max_entries: usize) -> Vec<sled::IVec> {
+    if entries.len() <= max_entries {
+        return Vec::new();
+    }
+
+    entries.sort_by_key(|(last_access, _)| *last_access);
+    let excess = entries.len() - max_entries;
+    entries.into_iter().take(excess).map(|(_, key)| key).collect()
+}
+
+fn evict_lru(db: &Db, max_entries: usize) {
+    let entries: Vec<(i64, sled::IVec)> = db
+        .iter()
+        .filter_map(|item| item.ok())
+        .map(|(key, value)| {
+            // Unreadable entries sort first so they are evicted before anything useful
+            let last_access = bincode::deserialize::<CachedEmbedding>(&value)
+                .map(|entry| entry.last_access)
+                .unwrap_or(i64::MIN);
+            (last_access, key)
+        })
+        .collect();
+
+    let victims = lru_victims(entries, max_entries);
+    let mut batch = sled::Batch::default();
+    for key in &victims {
+        batch.remove(key);
+    }
+    match db.apply_batch(batch) {
+        Ok(()) => info!(evicted = victims.len(), "Evicted least recently used embeddings"),
+        Err(e) => warn!(error = %e, "Failed to evict embedding cache entries"),
+    }
+}
+
+#[tauri::command]
+pub async fn get_embedding_cache_stats() -> Result<EmbeddingCacheStats, String> {
+    let db = cache_db()?;
+
+    let total_bytes = db
+        .iter()
+        .filter_map(|item| item.ok())
+        .map(|(key, value)| (key.len() + value.len()) as u64)
+        .sum();
+
+    let hits = CACHE_HITS.load(Ordering::Relaxed);
+    let misses = CACHE_MISSES.load(Ordering::Relaxed);
+    let lookups = hits + misses;
+
+    Ok(EmbeddingCacheStats {
+        entry_count: db.len(),
+        total_bytes,
+        max_entries: crate::settings::current().embedding_cache_max_entries,
+        hits,
+        misses,
+        hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_victims_evicts_oldest() {
+        let entries = vec![
+            (30, sled::IVec::from("c")),
+            (10, sled::IVec::from("a")),
+            (40, sled::IVec::from("d")),
+            (20, sled::IVec::from("b")),
+        ];
+
+        let victims = lru_victims(entries, 2);
+        assert_eq!(victims, vec![sled::IVec::from("a"), sled::IVec::from("b")]);
+        assert!(lru_victims(vec![(1, sled::IVec::from("a"))], 2).is_empty());
+    }
+
+    #[test]
+    fn test_eviction_target_leaves_headroom() {
+        assert_eq!(eviction_target(20_000), 18_000);
+        assert_eq!(eviction_target(5), 5);
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_model() {
+        assert_eq!(cache_key("bge", "hello"), cache_key("bge", "hello"));
+        assert_ne!(cache_key("bge", "hello"), cache_key("other", "hello"));
+    }
+}
@@ -1,6 +1,66 @@
-use super::Document;
+use super::{embedding_cache, Document};
+use super::vector_store::VectorStore;
 use async_openai::{ types::CreateEmbeddingRequestArgs, Client };
 use async_openai::config::OpenAIConfig;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+// Number of texts sent to the embedding endpoint per request
+const EMBEDDING_BATCH_SIZE: usize = 32;
+
+// How often a running embedding request checks whether it was cancelled
+const CANCEL_POLL_MS: u64 = 200;
+
+// Error create_embeddings_in_batches stops with, turned into EmbeddingOutcome::Cancelled
+// before it reaches the UI
+const CANCELLED: &str = "cancelled";
+
+lazy_static::lazy_static! {
+    // Running operations by id, true once the UI asked to cancel them. Entries only exist
+    // while the operation runs, so cancelling an unknown id leaves nothing behind
+    static ref RUNNING_OPERATIONS: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+}
+
+/// Result of create_document_embeddings. Cancelling isn't an error: batches finished before
+/// that are kept in the embedding cache when `resumable`, so embedding the same documents
+/// again picks up where it stopped. With embedding_cache_max_entries at 0 it starts over
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum EmbeddingOutcome {
+    Completed {
+        documents: Vec<Document>,
+    },
+    Cancelled {
+        processed: usize,
+        total: usize,
+        resumable: bool,
+    },
+}
+
+fn start_operation(operation_id: Option<&str>) {
+    if let (Some(id), Ok(mut running)) = (operation_id, RUNNING_OPERATIONS.lock()) {
+        running.insert(id.to_string(), false);
+    }
+}
+
+fn is_operation_cancelled(operation_id: Option<&str>) -> bool {
+    match operation_id {
+        Some(id) =>
+            RUNNING_OPERATIONS.lock()
+                .map(|running| running.get(id) == Some(&true))
+                .unwrap_or(false),
+        None => false,
+    }
+}
+
+fn finish_operation(operation_id: Option<&str>) {
+    if let (Some(id), Ok(mut running)) = (operation_id, RUNNING_OPERATIONS.lock()) {
+        running.remove(id);
+    }
+}
 
 pub struct EmbeddingService {
     client: Client<OpenAIConfig>,
@@ -10,20 +70,49 @@ impl EmbeddingService {
     pub fn new() -> Self {
         let config = OpenAIConfig::new()
             .with_api_key("unused")
-            .with_api_base("http://localhost:1114/v3"); // Your OVMS endpoint
+            .with_api_base(format!("{}/v3", crate::settings::ovms_base_url())); // Your OVMS endpoint
 
         Self {
             client: Client::with_config(config),
         }
     }
 
+    /// Embed texts, reusing cached embeddings and only sending the rest to OVMS
     pub async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
         if texts.is_empty() {
             return Ok(Vec::new());
         }
 
+        let model = crate::settings::current().embedding_model;
+        let mut embeddings = embedding_cache::get_many(&model, &texts);
+        let missing: Vec<String> = texts
+            .iter()
+            .zip(&embeddings)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(text, _)| text.clone())
+            .collect();
+
+        if !missing.is_empty() {
+            let fresh = self.request_embeddings(&model, missing.clone()).await?;
+            if fresh.len() != missing.len() {
+                return Err(
+                    format!("Expected {} embeddings, OVMS returned {}", missing.len(), fresh.len())
+                );
+            }
+            embedding_cache::insert_many(&model, &missing, &fresh);
+
+            let mut fresh = fresh.into_iter();
+            for slot in embeddings.iter_mut().filter(|slot| slot.is_none()) {
+                *slot = fresh.next();
+            }
+        }
+
+        Ok(embeddings.into_iter().flatten().collect())
+    }
+
+    async fn request_embeddings(&self, model: &str, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
         let request = CreateEmbeddingRequestArgs::default()
-            .model("bge-base-en-v1.5-int8-ov") // or your local embedding model
+            .model(model)
             .input(texts)
             .build()
             .map_err(|e| format!("Failed to build embedding request: {}", e))?;
@@ -41,6 +130,42 @@ impl EmbeddingService {
         Ok(embeddings)
     }
 
+    /// Embed texts in fixed-size batches, reporting (processed, total) after each batch.
+    /// Fails with CANCELLED as soon as `operation_id` is cancelled, even mid-request
+    pub async fn create_embeddings_in_batches<F>(
+        &self,
+        texts: Vec<String>,
+        operation_id: Option<&str>,
+        mut on_batch: F
+    ) -> Result<Vec<Vec<f32>>, String>
+        where F: FnMut(usize, usize)
+    {
+        let total = texts.len();
+        let mut embeddings = Vec::with_capacity(total);
+
+        for batch in texts.chunks(EMBEDDING_BATCH_SIZE) {
+            let request = self.create_embeddings(batch.to_vec());
+            tokio::pin!(request);
+
+            // A slow OVMS would otherwise keep the batch running until it answers
+            let batch_embeddings = loop {
+                if is_operation_cancelled(operation_id) {
+                    info!(processed = embeddings.len(), total, "Embedding operation cancelled");
+                    return Err(CANCELLED.to_string());
+                }
+                tokio::select! {
+                    result = &mut request => break result?,
+                    _ = tokio::time::sleep(std::time::Duration::from_millis(CANCEL_POLL_MS)) => {}
+                }
+            };
+
+            embeddings.extend(batch_embeddings);
+            on_batch(embeddings.len(), total);
+        }
+
+        Ok(embeddings)
+    }
+
     pub async fn create_single_embedding(&self, text: String) -> Result<Vec<f32>, String> {
         let embeddings = self.create_embeddings(vec![text]).await?;
         embeddings
@@ -50,8 +175,13 @@ impl EmbeddingService {
     }
 }
 
-#[tauri::command]
-pub async fn create_document_embeddings(documents: Vec<Document>) -> Result<Vec<Document>, String> {
+async fn embed_documents_with_progress<F>(
+    documents: Vec<Document>,
+    operation_id: Option<&str>,
+    on_batch: F
+) -> Result<Vec<Document>, String>
+    where F: FnMut(usize, usize)
+{
     if documents.is_empty() {
         return Ok(documents);
     }
@@ -63,7 +193,7 @@ pub async fn create_document_embeddings(documents: Vec<Document>) -> Result<Vec<
         .map(|doc| doc.content.clone())
         .collect();
 
-    let embeddings = embedding_service.create_embeddings(texts).await?;
+    let embeddings = embedding_service.create_embeddings_in_batches(texts, operation_id, on_batch).await?;
 
     let mut updated_docs = documents;
     for (i, embedding) in embeddings.into_iter().enumerate() {
@@ -75,6 +205,105 @@ pub async fn create_document_embeddings(documents: Vec<Document>) -> Result<Vec<
     Ok(updated_docs)
 }
 
+/// Embed documents without emitting progress events, for callers without an app handle
+pub async fn embed_documents(documents: Vec<Document>) -> Result<Vec<Document>, String> {
+    embed_documents_with_progress(documents, None, |_, _| {}).await
+}
+
+/// Embed documents, emitting embedding-progress events. Pass an `operation_id` to be able
+/// to stop it with cancel_embedding_operation
+#[tauri::command]
+pub async fn create_document_embeddings(
+    app: AppHandle,
+    documents: Vec<Document>,
+    operation_id: Option<String>
+) -> Result<EmbeddingOutcome, String> {
+    let total = documents.len();
+    let mut processed = 0;
+
+    start_operation(operation_id.as_deref());
+    let result = embed_documents_with_progress(documents, operation_id.as_deref(), |done, total| {
+        processed = done;
+        let _ = app.emit(
+            "embedding-progress",
+            serde_json::json!({
+                "processed": done,
+                "total": total
+            })
+        );
+    }).await;
+    finish_operation(operation_id.as_deref());
+
+    match result {
+        Ok(documents) => Ok(EmbeddingOutcome::Completed { documents }),
+        Err(e) if e == CANCELLED => Ok(EmbeddingOutcome::Cancelled {
+            processed,
+            total,
+            resumable: crate::settings::current().embedding_cache_max_entries > 0,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+/// Ask a running embedding operation to stop. Returns false if no operation with that id
+/// is running, e.g. because it already finished
+#[tauri::command]
+pub async fn cancel_embedding_operation(operation_id: String) -> Result<bool, String> {
+    let mut running = RUNNING_OPERATIONS.lock().map_err(|e| format!("Lock error: {}", e))?;
+    match running.get_mut(&operation_id) {
+        Some(cancelled) => {
+            info!(operation_id = %operation_id, "Embedding cancel requested");
+            *cancelled = true;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Recompute every stored embedding with the current embedding model. Run this after
+/// switching embedding_model, since vectors from different models can't be compared
+#[tauri::command]
+pub async fn reembed_all_documents(app: AppHandle) -> Result<usize, String> {
+    let vector_store = VectorStore::new()?;
+    let documents = vector_store.list_all_documents()?;
+    let total = documents.len();
+    let embedding_service = EmbeddingService::new();
+
+    let mut processed = 0;
+    let mut dim = None;
+    for batch in documents.chunks(EMBEDDING_BATCH_SIZE) {
+        let texts: Vec<String> = batch
+            .iter()
+            .map(|doc| doc.content.clone())
+            .collect();
+        let embeddings = embedding_service.create_embeddings(texts).await?;
+
+        for (document, embedding) in batch.iter().zip(embeddings) {
+            dim = Some(embedding.len());
+            let mut updated = document.clone();
+            updated.embedding = Some(embedding);
+            vector_store.update_document(&updated)?;
+        }
+
+        processed += batch.len();
+        let _ = app.emit(
+            "reembed-progress",
+            serde_json::json!({
+                "processed": processed,
+                "total": total
+            })
+        );
+    }
+
+    if let Some(dim) = dim {
+        vector_store.set_embedding_dim(dim)?;
+    }
+    vector_store.flush()?;
+
+    info!(documents = total, "Re-embedded all documents");
+    Ok(total)
+}
+
 #[tauri::command]
 pub async fn create_query_embedding(query: String) -> Result<Vec<f32>, String> {
     let embedding_service = EmbeddingService::new();
@@ -1,8 +1,11 @@
 pub mod documents;
 pub mod embeddings; 
+pub mod embedding_cache;
 pub mod vector_store;
 pub mod reranker;
 pub mod search;
+pub mod search_index;
+pub mod watcher;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -27,6 +30,16 @@ pub struct SearchResult {
     pub rerank_score: Option<f32>,
 }
 
+/// Search hits from one source file, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSearchResult {
+    pub file_path: String,
+    pub title: String,
+    pub file_type: String,
+    pub best_score: f32,
+    pub chunks: Vec<SearchResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
     pub file_path: String,
@@ -46,6 +59,43 @@ pub struct FileInfoSummary {
     pub created_at: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionStats {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub reclaimed_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TempCleanupStats {
+    pub files_removed: usize,
+    pub bytes_reclaimed: u64,
+}
+
+/// Stored chunks compared to the max_stored_chunks soft limit, 0 meaning no limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreCapacity {
+    pub chunk_count: usize,
+    pub max_chunks: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreDocumentsResult {
+    pub stored: usize,
+    pub capacity: StoreCapacity,
+    /// Set when the store is now over the soft limit
+    pub warning: Option<String>,
+}
+
+/// How one chunk of a previewed document would be stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkPreview {
+    pub index: usize,
+    pub title: String,
+    pub char_length: usize,
+    pub preview: String,
+}
+
 impl Document {
     pub fn new(
         title: String,
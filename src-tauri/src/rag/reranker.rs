@@ -1,4 +1,59 @@
-use super::SearchResult;
+use super::{Document, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// Optional adjustments folded into the combined rerank score. The defaults leave scores unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RerankBoosts {
+    /// Added to the score of a document created just now, halving every recency_half_life_days
+    pub recency_weight: f32,
+    pub recency_half_life_days: f32,
+    /// Score multiplier per file type, e.g. { "pdf": 1.2 }
+    pub file_type_multipliers: HashMap<String, f32>,
+    /// Score multiplier for documents whose file_path starts with the key
+    pub source_multipliers: HashMap<String, f32>,
+}
+
+impl Default for RerankBoosts {
+    fn default() -> Self {
+        Self {
+            recency_weight: 0.0,
+            recency_half_life_days: 30.0,
+            file_type_multipliers: HashMap::new(),
+            source_multipliers: HashMap::new(),
+        }
+    }
+}
+
+// Apply the recency bonus and the file type and source multipliers to a combined score
+fn apply_boosts(score: f32, document: &Document, boosts: &RerankBoosts, now_ms: i64) -> f32 {
+    let mut boosted = score;
+
+    if boosts.recency_weight != 0.0 && boosts.recency_half_life_days > 0.0 {
+        let age_days = ((now_ms - document.created_at).max(0) as f64) / MS_PER_DAY;
+        let decay = 0.5f64.powf(age_days / (boosts.recency_half_life_days as f64));
+        boosted += boosts.recency_weight * (decay as f32);
+    }
+
+    if let Some(multiplier) = boosts.file_type_multipliers.get(&document.file_type) {
+        boosted *= multiplier;
+    }
+
+    // When several prefixes match, the most specific one wins
+    let source_multiplier = boosts.source_multipliers
+        .iter()
+        .filter(|(prefix, _)| document.file_path.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, multiplier)| *multiplier);
+    if let Some(multiplier) = source_multiplier {
+        boosted *= multiplier;
+    }
+
+    boosted
+}
 
 pub struct RerankerService {}
 
@@ -11,12 +66,15 @@ impl RerankerService {
     pub async fn rerank(
         &self,
         query: &str,
-        results: Vec<SearchResult>
+        results: Vec<SearchResult>,
+        boosts: &RerankBoosts
     ) -> Result<Vec<SearchResult>, String> {
         if results.is_empty() {
             return Ok(results);
         }
 
+        let now_ms = chrono::Utc::now().timestamp_millis();
+
         // For now, implement a hybrid scoring approach
         // You can replace this with actual reranker model calls when available
         let mut reranked_results = results;
@@ -29,7 +87,7 @@ impl RerankerService {
             // Combine scores with weights
             let combined_score = semantic_score * 0.6 + lexical_score * 0.3 + length_penalty * 0.1;
 
-            result.rerank_score = Some(combined_score);
+            result.rerank_score = Some(apply_boosts(combined_score, &result.document, boosts, now_ms));
         }
 
         // Sort by reranked scores
@@ -117,10 +175,11 @@ fn calculate_length_penalty(content: &str) -> f32 {
 #[tauri::command]
 pub async fn rerank_search_results(
     query: String,
-    results: Vec<SearchResult>
+    results: Vec<SearchResult>,
+    boosts: Option<RerankBoosts>
 ) -> Result<Vec<SearchResult>, String> {
     let reranker = RerankerService::new();
-    reranker.rerank(&query, results).await
+    reranker.rerank(&query, results, &boosts.unwrap_or_default()).await
 }
 
 #[tauri::command]
@@ -155,4 +214,29 @@ mod tests {
         );
         assert!(calculate_length_penalty(&ideal_content) > calculate_length_penalty(&long_content));
     }
+
+    #[test]
+    fn test_apply_boosts() {
+        let now = 100 * (MS_PER_DAY as i64);
+        let mut document = Document::new(
+            "report".to_string(),
+            "content".to_string(),
+            "pdf".to_string(),
+            "/docs/reports/q3.pdf".to_string(),
+            None
+        );
+        document.created_at = now - 30 * (MS_PER_DAY as i64);
+
+        // Neutral defaults keep the score
+        assert_eq!(apply_boosts(0.5, &document, &RerankBoosts::default(), now), 0.5);
+
+        let mut boosts = RerankBoosts { recency_weight: 0.2, ..Default::default() };
+        assert!((apply_boosts(0.5, &document, &boosts, now) - 0.6).abs() < 1e-6);
+
+        boosts.recency_weight = 0.0;
+        boosts.file_type_multipliers.insert("pdf".to_string(), 2.0);
+        boosts.source_multipliers.insert("/docs".to_string(), 0.5);
+        boosts.source_multipliers.insert("/docs/reports".to_string(), 1.5);
+        assert!((apply_boosts(0.5, &document, &boosts, now) - 1.5).abs() < 1e-6);
+    }
 }
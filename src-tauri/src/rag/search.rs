@@ -1,8 +1,61 @@
-use super::SearchResult;
+use super::{FileSearchResult, SearchResult};
+use serde::Serialize;
 use crate::rag::embeddings::EmbeddingService;
 use crate::rag::vector_store::VectorStore;
 use crate::rag::reranker::RerankerService;
 
+// Chunks kept per file when results are grouped by file
+const MAX_CHUNKS_PER_FILE: usize = 3;
+
+// Characters of chunk content written as the snippet when exporting results
+const EXPORT_SNIPPET_CHARS: usize = 300;
+
+/// Either individual chunks or, with group_by_file, one entry per source file
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum SearchResponse {
+    Chunks(Vec<SearchResult>),
+    Files(Vec<FileSearchResult>),
+}
+
+// Reranked results are ordered by the reranker, so prefer its score when present
+fn effective_score(result: &SearchResult) -> f32 {
+    result.rerank_score.unwrap_or(result.score)
+}
+
+/// Collapse chunks from the same file into one entry with its best score and top chunks,
+/// ordered by best score
+fn group_results_by_file(results: Vec<SearchResult>, limit: usize) -> Vec<FileSearchResult> {
+    let mut groups: Vec<FileSearchResult> = Vec::new();
+
+    for result in results {
+        let score = effective_score(&result);
+        match groups.iter_mut().find(|group| group.file_path == result.document.file_path) {
+            Some(group) => {
+                group.best_score = group.best_score.max(score);
+                group.chunks.push(result);
+            }
+            None => {
+                groups.push(FileSearchResult {
+                    file_path: result.document.file_path.clone(),
+                    title: result.document.title.clone(),
+                    file_type: result.document.file_type.clone(),
+                    best_score: score,
+                    chunks: vec![result],
+                });
+            }
+        }
+    }
+
+    for group in &mut groups {
+        group.chunks.sort_by(|a, b| effective_score(b).total_cmp(&effective_score(a)));
+        group.chunks.truncate(MAX_CHUNKS_PER_FILE);
+    }
+    groups.sort_by(|a, b| b.best_score.total_cmp(&a.best_score));
+    groups.truncate(limit);
+    groups
+}
+
 pub struct SearchService {
     embedding_service: EmbeddingService,
     vector_store: VectorStore,
@@ -27,7 +80,7 @@ impl SearchService {
         
         // Step 3: Rerank if requested
         let final_results = if use_reranking && !initial_results.is_empty() {
-            let reranked = self.reranker_service.rerank(query, initial_results).await?;
+            let reranked = self.reranker_service.rerank(query, initial_results, &Default::default()).await?;
             reranked.into_iter().take(limit).collect()
         } else {
             initial_results.into_iter().take(limit).collect()
@@ -62,43 +115,210 @@ pub async fn search_documents_by_query(
     query: String, 
     limit: Option<usize>, 
     use_reranking: Option<bool>,
-    file_types: Option<Vec<String>>
-) -> Result<Vec<SearchResult>, String> {
+    file_types: Option<Vec<String>>,
+    group_by_file: Option<bool>
+) -> Result<SearchResponse, String> {
+    crate::ovms::ensure_embedding_model_loaded().await?;
+
     let search_service = SearchService::new()?;
     let search_limit = limit.unwrap_or(10);
     let should_rerank = use_reranking.unwrap_or(true);
+    let group_by_file = group_by_file.unwrap_or(false);
     
-    if let Some(types) = file_types {
-        search_service.search_with_filters(&query, search_limit, Some(types), should_rerank).await
+    // Grouping folds several chunks into one entry, so fetch more to still fill the limit
+    let chunk_limit = if group_by_file { search_limit * MAX_CHUNKS_PER_FILE } else { search_limit };
+    let results = if let Some(types) = file_types {
+        search_service.search_with_filters(&query, chunk_limit, Some(types), should_rerank).await?
     } else {
-        search_service.search(&query, search_limit, should_rerank).await
+        search_service.search(&query, chunk_limit, should_rerank).await?
+    };
+    
+    if group_by_file {
+        Ok(SearchResponse::Files(group_results_by_file(results, search_limit)))
+    } else {
+        Ok(SearchResponse::Chunks(results))
     }
 }
 
+// Chunks whose content suggestions are drawn from
+const SUGGESTION_SOURCE_CHUNKS: usize = 10;
+const MAX_SUGGESTIONS: usize = 5;
+
+// Words too common to make a useful suggestion on their own
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "could", "did", "do", "does", "each", "for", "from", "had", "has",
+    "have", "he", "her", "his", "how", "if", "in", "into", "is", "it", "its", "may", "more",
+    "most", "not", "of", "on", "one", "or", "other", "our", "out", "she", "should", "so",
+    "some", "such", "than", "that", "the", "their", "them", "then", "there", "these", "they",
+    "this", "those", "to", "up", "use", "used", "was", "we", "were", "what", "when", "where",
+    "which", "while", "who", "will", "with", "would", "you", "your",
+];
+
+fn is_meaningful_word(word: &str) -> bool {
+    word.chars().count() >= 3
+        && !word.chars().all(|c| c.is_ascii_digit())
+        && !STOPWORDS.contains(&word)
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .map(|word| word.trim_matches('-').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Query completions built from the words and two-word phrases that occur most often in
+/// `contents`. A partly typed last word is completed first, otherwise phrases are appended
+fn suggest_completions(query: &str, contents: &[&str], limit: usize) -> Vec<String> {
+    let query_words = tokenize(query);
+    let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for content in contents {
+        let words = tokenize(content);
+        for word in words.iter().filter(|word| is_meaningful_word(word)) {
+            *counts.entry(word.clone()).or_default() += 1;
+        }
+        // Phrases count double, they make more specific suggestions than single words
+        for pair in words.windows(2) {
+            if is_meaningful_word(&pair[0]) && is_meaningful_word(&pair[1]) {
+                *counts.entry(format!("{} {}", pair[0], pair[1])).or_default() += 2;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().filter(|(_, count)| *count > 1).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // The last word is still being typed unless it's followed by a space or is a known word
+    let last_word = query_words.last().filter(|_| !query.ends_with(char::is_whitespace));
+    let completing = last_word.is_some_and(|word| !ranked.iter().any(|(phrase, _)| phrase == word));
+    let leading_words = query_words[..query_words.len().saturating_sub(1)].join(" ");
+    let full_query = query_words.join(" ");
+
+    let join = |base: &str, phrase: &str| {
+        if base.is_empty() { phrase.to_string() } else { format!("{} {}", base, phrase) }
+    };
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for (phrase, _) in &ranked {
+        let suggestion = match last_word {
+            // "vec" -> "vector search", "vector" -> "vector index"
+            Some(word) if phrase.starts_with(word.as_str()) && phrase != word => join(&leading_words, phrase),
+            // "vector" -> "vector cosine similarity"
+            _ if !completing && !phrase.split(' ').all(|word| query_words.iter().any(|query_word| query_word == word)) => {
+                join(&full_query, phrase)
+            }
+            _ => {
+                continue;
+            }
+        };
+
+        if !suggestions.contains(&suggestion) {
+            suggestions.push(suggestion);
+        }
+        if suggestions.len() == limit {
+            break;
+        }
+    }
+
+    suggestions
+}
+
 #[tauri::command]
 pub async fn get_search_suggestions(query: String) -> Result<Vec<String>, String> {
-    // Simple implementation - you can enhance this with more sophisticated suggestion logic
     let search_service = SearchService::new()?;
-    let results = search_service.search(&query, 5, false).await?;
-    
-    let suggestions: Vec<String> = results.into_iter()
-        .map(|result| {
-            // Extract key phrases from document titles or content
-            let title_words: Vec<&str> = result.document.title.split_whitespace().collect();
-            if title_words.len() > 2 {
-                title_words[0..2].join(" ")
-            } else {
-                result.document.title
-            }
-        })
-        .collect();
-    
-    Ok(suggestions)
+    let results = search_service.search(&query, SUGGESTION_SOURCE_CHUNKS, false).await?;
+
+    let contents: Vec<&str> = results.iter().map(|result| result.document.content.as_str()).collect();
+    Ok(suggest_completions(&query, &contents, MAX_SUGGESTIONS))
+}
+
+/// One exported search hit, the same columns are used for CSV and JSON
+#[derive(Debug, Serialize)]
+struct ExportedResult {
+    document_id: String,
+    title: String,
+    file_path: String,
+    score: f32,
+    rerank_score: Option<f32>,
+    snippet: String,
+}
+
+impl From<&SearchResult> for ExportedResult {
+    fn from(result: &SearchResult) -> Self {
+        // Line breaks are collapsed so every hit stays on one row in spreadsheet tools
+        let snippet: String = result.document.content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(EXPORT_SNIPPET_CHARS)
+            .collect();
+
+        Self {
+            document_id: result.document.id.clone(),
+            title: result.document.title.clone(),
+            file_path: result.document.file_path.clone(),
+            score: result.score,
+            rerank_score: result.rerank_score,
+            snippet,
+        }
+    }
+}
+
+// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn results_to_csv(results: &[ExportedResult]) -> String {
+    let mut csv = String::from("document_id,title,file_path,score,rerank_score,snippet\n");
+    for result in results {
+        let row = [
+            csv_field(&result.document_id),
+            csv_field(&result.title),
+            csv_field(&result.file_path),
+            result.score.to_string(),
+            result.rerank_score.map(|score| score.to_string()).unwrap_or_default(),
+            csv_field(&result.snippet),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Write search hits to `dest` as "csv" or "json", returning the number of rows written
+#[tauri::command]
+pub async fn export_search_results(
+    results: Vec<SearchResult>,
+    format: String,
+    dest: String
+) -> Result<usize, String> {
+    let exported: Vec<ExportedResult> = results.iter().map(ExportedResult::from).collect();
+
+    let contents = match format.to_lowercase().as_str() {
+        "csv" => results_to_csv(&exported),
+        "json" => serde_json::to_string_pretty(&exported)
+            .map_err(|e| format!("Failed to serialize search results: {}", e))?,
+        other => return Err(format!("Unsupported export format: {}. Use csv or json.", other)),
+    };
+
+    std::fs::write(&dest, contents)
+        .map_err(|e| format!("Failed to write {}: {}", dest, e))?;
+
+    Ok(exported.len())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rag::Document;
 
     #[tokio::test]
     async fn test_search_service_creation() {
@@ -112,4 +332,64 @@ mod tests {
             Err(_) => assert!(true), // Expected in test environment
         }
     }
+
+    fn result(file_path: &str, score: f32) -> SearchResult {
+        let mut document = Document::new(
+            file_path.to_string(),
+            format!("chunk scored {}", score),
+            "pdf".to_string(),
+            file_path.to_string(),
+            None,
+        );
+        document.id = format!("{}-{}", file_path, score);
+        SearchResult { document, score, rerank_score: None }
+    }
+
+    #[test]
+    fn test_suggest_completions_uses_frequent_phrases() {
+        let contents = [
+            "Vector search uses embeddings. The vector index is rebuilt on startup.",
+            "Vector search ranks chunks by cosine similarity, and the vector index is cached.",
+        ];
+
+        let suggestions = suggest_completions("vec", &contents, 5);
+        assert_eq!(suggestions, vec!["vector", "vector index", "vector search"]);
+
+        let suggestions = suggest_completions("vector", &contents, 5);
+        assert!(suggestions.iter().all(|suggestion| suggestion.starts_with("vector")));
+        assert!(suggestions.iter().any(|suggestion| suggestion.split(' ').count() > 1));
+    }
+
+    #[test]
+    fn test_results_to_csv_quotes_fields() {
+        let mut hit = result("notes, draft.txt", 0.5);
+        hit.document.content = "He said \"hi\"\nthen left".to_string();
+        hit.rerank_score = Some(0.75);
+
+        let csv = results_to_csv(&[ExportedResult::from(&hit)]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.contains("\"notes, draft.txt\""));
+        assert!(row.ends_with(",0.5,0.75,\"He said \"\"hi\"\" then left\""));
+    }
+
+    #[test]
+    fn test_group_results_by_file() {
+        let results = vec![
+            result("a.pdf", 0.5),
+            result("b.pdf", 0.9),
+            result("a.pdf", 0.7),
+            result("a.pdf", 0.2),
+            result("a.pdf", 0.6),
+            result("c.pdf", 0.1),
+        ];
+
+        let groups = group_results_by_file(results, 2);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].file_path, "b.pdf");
+        assert_eq!(groups[1].file_path, "a.pdf");
+        assert_eq!(groups[1].best_score, 0.7);
+        let scores: Vec<f32> = groups[1].chunks.iter().map(|chunk| chunk.score).collect();
+        assert_eq!(scores, vec![0.7, 0.6, 0.5]);
+    }
 }
\ No newline at end of file
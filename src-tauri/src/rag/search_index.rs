@@ -0,0 +1,131 @@
+use super::Document;
+use super::vector_store::{cosine_similarity, dot_product, normalize_embedding};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+// Shared by every VectorStore handle, cleared whenever documents change in this process
+static SEARCH_INDEX: Mutex<Option<Arc<SearchIndex>>> = Mutex::new(None);
+
+struct IndexEntry {
+    id: String,
+    embedding: Vec<f32>,
+    normalized: bool,
+}
+
+/// In-memory copy of every stored embedding, so searches don't have to deserialize
+/// each document from sled. Only the best matches are loaded from the store afterwards
+pub struct SearchIndex {
+    entries: Vec<IndexEntry>,
+    document_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexRebuildStats {
+    pub document_count: usize,
+    pub indexed_embeddings: usize,
+    pub duration_ms: u64,
+}
+
+impl SearchIndex {
+    /// Index the embeddings of `documents`. `document_count` is the number of document keys
+    /// in the store at build time, compared at search time to detect a stale index
+    pub fn from_documents(documents: Vec<Document>, document_count: usize) -> Self {
+        let entries = documents
+            .into_iter()
+            .filter_map(|document| {
+                let mut embedding = document.embedding?;
+                let normalized = normalize_embedding(&mut embedding);
+                Some(IndexEntry { id: document.id, embedding, normalized })
+            })
+            .collect();
+
+        Self { entries, document_count }
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.document_count
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Ids and similarity scores of the `limit` entries closest to the query, best first
+    pub fn top_matches(&self, query_embedding: &[f32], limit: usize) -> Vec<(String, f32)> {
+        let mut normalized_query = query_embedding.to_vec();
+        let query_is_normalized = normalize_embedding(&mut normalized_query);
+
+        let mut matches: Vec<(String, f32)> = self.entries
+            .iter()
+            .map(|entry| {
+                let similarity = if query_is_normalized && entry.normalized && entry.embedding.len() == normalized_query.len() {
+                    dot_product(&normalized_query, &entry.embedding)
+                } else {
+                    cosine_similarity(query_embedding, &entry.embedding)
+                };
+                (entry.id.clone(), similarity)
+            })
+            .filter(|(_, similarity)| similarity.is_finite())
+            .collect();
+
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// The current index, if one was built and nothing changed since
+pub fn current() -> Option<Arc<SearchIndex>> {
+    SEARCH_INDEX.lock().ok().and_then(|index| index.clone())
+}
+
+pub fn install(index: Arc<SearchIndex>) {
+    if let Ok(mut current) = SEARCH_INDEX.lock() {
+        *current = Some(index);
+    }
+}
+
+/// Drop the index so the next search rebuilds it
+pub fn invalidate() {
+    if let Ok(mut current) = SEARCH_INDEX.lock() {
+        *current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: &str, embedding: Option<Vec<f32>>) -> Document {
+        let mut document = Document::new(
+            id.to_string(),
+            id.to_string(),
+            "txt".to_string(),
+            format!("{}.txt", id),
+            None,
+        );
+        document.id = id.to_string();
+        document.embedding = embedding;
+        document
+    }
+
+    #[test]
+    fn test_top_matches_orders_by_similarity() {
+        let index = SearchIndex::from_documents(
+            vec![
+                document("x", Some(vec![2.0, 0.0])),
+                document("diagonal", Some(vec![1.0, 1.0])),
+                document("y", Some(vec![0.0, 3.0])),
+                document("no-embedding", None),
+            ],
+            4
+        );
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.document_count(), 4);
+
+        let matches = index.top_matches(&[1.0, 0.1], 2);
+        let ids: Vec<&str> = matches.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["x", "diagonal"]);
+    }
+}
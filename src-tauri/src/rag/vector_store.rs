@@ -1,27 +1,93 @@
-use super::{Document, SearchResult, FileInfo, FileInfoSummary};
+use super::{Document, SearchResult, FileInfo, FileInfoSummary, CompactionStats, StoreCapacity, StoreDocumentsResult};
+use super::search_index::{self, IndexRebuildStats, SearchIndex};
 use sled::Db;
-use nalgebra::DVector;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::Deserialize;
+use tracing::{info, warn};
 
 // Database schema version for future migrations
 const DB_SCHEMA_VERSION: &str = "v1.0.0";
 
+// Metadata flag set on documents whose embedding was L2-normalized before storing
+const NORMALIZED_METADATA_KEY: &str = "__normalized__";
+
+// Dimension of the stored embeddings, rewritten whenever documents are stored or re-embedded
+const EMBEDDING_DIM_KEY: &str = "__embedding_dim__";
+
+// How far in the future a created_at timestamp may be before it is treated as bogus
+const MAX_FUTURE_SKEW_MS: i64 = 86400000;
+
+// sled allows one open handle per path, so every VectorStore shares this one. It is a slot
+// rather than a OnceLock because compaction has to close the database to swap its files
+static STORE_DB: Mutex<Option<Db>> = Mutex::new(None);
+
 pub struct VectorStore {
     db: Db,
 }
 
+/// Result of checking an opened database against the current schema
+enum SchemaCheck {
+    Current,
+    Outdated,
+    Incompatible,
+}
+
+/// Document layout written before chunk_index and metadata were added
+#[derive(Deserialize)]
+struct LegacyDocument {
+    id: String,
+    title: String,
+    content: String,
+    file_type: String,
+    file_path: String,
+    embedding: Option<Vec<f32>>,
+    created_at: i64,
+}
+
+impl From<LegacyDocument> for Document {
+    fn from(legacy: LegacyDocument) -> Self {
+        Self {
+            id: legacy.id,
+            title: legacy.title,
+            content: legacy.content,
+            file_type: legacy.file_type,
+            file_path: legacy.file_path,
+            chunk_index: None,
+            metadata: std::collections::HashMap::new(),
+            embedding: legacy.embedding,
+            created_at: legacy.created_at,
+        }
+    }
+}
+
+/// Decode a stored document with the current schema, then any older layout we know about
+fn decode_document_any_version(bytes: &[u8]) -> Option<Document> {
+    if let Ok(document) = bincode::deserialize::<Document>(bytes) {
+        return Some(document);
+    }
+    bincode::deserialize::<LegacyDocument>(bytes).ok().map(Document::from)
+}
+
 impl VectorStore {
+    fn data_dir() -> Result<PathBuf, String> {
+        Ok(crate::paths::sparrow_data_dir()?.join("vector_store"))
+    }
+
+    /// Handle to the shared database, opened on first use
     pub fn new() -> Result<Self, String> {
-        // Get user profile directory
-        let home_dir = match std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
-            Ok(home) => std::path::PathBuf::from(home),
-            Err(_) => {
-                return Err("Failed to get user home directory".to_string());
-            }
-        };
+        let mut slot = STORE_DB.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(db) = slot.as_ref() {
+            return Ok(Self { db: db.clone() });
+        }
+
+        let db = Self::open_database()?;
+        *slot = Some(db.clone());
+        Ok(Self { db })
+    }
 
-        let mut data_dir = home_dir;
-        data_dir.push(".sparrow");
-        data_dir.push("vector_store");
+    fn open_database() -> Result<Db, String> {
+        let data_dir = Self::data_dir()?;
         
         // Create data directory if it doesn't exist
         if let Some(parent) = data_dir.parent() {
@@ -29,70 +95,103 @@ impl VectorStore {
                 .map_err(|e| format!("Failed to create data directory: {}", e))?;
         }
         
-        // Try to open the database, with fallback for corruption or schema mismatch
-        let db = match sled::open(&data_dir) {
-            Ok(db) => {
-                // Check if we can deserialize existing data
-                if Self::validate_database_schema(&db) {
-                    db
-                } else {
-                    // Schema mismatch - remove old database and create new one
-                    drop(db); // Close the database first
-                    
-                    if data_dir.exists() {
-                        if let Err(remove_err) = std::fs::remove_dir_all(&data_dir) {
-                            return Err(format!("Failed to remove incompatible database: {}", remove_err));
-                        }
+        // An open error may just mean the files are locked, so it never triggers a rebuild.
+        // Only a schema we can't read or migrate sets the old database aside
+        let db = sled::open(&data_dir)
+            .map_err(|e| format!("Failed to open vector store: {}", e))?;
+        let db = match Self::validate_database_schema(&db) {
+            SchemaCheck::Current => db,
+            SchemaCheck::Outdated => {
+                match Self::migrate_documents(&db) {
+                    Ok(migrated) => {
+                        info!(migrated = migrated, version = DB_SCHEMA_VERSION, "Migrated vector store schema");
+                        db
                     }
-                    
-                    // Create parent directory again
-                    if let Some(parent) = data_dir.parent() {
-                        std::fs::create_dir_all(parent)
-                            .map_err(|e| format!("Failed to recreate data directory: {}", e))?;
+                    Err(e) => {
+                        warn!(error = %e, "Vector store migration failed, rebuilding database");
+                        drop(db); // Close the database first
+                        Self::rebuild_database(&data_dir)?
                     }
-                    
-                    sled::open(&data_dir)
-                        .map_err(|e| format!("Failed to create new database after schema migration: {}", e))?
                 }
             }
-            Err(_) => {
-                // If the database is corrupted, try to remove it and create a new one
-                if data_dir.exists() {
-                    if let Err(remove_err) = std::fs::remove_dir_all(&data_dir) {
-                        return Err(format!("Failed to remove corrupted database: {}", remove_err));
-                    }
-                }
-                
-                // Create parent directory again
-                if let Some(parent) = data_dir.parent() {
-                    std::fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to recreate data directory: {}", e))?;
-                }
-                
-                // Try to open a fresh database
-                sled::open(&data_dir)
-                    .map_err(|e| format!("Failed to create new vector store after corruption recovery: {}", e))?
+            SchemaCheck::Incompatible => {
+                drop(db); // Close the database first
+                Self::rebuild_database(&data_dir)?
             }
         };
         
         // Store schema version for future migrations
         let _ = db.insert("__schema_version__", DB_SCHEMA_VERSION.as_bytes());
         
-        Ok(Self { db })
+        Ok(db)
+    }
+    
+    /// Move the existing database to a timestamped backup directory and open a fresh one
+    fn rebuild_database(data_dir: &Path) -> Result<Db, String> {
+        search_index::invalidate();
+        if data_dir.exists() {
+            let backup_dir = data_dir.with_file_name(
+                format!("vector_store.backup-{}", chrono::Utc::now().format("%Y%m%d%H%M%S"))
+            );
+            std::fs::rename(data_dir, &backup_dir)
+                .map_err(|e| format!("Failed to back up incompatible database: {}", e))?;
+            warn!(backup = %backup_dir.display(), "Backed up incompatible vector store");
+        }
+        
+        // Create parent directory again
+        if let Some(parent) = data_dir.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to recreate data directory: {}", e))?;
+        }
+        
+        sled::open(data_dir)
+            .map_err(|e| format!("Failed to create new vector store: {}", e))
+    }
+    
+    /// Re-serialize every document under the current schema, falling back to older layouts.
+    /// Nothing is written unless every document could be converted.
+    fn migrate_documents(db: &Db) -> Result<usize, String> {
+        let mut batch = sled::Batch::default();
+        let mut migrated = 0;
+        
+        for item_result in db.iter() {
+            let (key, value) = item_result
+                .map_err(|e| format!("Database iteration error: {}", e))?;
+            
+            // Skip metadata keys
+            if key.starts_with(b"__") {
+                continue;
+            }
+            
+            let document = decode_document_any_version(&value)
+                .ok_or_else(|| format!("Unrecognized document format for key {}", String::from_utf8_lossy(&key)))?;
+            let upgraded = bincode::serialize(&document)
+                .map_err(|e| format!("Failed to serialize migrated document: {}", e))?;
+            batch.insert(key, upgraded);
+            migrated += 1;
+        }
+        
+        batch.insert("__schema_version__", DB_SCHEMA_VERSION.as_bytes());
+        db.apply_batch(batch)
+            .map_err(|e| format!("Failed to apply migration: {}", e))?;
+        db.flush()
+            .map_err(|e| format!("Failed to flush migrated database: {}", e))?;
+        
+        Ok(migrated)
     }
     
     /// Validate that existing database entries can be deserialized with current Document schema
-    fn validate_database_schema(db: &Db) -> bool {
+    fn validate_database_schema(db: &Db) -> SchemaCheck {
         // Check schema version first
         if let Ok(Some(version_bytes)) = db.get("__schema_version__") {
             if let Ok(version_str) = std::str::from_utf8(&version_bytes) {
                 if version_str != DB_SCHEMA_VERSION {
-                    return false;
+                    return SchemaCheck::Outdated;
                 }
             }
         } else {
             // No version found - this means old database format
-            return false;
+            return SchemaCheck::Outdated;
         }
         
         let mut tested_count = 0;
@@ -112,46 +211,185 @@ impl VectorStore {
                     
                     // Try to deserialize with current Document schema
                     match bincode::deserialize::<Document>(&value) {
-                        Ok(doc) => {
-                            // Additional validation - check if fields make sense
+                        Ok(mut doc) => {
+                            // Odd field values don't mean the schema changed, so never rebuild for them
                             if doc.id.is_empty() || doc.content.is_empty() {
-                                return false;
+                                warn!(key = %String::from_utf8_lossy(&key), "Skipping document with empty id or content");
+                                continue;
                             }
-                            // Check if created_at is reasonable (not negative, not too far in future)
+                            // A clock that was wrong during ingest only affects this document, fix it in place
                             let now = chrono::Utc::now().timestamp_millis();
-                            if doc.created_at < 0 || doc.created_at > now + 86400000 { // Allow 1 day in future
-                                return false;
+                            if repair_created_at(&mut doc, now) {
+                                warn!(document_id = %doc.id, "Repaired out-of-range created_at timestamp");
+                                if let Ok(repaired) = bincode::serialize(&doc) {
+                                    let _ = db.insert(&key, repaired);
+                                }
                             }
                         }
                         Err(_) => {
-                            return false;
+                            return SchemaCheck::Incompatible;
                         }
                     }
                     tested_count += 1;
                 }
                 Err(_) => {
-                    return false;
+                    return SchemaCheck::Incompatible;
                 }
             }
         }
         
-        true
+        SchemaCheck::Current
     }
     
-    pub fn store_document(&self, document: &Document) -> Result<(), String> {
-        let key = document.id.as_bytes();
-        let value = bincode::serialize(document)
-            .map_err(|e| format!("Failed to serialize document: {}", e))?;
+    /// Store all documents in one atomic batch and flush so they survive a crash
+    pub fn store_documents_batch(&self, documents: &[Document]) -> Result<usize, String> {
+        for dim in documents.iter().filter_map(|doc| doc.embedding.as_ref().map(|e| e.len())) {
+            self.check_embedding_dim(dim)?;
+        }
         
-        self.db.insert(key, value)
-            .map_err(|e| format!("Failed to store document: {}", e))?;
+        let mut batch = sled::Batch::default();
+        for document in documents {
+            let value = serialize_for_storage(document)?;
+            batch.insert(document.id.as_bytes(), value);
+        }
+        if let Some(dim) = documents.iter().find_map(|doc| doc.embedding.as_ref().map(|e| e.len())) {
+            batch.insert(EMBEDDING_DIM_KEY, (dim as u64).to_le_bytes().to_vec());
+        }
         
+        self.db.apply_batch(batch)
+            .map_err(|e| format!("Failed to store documents: {}", e))?;
+        search_index::invalidate();
+        self.flush()?;
+        
+        Ok(documents.len())
+    }
+    
+    /// Replace a stored document. Its embedding is treated as new and normalized again,
+    /// so this is safe to call after re-embedding with a different model
+    pub fn update_document(&self, document: &Document) -> Result<(), String> {
+        if !self.db.contains_key(document.id.as_bytes()).map_err(|e| format!("Database error: {}", e))? {
+            return Err(format!("Document not found: {}", document.id));
+        }
+
+        let mut updated = document.clone();
+        updated.metadata.remove(NORMALIZED_METADATA_KEY);
+        let value = serialize_for_storage(&updated)?;
+
+        self.db.insert(document.id.as_bytes(), value)
+            .map_err(|e| format!("Failed to update document: {}", e))?;
+        search_index::invalidate();
         Ok(())
     }
     
+    /// Dimension of the stored embeddings, None if nothing was stored since it was tracked
+    pub fn embedding_dim(&self) -> Option<usize> {
+        let bytes = self.db.get(EMBEDDING_DIM_KEY).ok()??;
+        let bytes: [u8; 8] = bytes.as_ref().try_into().ok()?;
+        Some(u64::from_le_bytes(bytes) as usize)
+    }
+    
+    // Embeddings from a different model can't be compared with the stored ones
+    fn check_embedding_dim(&self, dim: usize) -> Result<(), String> {
+        if self.document_key_count() == 0 {
+            return Ok(());
+        }
+        match dimension_mismatch(self.embedding_dim(), dim) {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    }
+    
+    pub fn set_embedding_dim(&self, dim: usize) -> Result<(), String> {
+        self.db.insert(EMBEDDING_DIM_KEY, (dim as u64).to_le_bytes().to_vec())
+            .map_err(|e| format!("Failed to store embedding dimension: {}", e))?;
+        Ok(())
+    }
+    
+    /// Number of document keys in the store, without deserializing anything. Used to notice
+    /// when the search index no longer matches the store
+    fn document_key_count(&self) -> usize {
+        let metadata_keys = self.db.scan_prefix("__").count();
+        self.db.len().saturating_sub(metadata_keys)
+    }
+    
+    pub fn capacity(&self) -> StoreCapacity {
+        StoreCapacity {
+            chunk_count: self.document_key_count(),
+            max_chunks: crate::settings::current().max_stored_chunks,
+        }
+    }
+    
+    fn build_search_index(&self) -> Result<(Arc<SearchIndex>, IndexRebuildStats), String> {
+        let started = std::time::Instant::now();
+        let document_count = self.document_key_count();
+        let index = Arc::new(SearchIndex::from_documents(self.list_all_documents()?, document_count));
+        search_index::install(index.clone());
+        
+        let stats = IndexRebuildStats {
+            document_count,
+            indexed_embeddings: index.len(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        info!(
+            documents = stats.document_count,
+            embeddings = stats.indexed_embeddings,
+            duration_ms = stats.duration_ms,
+            "Rebuilt search index"
+        );
+        Ok((index, stats))
+    }
+    
+    /// Discard the in-memory search index and rebuild it from every stored document
+    pub fn rebuild_search_index(&self) -> Result<IndexRebuildStats, String> {
+        search_index::invalidate();
+        self.build_search_index().map(|(_, stats)| stats)
+    }
     
     pub fn search_similar(&self, query_embedding: &[f32], limit: usize) -> Result<Vec<SearchResult>, String> {
+        self.check_embedding_dim(query_embedding.len())?;
+        
+        // Rebuild when the store was changed behind the index's back, e.g. by another process
+        let index = match search_index::current() {
+            Some(index) if index.document_count() == self.document_key_count() => index,
+            stale => {
+                if stale.is_some() {
+                    info!("Search index is out of date with the vector store, rebuilding");
+                }
+                self.build_search_index()?.0
+            }
+        };
+        
         let mut results = Vec::new();
+        for (id, similarity) in index.top_matches(query_embedding, limit) {
+            let document = self.db.get(id.as_bytes())
+                .ok()
+                .flatten()
+                .and_then(|value| bincode::deserialize::<Document>(&value).ok());
+            match document {
+                Some(document) => results.push(SearchResult {
+                    document,
+                    score: similarity,
+                    rerank_score: None,
+                }),
+                None => {
+                    // Removed since the index was built, the next search rebuilds it
+                    search_index::invalidate();
+                }
+            }
+        }
+        
+        Ok(results)
+    }
+    
+    pub fn get_document(&self, id: &str) -> Result<Option<Document>, String> {
+        let value = self.db.get(id.as_bytes())
+            .map_err(|e| format!("Failed to read document: {}", e))?;
+        Ok(value.and_then(|value| bincode::deserialize::<Document>(&value).ok()))
+    }
+    
+    /// Every chunk stored for `file_path`, sorted by chunk index
+    pub fn file_chunks(&self, file_path: &str) -> Vec<Document> {
+        let mut chunks = Vec::new();
         
         for item_result in self.db.iter() {
             match item_result {
@@ -163,16 +401,8 @@ impl VectorStore {
                     
                     match bincode::deserialize::<Document>(&value) {
                         Ok(document) => {
-                            if let Some(embedding) = &document.embedding {
-                                let similarity = cosine_similarity(query_embedding, embedding);
-                                // Only add if similarity is valid (not NaN)
-                                if similarity.is_finite() {
-                                    results.push(SearchResult {
-                                        document,
-                                        score: similarity,
-                                        rerank_score: None,
-                                    });
-                                }
+                            if document.file_path == file_path {
+                                chunks.push(document);
                             }
                         }
                         Err(_) => {
@@ -188,24 +418,24 @@ impl VectorStore {
             }
         }
         
-        // Sort by similarity score (highest first) with safe comparison
-        results.sort_by(|a, b| {
-            match (a.score.is_finite(), b.score.is_finite()) {
-                (true, true) => b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal),
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                (false, false) => std::cmp::Ordering::Equal,
+        // Sort by chunk index
+        chunks.sort_by(|a, b| {
+            match (a.chunk_index, b.chunk_index) {
+                (Some(a_idx), Some(b_idx)) => a_idx.cmp(&b_idx),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.created_at.cmp(&b.created_at),
             }
         });
-        results.truncate(limit);
         
-        Ok(results)
+        chunks
     }
     
     pub fn delete_document(&self, id: &str) -> Result<bool, String> {
         let key = id.as_bytes();
         let result = self.db.remove(key)
             .map_err(|e| format!("Failed to delete document: {}", e))?;
+        search_index::invalidate();
         
         Ok(result.is_some())
     }
@@ -279,9 +509,88 @@ impl VectorStore {
         Ok(count)
     }
     
+    /// Number of stored chunks per file_type
+    pub fn file_type_counts(&self) -> std::collections::HashMap<String, usize> {
+        let mut counts = std::collections::HashMap::new();
+        
+        for item_result in self.db.iter() {
+            match item_result {
+                Ok((key, value)) => {
+                    // Skip metadata keys
+                    if key.starts_with(b"__") {
+                        continue;
+                    }
+                    
+                    if let Ok(document) = bincode::deserialize::<Document>(&value) {
+                        *counts.entry(document.file_type).or_insert(0) += 1;
+                    }
+                }
+                Err(_) => {
+                    // Skip corrupted entries
+                    continue;
+                }
+            }
+        }
+        
+        counts
+    }
+    
+    /// Write any buffered changes to disk, returning the number of bytes flushed
+    pub fn flush(&self) -> Result<usize, String> {
+        self.db.flush()
+            .map_err(|e| format!("Failed to flush database: {}", e))
+    }
+    
+    /// Rewrite the database into a fresh directory so space from deleted documents is reclaimed.
+    /// Consumes the store since the underlying files are replaced.
+    pub fn compact(self) -> Result<CompactionStats, String> {
+        // Hold the slot for the whole swap so no new handle opens the files being replaced
+        let mut slot = STORE_DB.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let data_dir = Self::data_dir()?;
+        self.flush()?;
+        let size_before = crate::paths::dir_size(&data_dir);
+        
+        let compact_dir = data_dir.with_file_name("vector_store.compact");
+        let old_dir = data_dir.with_file_name("vector_store.old");
+        for leftover in [&compact_dir, &old_dir] {
+            if leftover.exists() {
+                std::fs::remove_dir_all(leftover)
+                    .map_err(|e| format!("Failed to remove leftover compaction directory: {}", e))?;
+            }
+        }
+        
+        {
+            let compacted = sled::open(&compact_dir)
+                .map_err(|e| format!("Failed to create compacted database: {}", e))?;
+            compacted.import(self.db.export());
+            compacted.flush()
+                .map_err(|e| format!("Failed to flush compacted database: {}", e))?;
+        }
+        slot.take();
+        drop(self);
+        
+        // Keep the original around until the compacted copy is in place
+        std::fs::rename(&data_dir, &old_dir)
+            .map_err(|e| format!("Failed to move original database aside: {}", e))?;
+        if let Err(e) = std::fs::rename(&compact_dir, &data_dir) {
+            let _ = std::fs::rename(&old_dir, &data_dir);
+            return Err(format!("Failed to replace database with compacted copy: {}", e));
+        }
+        std::fs::remove_dir_all(&old_dir)
+            .map_err(|e| format!("Failed to remove original database: {}", e))?;
+        
+        let size_after = crate::paths::dir_size(&data_dir);
+        Ok(CompactionStats {
+            size_before,
+            size_after,
+            reclaimed_bytes: size_before.saturating_sub(size_after),
+        })
+    }
+    
     pub fn clear_all(&self) -> Result<(), String> {
         self.db.clear()
             .map_err(|e| format!("Failed to clear database: {}", e))?;
+        search_index::invalidate();
         Ok(())
     }
     
@@ -362,9 +671,53 @@ impl VectorStore {
         Ok(files)
     }
     
+    /// Swap the stored chunks of a file for `documents` in one atomic batch, so a failed write
+    /// leaves the old chunks in place
+    pub fn replace_file(&self, file_path: &str, documents: &[Document]) -> Result<usize, String> {
+        for dim in documents.iter().filter_map(|doc| doc.embedding.as_ref().map(|e| e.len())) {
+            self.check_embedding_dim(dim)?;
+        }
+        
+        let mut batch = sled::Batch::default();
+        for key in self.file_keys(file_path) {
+            batch.remove(key);
+        }
+        // Inserts come after the removes so chunks that keep their id survive the batch
+        for document in documents {
+            let value = serialize_for_storage(document)?;
+            batch.insert(document.id.as_bytes(), value);
+        }
+        if let Some(dim) = documents.iter().find_map(|doc| doc.embedding.as_ref().map(|e| e.len())) {
+            batch.insert(EMBEDDING_DIM_KEY, (dim as u64).to_le_bytes().to_vec());
+        }
+        
+        self.db.apply_batch(batch)
+            .map_err(|e| format!("Failed to replace documents of {}: {}", file_path, e))?;
+        search_index::invalidate();
+        self.flush()?;
+        
+        Ok(documents.len())
+    }
+    
     pub fn delete_file(&self, file_path: &str) -> Result<usize, String> {
         let mut deleted_count = 0;
-        let mut keys_to_delete = Vec::new();
+        
+        // Delete all found keys
+        for key in self.file_keys(file_path) {
+            if let Ok(Some(_)) = self.db.remove(&key) {
+                deleted_count += 1;
+            }
+        }
+        if deleted_count > 0 {
+            search_index::invalidate();
+        }
+        
+        Ok(deleted_count)
+    }
+    
+    // Keys of every stored chunk of the file
+    fn file_keys(&self, file_path: &str) -> Vec<Vec<u8>> {
+        let mut keys = Vec::new();
         
         // Find all documents for this file
         for item_result in self.db.iter() {
@@ -378,7 +731,7 @@ impl VectorStore {
                     match bincode::deserialize::<Document>(&value) {
                         Ok(document) => {
                             if document.file_path == file_path {
-                                keys_to_delete.push(key.to_vec());
+                                keys.push(key.to_vec());
                             }
                         }
                         Err(_) => {
@@ -394,28 +747,105 @@ impl VectorStore {
             }
         }
         
-        // Delete all found keys
-        for key in keys_to_delete {
-            if let Ok(Some(_)) = self.db.remove(&key) {
-                deleted_count += 1;
-            }
+        keys
+    }
+}
+
+// Why an embedding of `dim` values can't be used with a store holding `stored`-dimensional
+// ones, None when they match or the stored dimension isn't known
+fn dimension_mismatch(stored: Option<usize>, dim: usize) -> Option<String> {
+    match stored {
+        Some(stored) if stored != dim => Some(format!(
+            "Embedding has {} dimensions but the stored documents have {}. The embedding model changed; re-embed all documents first",
+            dim, stored
+        )),
+        _ => None,
+    }
+}
+
+// Keep the chunks whose index is within `window` of `center`
+fn chunks_within_window(chunks: Vec<Document>, center: usize, window: usize) -> Vec<Document> {
+    chunks
+        .into_iter()
+        .filter(|chunk| {
+            chunk.chunk_index
+                .map(|index| index.abs_diff(center) <= window)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Clamp a created_at that is negative or too far in the future to `now`.
+/// Returns whether the document was changed.
+fn repair_created_at(doc: &mut Document, now: i64) -> bool {
+    if doc.created_at < 0 || doc.created_at > now + MAX_FUTURE_SKEW_MS {
+        doc.created_at = now;
+        true
+    } else {
+        false
+    }
+}
+
+fn is_normalized(document: &Document) -> bool {
+    document.metadata.get(NORMALIZED_METADATA_KEY).map(|v| v == "true").unwrap_or(false)
+}
+
+/// Scale the embedding to unit length in place. Zero or non-finite vectors are left
+/// untouched and reported as not normalized.
+pub(super) fn normalize_embedding(embedding: &mut [f32]) -> bool {
+    let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return false;
+    }
+    
+    // Skip the division for vectors that are already unit length
+    if (norm - 1.0).abs() > 1e-6 {
+        for value in embedding.iter_mut() {
+            *value /= norm;
         }
-        
-        Ok(deleted_count)
     }
+    true
+}
+
+// Serialize a document, normalizing its embedding first if that hasn't happened yet
+fn serialize_for_storage(document: &Document) -> Result<Vec<u8>, String> {
+    let result = if is_normalized(document) || document.embedding.is_none() {
+        bincode::serialize(document)
+    } else {
+        let mut normalized = document.clone();
+        if let Some(embedding) = normalized.embedding.as_mut() {
+            if normalize_embedding(embedding) {
+                normalized.metadata.insert(NORMALIZED_METADATA_KEY.to_string(), "true".to_string());
+            }
+        }
+        bincode::serialize(&normalized)
+    };
+    
+    result.map_err(|e| format!("Failed to serialize document: {}", e))
+}
+
+pub(super) fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+// Computes the dot product and both norms in a single pass without allocating,
+// since this runs once per stored document on every search
+pub(super) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() {
         return 0.0;
     }
     
-    let vec_a = DVector::from_vec(a.to_vec());
-    let vec_b = DVector::from_vec(b.to_vec());
+    let mut dot_product = 0.0f32;
+    let mut norm_a_sq = 0.0f32;
+    let mut norm_b_sq = 0.0f32;
+    for (x, y) in a.iter().zip(b.iter()) {
+        dot_product += x * y;
+        norm_a_sq += x * x;
+        norm_b_sq += y * y;
+    }
     
-    let dot_product = vec_a.dot(&vec_b);
-    let norm_a = vec_a.norm();
-    let norm_b = vec_b.norm();
+    let norm_a = norm_a_sq.sqrt();
+    let norm_b = norm_b_sq.sqrt();
     
     if norm_a == 0.0 || norm_b == 0.0 {
         0.0
@@ -424,19 +854,43 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     }
 }
 
-#[tauri::command]
-pub async fn store_documents(documents: Vec<Document>) -> Result<String, String> {
-    if documents.is_empty() {
-        return Ok("No documents to store".to_string());
+// Search scans every chunk, so warn once the store grows past the configured soft limit
+fn capacity_warning(capacity: &StoreCapacity) -> Option<String> {
+    if capacity.max_chunks == 0 || capacity.chunk_count <= capacity.max_chunks {
+        return None;
     }
+    
+    Some(format!(
+        "The document store holds {} chunks, {} over the limit of {}. Search will get slower; consider removing documents you no longer need.",
+        capacity.chunk_count,
+        capacity.chunk_count - capacity.max_chunks,
+        capacity.max_chunks
+    ))
+}
 
+/// Store documents, warning rather than failing when the store ends up over max_stored_chunks
+#[tauri::command]
+pub async fn store_documents(documents: Vec<Document>) -> Result<StoreDocumentsResult, String> {
     let vector_store = VectorStore::new()?;
+    let stored = if documents.is_empty() {
+        0
+    } else {
+        vector_store.store_documents_batch(&documents)?
+    };
     
-    for document in &documents {
-        vector_store.store_document(document)?;
+    let capacity = vector_store.capacity();
+    let warning = capacity_warning(&capacity);
+    if let Some(warning) = &warning {
+        warn!("{}", warning);
     }
     
-    Ok(format!("Successfully stored {} documents", documents.len()))
+    Ok(StoreDocumentsResult { stored, capacity, warning })
+}
+
+#[tauri::command]
+pub async fn get_store_capacity() -> Result<StoreCapacity, String> {
+    let vector_store = VectorStore::new()?;
+    Ok(vector_store.capacity())
 }
 
 #[tauri::command]
@@ -447,6 +901,12 @@ pub async fn search_documents(query_embedding: Vec<f32>, limit: Option<usize>) -
     vector_store.search_similar(&query_embedding, search_limit)
 }
 
+#[tauri::command]
+pub async fn rebuild_search_index() -> Result<IndexRebuildStats, String> {
+    let vector_store = VectorStore::new()?;
+    vector_store.rebuild_search_index()
+}
+
 #[tauri::command]
 pub async fn get_all_documents() -> Result<Vec<Document>, String> {
     let vector_store = VectorStore::new()?;
@@ -472,6 +932,12 @@ pub async fn clear_all_documents() -> Result<String, String> {
     Ok("All documents cleared successfully".to_string())
 }
 
+#[tauri::command]
+pub async fn compact_vector_store() -> Result<CompactionStats, String> {
+    let vector_store = VectorStore::new()?;
+    vector_store.compact()
+}
+
 #[tauri::command]
 pub async fn get_all_files() -> Result<Vec<FileInfoSummary>, String> {
     let vector_store = VectorStore::new()?;
@@ -491,50 +957,35 @@ pub async fn get_all_files() -> Result<Vec<FileInfoSummary>, String> {
     Ok(summaries)
 }
 
+/// Chunk counts per file type, for filtering the document list
+#[tauri::command]
+pub async fn get_file_type_facets() -> Result<std::collections::HashMap<String, usize>, String> {
+    let vector_store = VectorStore::new()?;
+    Ok(vector_store.file_type_counts())
+}
+
 #[tauri::command]
 pub async fn get_file_chunks(#[allow(non_snake_case)] filePath: String) -> Result<Vec<Document>, String> {
     let vector_store = VectorStore::new()?;
+    Ok(vector_store.file_chunks(&filePath))
+}
+
+/// Chunks of the hit's file whose chunk_index is within `window` of the hit, in order.
+/// A hit without a chunk_index only returns itself
+#[tauri::command]
+pub async fn get_chunk_context(document_id: String, window: usize) -> Result<Vec<Document>, String> {
+    let vector_store = VectorStore::new()?;
+    let hit = vector_store.get_document(&document_id)?
+        .ok_or_else(|| format!("Document not found: {}", document_id))?;
     
-    let mut chunks = Vec::new();
-    
-    for item_result in vector_store.db.iter() {
-        match item_result {
-            Ok((key, value)) => {
-                // Skip metadata keys
-                if key.starts_with(b"__") {
-                    continue;
-                }
-                
-                match bincode::deserialize::<Document>(&value) {
-                    Ok(document) => {
-                        if document.file_path == filePath {
-                            chunks.push(document);
-                        }
-                    }
-                    Err(_) => {
-                        // Skip corrupted documents
-                        continue;
-                    }
-                }
-            }
-            Err(_) => {
-                // Skip database iteration errors
-                continue;
-            }
-        }
-    }
-    
-    // Sort by chunk index
-    chunks.sort_by(|a, b| {
-        match (a.chunk_index, b.chunk_index) {
-            (Some(a_idx), Some(b_idx)) => a_idx.cmp(&b_idx),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.created_at.cmp(&b.created_at),
+    let center = match hit.chunk_index {
+        Some(index) => index,
+        None => {
+            return Ok(vec![hit]);
         }
-    });
+    };
     
-    Ok(chunks)
+    Ok(chunks_within_window(vector_store.file_chunks(&hit.file_path), center, window))
 }
 
 #[tauri::command]
@@ -546,6 +997,87 @@ pub async fn delete_file_by_path(#[allow(non_snake_case)] filePath: String) -> R
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nalgebra::DVector;
+
+    // The previous allocating implementation, kept as a reference
+    fn cosine_similarity_dvector(a: &[f32], b: &[f32]) -> f32 {
+        let vec_a = DVector::from_vec(a.to_vec());
+        let vec_b = DVector::from_vec(b.to_vec());
+        let norm_a = vec_a.norm();
+        let norm_b = vec_b.norm();
+        if norm_a == 0.0 || norm_b == 0.0 {
+            0.0
+        } else {
+            vec_a.dot(&vec_b) / (norm_a * norm_b)
+        }
+    }
+
+    fn sample_embedding(seed: usize, dim: usize) -> Vec<f32> {
+        (0..dim).map(|i| (((i * 31 + seed * 17) % 97) as f32 - 48.0) / 48.0).collect()
+    }
+
+    #[test]
+    fn test_cosine_similarity_matches_dvector() {
+        for seed in 0..20 {
+            let a = sample_embedding(seed, 384);
+            let b = sample_embedding(seed + 7, 384);
+            let expected = cosine_similarity_dvector(&a, &b);
+            assert!((cosine_similarity(&a, &b) - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_normalize_embedding() {
+        let mut embedding = vec![3.0, 4.0];
+        assert!(normalize_embedding(&mut embedding));
+        assert!((embedding[0] - 0.6).abs() < 1e-6);
+        assert!((embedding[1] - 0.8).abs() < 1e-6);
+
+        // Already normalized vectors stay the same
+        assert!(normalize_embedding(&mut embedding));
+        assert!((embedding[0] - 0.6).abs() < 1e-6);
+
+        let mut zero = vec![0.0, 0.0];
+        assert!(!normalize_embedding(&mut zero));
+        assert_eq!(zero, vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_normalized_dot_product_matches_cosine() {
+        let a = sample_embedding(3, 384);
+        let b = sample_embedding(11, 384);
+        let expected = cosine_similarity(&a, &b);
+
+        let mut a_norm = a.clone();
+        let mut b_norm = b.clone();
+        normalize_embedding(&mut a_norm);
+        normalize_embedding(&mut b_norm);
+        assert!((dot_product(&a_norm, &b_norm) - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_cosine_similarity_guards() {
+        assert_eq!(cosine_similarity(&[1.0, 2.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    #[ignore] // Run with `cargo test -- --ignored --nocapture` to compare timings
+    fn bench_cosine_similarity() {
+        let query = sample_embedding(1, 1024);
+        let stored: Vec<Vec<f32>> = (0..5000).map(|seed| sample_embedding(seed, 1024)).collect();
+
+        let start = std::time::Instant::now();
+        let fast: f32 = stored.iter().map(|doc| cosine_similarity(&query, doc)).sum();
+        let fast_elapsed = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let reference: f32 = stored.iter().map(|doc| cosine_similarity_dvector(&query, doc)).sum();
+        let reference_elapsed = start.elapsed();
+
+        println!("single pass: {:?}, dvector: {:?}", fast_elapsed, reference_elapsed);
+        assert!((fast - reference).abs() < 0.1);
+    }
 
     #[test]
     fn test_cosine_similarity() {
@@ -555,6 +1087,93 @@ mod tests {
         assert!((similarity - 1.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_chunks_within_window() {
+        let chunks: Vec<Document> = [Some(0), Some(1), Some(2), Some(3), Some(4), None]
+            .into_iter()
+            .map(|index| Document::new("t".into(), "c".into(), "txt".into(), "a.txt".into(), index))
+            .collect();
+        
+        let context = chunks_within_window(chunks, 1, 1);
+        let indexes: Vec<Option<usize>> = context.iter().map(|chunk| chunk.chunk_index).collect();
+        assert_eq!(indexes, vec![Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_repair_created_at() {
+        let now = 1_700_000_000_000;
+        let mut doc = Document::new(
+            "title".to_string(),
+            "content".to_string(),
+            "txt".to_string(),
+            "notes.txt".to_string(),
+            Some(0),
+        );
+
+        doc.created_at = now - 1000;
+        assert!(!repair_created_at(&mut doc, now));
+        assert_eq!(doc.created_at, now - 1000);
+
+        doc.created_at = now + MAX_FUTURE_SKEW_MS * 30;
+        assert!(repair_created_at(&mut doc, now));
+        assert_eq!(doc.created_at, now);
+
+        doc.created_at = -5;
+        assert!(repair_created_at(&mut doc, now));
+        assert_eq!(doc.created_at, now);
+    }
+
+    #[test]
+    fn test_decode_legacy_document() {
+        #[derive(serde::Serialize)]
+        struct OldDocument {
+            id: String,
+            title: String,
+            content: String,
+            file_type: String,
+            file_path: String,
+            embedding: Option<Vec<f32>>,
+            created_at: i64,
+        }
+
+        let old = OldDocument {
+            id: "doc-1".to_string(),
+            title: "Notes".to_string(),
+            content: "hello".to_string(),
+            file_type: "txt".to_string(),
+            file_path: "notes.txt".to_string(),
+            embedding: Some(vec![0.5, 0.5]),
+            created_at: 42,
+        };
+        let bytes = bincode::serialize(&old).unwrap();
+
+        let document = decode_document_any_version(&bytes).unwrap();
+        assert_eq!(document.id, "doc-1");
+        assert_eq!(document.chunk_index, None);
+        assert!(document.metadata.is_empty());
+        assert_eq!(document.embedding, Some(vec![0.5, 0.5]));
+        assert_eq!(document.created_at, 42);
+
+        assert!(decode_document_any_version(b"garbage").is_none());
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        assert!(dimension_mismatch(None, 768).is_none());
+        assert!(dimension_mismatch(Some(768), 768).is_none());
+        assert!(dimension_mismatch(Some(768), 384).unwrap().contains("384 dimensions"));
+    }
+
+    #[test]
+    fn test_capacity_warning_only_over_limit() {
+        let capacity = |chunk_count, max_chunks| StoreCapacity { chunk_count, max_chunks };
+        assert!(capacity_warning(&capacity(100, 100)).is_none());
+        assert!(capacity_warning(&capacity(5_000, 0)).is_none());
+
+        let warning = capacity_warning(&capacity(120, 100)).unwrap();
+        assert!(warning.contains("20 over the limit of 100"));
+    }
+
     #[test]
     fn test_cosine_similarity_orthogonal() {
         let a = vec![1.0, 0.0];
@@ -0,0 +1,292 @@
+use super::documents::{process_document, SUPPORTED_EXTENSIONS};
+use super::embeddings::embed_documents;
+use super::vector_store::VectorStore;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::event::ModifyKind;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn, error, debug};
+
+// Wait for editors to finish writing before reindexing a file
+const DEBOUNCE_MS: u64 = 1000;
+
+lazy_static::lazy_static! {
+    static ref FOLDER_WATCHERS: Arc<Mutex<HashMap<String, RecommendedWatcher>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref PENDING_EVENTS: Arc<Mutex<HashMap<PathBuf, u64>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    // Reindexing runs embeddings through OVMS, so watched file updates go one at a time
+    static ref REINDEX_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+static EVENT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WatchedFolders {
+    folders: Vec<String>,
+}
+
+fn get_watched_folders_path() -> Result<PathBuf, String> {
+    let rag_dir = crate::paths::sparrow_data_dir()?.join("rag");
+    std::fs::create_dir_all(&rag_dir)
+        .map_err(|e| format!("Failed to create rag directory: {}", e))?;
+
+    Ok(rag_dir.join("watched.json"))
+}
+
+fn load_watched_folders() -> Result<WatchedFolders, String> {
+    let path = get_watched_folders_path()?;
+    if !path.exists() {
+        return Ok(WatchedFolders::default());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read watched folders: {}", e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse watched folders: {}", e))
+}
+
+fn save_watched_folders(watched: &WatchedFolders) -> Result<(), String> {
+    let path = get_watched_folders_path()?;
+    let contents = serde_json::to_string_pretty(watched)
+        .map_err(|e| format!("Failed to serialize watched folders: {}", e))?;
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write watched folders: {}", e))
+}
+
+fn is_supported_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Run the file through the ingestion pipeline again and swap its stored chunks for the new ones
+pub async fn reindex_file(file_path: &str) -> Result<usize, String> {
+    let documents = process_document(file_path.to_string(), None, None).await?;
+    let documents = embed_documents(documents).await?;
+
+    VectorStore::new()?.replace_file(file_path, &documents)
+}
+
+async fn handle_file_change(app: AppHandle, folder: String, path: PathBuf) {
+    let file_path = path.to_string_lossy().to_string();
+    let _guard = REINDEX_LOCK.lock().await;
+
+    // Renames and deletes both show up as a path that no longer exists
+    let payload = if path.is_file() {
+        match reindex_file(&file_path).await {
+            Ok(chunks) => {
+                info!(file_path = %file_path, chunks = chunks, "Reindexed watched file");
+                serde_json::json!({
+                    "folder": folder,
+                    "file_path": file_path,
+                    "action": "indexed",
+                    "chunks": chunks
+                })
+            }
+            Err(e) => {
+                error!(file_path = %file_path, error = %e, "Failed to reindex watched file");
+                serde_json::json!({
+                    "folder": folder,
+                    "file_path": file_path,
+                    "action": "error",
+                    "error": e
+                })
+            }
+        }
+    } else {
+        match VectorStore::new().and_then(|store| store.delete_file(&file_path)) {
+            Ok(removed) => {
+                info!(file_path = %file_path, removed = removed, "Removed watched file from store");
+                serde_json::json!({
+                    "folder": folder,
+                    "file_path": file_path,
+                    "action": "removed",
+                    "chunks": removed
+                })
+            }
+            Err(e) => {
+                error!(file_path = %file_path, error = %e, "Failed to remove watched file");
+                serde_json::json!({
+                    "folder": folder,
+                    "file_path": file_path,
+                    "action": "error",
+                    "error": e
+                })
+            }
+        }
+    };
+
+    let _ = app.emit("rag-folder-updated", payload);
+}
+
+fn schedule_file_change(app: &AppHandle, folder: &str, path: PathBuf) {
+    let generation = EVENT_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut pending) = PENDING_EVENTS.lock() {
+        pending.insert(path.clone(), generation);
+    }
+
+    let app = app.clone();
+    let folder = folder.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_millis(DEBOUNCE_MS)).await;
+
+        // Only the most recent event for a path gets processed
+        let is_latest = match PENDING_EVENTS.lock() {
+            Ok(mut pending) => {
+                if pending.get(&path) == Some(&generation) {
+                    pending.remove(&path);
+                    true
+                } else {
+                    false
+                }
+            }
+            Err(_) => false,
+        };
+
+        if is_latest {
+            handle_file_change(app, folder, path).await;
+        }
+    });
+}
+
+fn start_watcher(app: AppHandle, folder: &str) -> Result<RecommendedWatcher, String> {
+    let watched_folder = folder.to_string();
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        match result {
+            Ok(event) => {
+                let relevant = match event.kind {
+                    EventKind::Create(_) | EventKind::Remove(_) => true,
+                    EventKind::Modify(ModifyKind::Metadata(_)) => false,
+                    EventKind::Modify(_) => true,
+                    _ => false,
+                };
+                if !relevant {
+                    return;
+                }
+
+                for path in event.paths {
+                    if is_supported_file(&path) {
+                        debug!(path = %path.display(), kind = ?event.kind, "Watched folder change");
+                        schedule_file_change(&app, &watched_folder, path);
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(folder = %watched_folder, error = %e, "Folder watcher error");
+            }
+        }
+    }).map_err(|e| format!("Failed to create folder watcher: {}", e))?;
+
+    watcher.watch(Path::new(folder), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch folder {}: {}", folder, e))?;
+
+    Ok(watcher)
+}
+
+#[tauri::command]
+pub async fn watch_folder(app_handle: AppHandle, path: String) -> Result<String, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Folder does not exist: {}", path));
+    }
+
+    {
+        let mut watchers = FOLDER_WATCHERS.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if watchers.contains_key(&path) {
+            return Ok(format!("Already watching folder: {}", path));
+        }
+
+        let watcher = start_watcher(app_handle, &path)?;
+        watchers.insert(path.clone(), watcher);
+    }
+
+    let mut watched = load_watched_folders()?;
+    if !watched.folders.contains(&path) {
+        watched.folders.push(path.clone());
+        save_watched_folders(&watched)?;
+    }
+
+    info!(folder = %path, "Started watching folder");
+    Ok(format!("Watching folder: {}", path))
+}
+
+#[tauri::command]
+pub async fn stop_watching_folder(path: String) -> Result<String, String> {
+    let was_watching = {
+        let mut watchers = FOLDER_WATCHERS.lock().map_err(|e| format!("Lock error: {}", e))?;
+        watchers.remove(&path).is_some()
+    };
+
+    let mut watched = load_watched_folders()?;
+    let was_saved = watched.folders.contains(&path);
+    if was_saved {
+        watched.folders.retain(|folder| folder != &path);
+        save_watched_folders(&watched)?;
+    }
+
+    if !was_watching && !was_saved {
+        return Err(format!("Folder is not being watched: {}", path));
+    }
+
+    info!(folder = %path, "Stopped watching folder");
+    Ok(format!("Stopped watching folder: {}", path))
+}
+
+/// Re-register the watchers saved in watched.json, called once on startup
+pub fn restore_watched_folders(app_handle: AppHandle) {
+    let watched = match load_watched_folders() {
+        Ok(watched) => watched,
+        Err(e) => {
+            warn!(error = %e, "Failed to load watched folders");
+            return;
+        }
+    };
+
+    let mut watchers = match FOLDER_WATCHERS.lock() {
+        Ok(watchers) => watchers,
+        Err(e) => {
+            error!(error = %e, "Failed to lock folder watchers");
+            return;
+        }
+    };
+
+    for folder in watched.folders {
+        if watchers.contains_key(&folder) {
+            continue;
+        }
+        if !Path::new(&folder).is_dir() {
+            warn!(folder = %folder, "Watched folder no longer exists, skipping");
+            continue;
+        }
+
+        match start_watcher(app_handle.clone(), &folder) {
+            Ok(watcher) => {
+                info!(folder = %folder, "Restored folder watcher");
+                watchers.insert(folder, watcher);
+            }
+            Err(e) => {
+                warn!(folder = %folder, error = %e, "Failed to restore folder watcher");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_supported_file() {
+        assert!(is_supported_file(Path::new("notes/report.pdf")));
+        assert!(is_supported_file(Path::new("notes/Budget.XLSX")));
+        assert!(is_supported_file(Path::new("notes/readme.md")));
+        assert!(!is_supported_file(Path::new("notes/image.png")));
+        assert!(!is_supported_file(Path::new("notes/no_extension")));
+    }
+}
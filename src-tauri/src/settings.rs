@@ -0,0 +1,337 @@
+use serde::{ Deserialize, Serialize };
+use std::collections::HashMap;
+use std::sync::{ OnceLock, RwLock };
+use tracing::{ info, warn };
+
+/// Application settings stored in ~/.sparrow/settings.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Overrides where SparrowAI keeps its data (see paths::sparrow_data_dir)
+    pub data_dir: Option<String>,
+    pub ovms_port: u16,
+    /// OpenVINO device used for the embedding and LLM graphs, e.g. "GPU" or "CPU"
+    pub inference_device: String,
+    /// Per-model device overrides keyed by model id, e.g. "OpenVINO/Qwen3-8B-int4-ov" -> "GPU".
+    /// Models without an entry use inference_device
+    pub model_devices: HashMap<String, String>,
+    pub log_retention_days: u32,
+    /// Where models are downloaded to when no path is given, defaults to <data_dir>/models
+    pub default_download_path: Option<String>,
+    pub embedding_model: String,
+    /// Most embeddings kept in the on-disk embedding cache, 0 disables caching
+    pub embedding_cache_max_entries: usize,
+    /// Chat streams allowed to run against OVMS at once, later ones wait. 0 means no limit
+    pub max_concurrent_chats: usize,
+    /// Stored chunks above which store_documents warns that search will slow down, 0 means no limit.
+    /// A soft limit: documents are still stored
+    pub max_stored_chunks: usize,
+    /// Largest document save_temp_file accepts, in MB
+    pub max_upload_mb: u64,
+    /// LLM scheduler options written into generated graph.pbtxt files
+    pub ovms_runtime: OvmsRuntimeOptions,
+}
+
+/// Continuous batching options for LLM graphs. Lower values use less device memory,
+/// higher ones allow more concurrent requests on large GPUs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OvmsRuntimeOptions {
+    pub max_num_seqs: u32,
+    pub max_num_batched_tokens: u32,
+    /// KV cache size in GB
+    pub cache_size: u32,
+    /// Reuse the KV cache of a prompt prefix seen before, so in multi-turn chat the system
+    /// prompt and history aren't processed again each turn. Faster follow-up answers, but
+    /// cached blocks stay in the KV cache and compete with new requests for cache_size.
+    /// Not applied to NPU graphs
+    pub enable_prefix_caching: bool,
+}
+
+impl Default for OvmsRuntimeOptions {
+    fn default() -> Self {
+        Self {
+            max_num_seqs: 256,
+            max_num_batched_tokens: 8192,
+            cache_size: 2,
+            enable_prefix_caching: true,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            ovms_port: 1114,
+            inference_device: "GPU".to_string(),
+            model_devices: HashMap::new(),
+            log_retention_days: 30,
+            default_download_path: None,
+            embedding_model: "bge-base-en-v1.5-int8-ov".to_string(),
+            embedding_cache_max_entries: 20_000,
+            max_concurrent_chats: 0,
+            max_stored_chunks: 50_000,
+            max_upload_mb: 100,
+            ovms_runtime: OvmsRuntimeOptions::default(),
+        }
+    }
+}
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+
+fn load_settings() -> Settings {
+    let path = match crate::paths::bootstrap_settings_path() {
+        Ok(path) => path,
+        Err(_) => {
+            return Settings::default();
+        }
+    };
+
+    if !path.exists() {
+        return Settings::default();
+    }
+
+    match std::fs::read_to_string(&path).map(|contents| serde_json::from_str::<Settings>(&contents)) {
+        Ok(Ok(settings)) => settings,
+        Ok(Err(e)) => {
+            warn!(error = %e, "Failed to parse settings, using defaults");
+            Settings::default()
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to read settings, using defaults");
+            Settings::default()
+        }
+    }
+}
+
+fn save_settings(settings: &Settings) -> Result<(), String> {
+    let path = crate::paths::bootstrap_settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs
+            ::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    let contents = serde_json
+        ::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+fn settings_lock() -> &'static RwLock<Settings> {
+    SETTINGS.get_or_init(|| RwLock::new(load_settings()))
+}
+
+/// Current settings, loaded from disk on first use and cached afterwards
+pub fn current() -> Settings {
+    settings_lock()
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Base URL of the local OVMS REST API
+pub fn ovms_base_url() -> String {
+    format!("http://localhost:{}", current().ovms_port)
+}
+
+fn normalize_model_id(model_id: &str) -> String {
+    let model_id = model_id.replace('\\', "/");
+    if model_id.starts_with("OpenVINO/") { model_id } else { format!("OpenVINO/{}", model_id) }
+}
+
+fn resolve_model_device(settings: &Settings, model_id: &str) -> String {
+    settings.model_devices
+        .get(&normalize_model_id(model_id))
+        .cloned()
+        .unwrap_or_else(|| settings.inference_device.clone())
+}
+
+/// Device the given model's graph should target, its override or the global inference_device
+pub fn device_for_model(model_id: &str) -> String {
+    resolve_model_device(&current(), model_id)
+}
+
+/// Apply the fields present in `partial` on top of `settings`
+fn merge_settings(settings: &Settings, partial: serde_json::Value) -> Result<Settings, String> {
+    let patch = match partial {
+        serde_json::Value::Object(patch) => patch,
+        _ => {
+            return Err("Settings update must be a JSON object".to_string());
+        }
+    };
+
+    let mut merged = serde_json
+        ::to_value(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    if let Some(fields) = merged.as_object_mut() {
+        for (key, value) in patch {
+            if !fields.contains_key(&key) {
+                return Err(format!("Unknown setting: {}", key));
+            }
+            fields.insert(key, value);
+        }
+    }
+
+    let merged: Settings = serde_json
+        ::from_value(merged)
+        .map_err(|e| format!("Invalid settings: {}", e))?;
+
+    if merged.ovms_port == 0 {
+        return Err("ovms_port must be between 1 and 65535".to_string());
+    }
+    if merged.log_retention_days == 0 {
+        return Err("log_retention_days must be at least 1".to_string());
+    }
+    if merged.inference_device.trim().is_empty() {
+        return Err("inference_device must not be empty".to_string());
+    }
+    if merged.max_upload_mb == 0 {
+        return Err("max_upload_mb must be at least 1".to_string());
+    }
+    if let Some((model_id, _)) = merged.model_devices.iter().find(|(_, device)| device.trim().is_empty()) {
+        return Err(format!("Device for {} must not be empty", model_id));
+    }
+    validate_runtime_options(&merged.ovms_runtime)?;
+
+    Ok(merged)
+}
+
+fn validate_runtime_options(options: &OvmsRuntimeOptions) -> Result<(), String> {
+    if options.max_num_seqs == 0 {
+        return Err("max_num_seqs must be at least 1".to_string());
+    }
+    if options.max_num_batched_tokens == 0 {
+        return Err("max_num_batched_tokens must be at least 1".to_string());
+    }
+    if options.cache_size == 0 {
+        return Err("cache_size must be at least 1 GB".to_string());
+    }
+    Ok(())
+}
+
+/// Save new LLM graph options. Graphs pick them up when they are regenerated
+pub fn set_ovms_runtime_options(options: OvmsRuntimeOptions) -> Result<Settings, String> {
+    validate_runtime_options(&options)?;
+
+    let mut settings = settings_lock()
+        .write()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    let mut updated = settings.clone();
+    updated.ovms_runtime = options;
+    save_settings(&updated)?;
+    *settings = updated.clone();
+
+    info!(options = ?updated.ovms_runtime, "OVMS runtime options updated");
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn get_settings() -> Result<Settings, String> {
+    Ok(current())
+}
+
+#[tauri::command]
+pub async fn update_settings(partial: serde_json::Value) -> Result<Settings, String> {
+    let mut settings = settings_lock()
+        .write()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    let updated = merge_settings(&settings, partial)?;
+    save_settings(&updated)?;
+    *settings = updated.clone();
+
+    info!("Settings updated");
+    Ok(updated)
+}
+
+/// Set the device one model runs on, or remove its override with `device: None`.
+/// Graphs pick it up when they are regenerated
+pub fn set_model_device(model_id: String, device: Option<String>) -> Result<Settings, String> {
+    let mut settings = settings_lock()
+        .write()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    let mut updated = settings.clone();
+    let model_id = normalize_model_id(&model_id);
+    match device.map(|device| device.trim().to_string()) {
+        Some(device) if device.is_empty() => {
+            return Err("Device must not be empty".to_string());
+        }
+        Some(device) => {
+            updated.model_devices.insert(model_id.clone(), device);
+        }
+        None => {
+            updated.model_devices.remove(&model_id);
+        }
+    }
+
+    save_settings(&updated)?;
+    *settings = updated.clone();
+
+    info!(model_id = %model_id, "Model device updated");
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_settings_keeps_other_fields() {
+        let settings = Settings::default();
+        let merged = merge_settings(
+            &settings,
+            serde_json::json!({ "ovms_port": 2000, "inference_device": "CPU" })
+        ).unwrap();
+
+        assert_eq!(merged.ovms_port, 2000);
+        assert_eq!(merged.inference_device, "CPU");
+        assert_eq!(merged.log_retention_days, settings.log_retention_days);
+        assert_eq!(merged.embedding_model, settings.embedding_model);
+    }
+
+    #[test]
+    fn test_merge_settings_rejects_invalid_values() {
+        let settings = Settings::default();
+        assert!(merge_settings(&settings, serde_json::json!({ "ovms_port": 0 })).is_err());
+        assert!(merge_settings(&settings, serde_json::json!({ "ovms_port": "abc" })).is_err());
+        assert!(merge_settings(&settings, serde_json::json!({ "unknown": true })).is_err());
+        assert!(merge_settings(&settings, serde_json::json!([1, 2])).is_err());
+        assert!(merge_settings(&settings, serde_json::json!({ "max_upload_mb": 0 })).is_err());
+    }
+
+    #[test]
+    fn test_resolve_model_device_falls_back_to_global() {
+        let mut settings = Settings::default();
+        settings.inference_device = "GPU".to_string();
+        settings.model_devices.insert("OpenVINO/bge-reranker-base-int8-ov".to_string(), "CPU".to_string());
+
+        assert_eq!(resolve_model_device(&settings, "bge-reranker-base-int8-ov"), "CPU");
+        assert_eq!(resolve_model_device(&settings, "OpenVINO/bge-reranker-base-int8-ov"), "CPU");
+        assert_eq!(resolve_model_device(&settings, "OpenVINO/Qwen3-8B-int4-ov"), "GPU");
+    }
+
+    #[test]
+    fn test_merge_settings_rejects_zero_runtime_options() {
+        let settings = Settings::default();
+        let merged = merge_settings(
+            &settings,
+            serde_json::json!({ "ovms_runtime": { "max_num_seqs": 16, "enable_prefix_caching": false } })
+        ).unwrap();
+        assert_eq!(merged.ovms_runtime.max_num_seqs, 16);
+        assert!(!merged.ovms_runtime.enable_prefix_caching);
+        assert_eq!(merged.ovms_runtime.max_num_batched_tokens, 8192);
+
+        assert!(merge_settings(&settings, serde_json::json!({ "ovms_runtime": { "cache_size": 0 } })).is_err());
+    }
+
+    #[test]
+    fn test_settings_missing_fields_use_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{ "data_dir": "/tmp/sparrow" }"#).unwrap();
+        assert_eq!(settings.data_dir.as_deref(), Some("/tmp/sparrow"));
+        assert_eq!(settings.ovms_port, 1114);
+    }
+}
@@ -7,6 +7,8 @@ import {
   Dialog,
   DialogTitle,
   DialogContent,
+  DialogActions,
+  Button,
   Box,
   CircularProgress
 } from "@mui/material";
@@ -44,9 +46,11 @@ function App() {
     clearCurrentChatMessages,
     clearTemporarySession,
     setTemporarySession,
+    addChatSession,
   } = useChat();
   
   const [initStatus, setInitStatus] = React.useState(null);
+  const [ovmsDownload, setOvmsDownload] = React.useState(null);
   const [showInitDialog, setShowInitDialog] = React.useState(false);
 
   // Create theme based on current mode and color
@@ -57,8 +61,32 @@ function App() {
 
   // Create a new chat session on app startup
   useEffect(() => {
+    // Save temporary sessions left behind by a crash so their messages aren't lost
+    const recoverTemporarySessions = async () => {
+      try {
+        const recoverable = await invoke("list_recoverable_temp_sessions");
+        let recovered = 0;
+        for (const session of recoverable) {
+          if (session.messages.length > 0) {
+            const persisted = await invoke("persist_temporary_session", { session });
+            addChatSession(persisted);
+            recovered += 1;
+          } else {
+            await invoke("discard_temp_session", { sessionId: session.id });
+          }
+        }
+        if (recovered > 0) {
+          showNotification(`Recovered ${recovered} unsaved chat session(s)`, "info");
+        }
+      } catch (error) {
+        console.error("Failed to recover temporary chat sessions:", error);
+      }
+    };
+
     const createNewChatOnStartup = async () => {
       try {
+        await recoverTemporarySessions();
+
         // Clear any existing state first
         clearTemporarySession();
         clearCurrentChatMessages();
@@ -71,8 +99,12 @@ function App() {
           title: "New Chat",
         });
 
-        // Set this as the temporary session (not saved to storage yet)
+        // Set this as the temporary session (not saved to storage yet), but mirror it to
+        // disk so it can be recovered if the app dies before it's persisted
         setTemporarySession(newSession);
+        invoke("enable_temp_session_autosave", { session: newSession }).catch((error) =>
+          console.error("Failed to enable temp session autosave:", error)
+        );
         setActiveChatSessionId(newSession.id);
 
         // Force clear messages again after setting the session
@@ -92,12 +124,16 @@ function App() {
         const status = await invoke("get_initialization_status");
         setInitStatus(status);
         
+        const isCancelled = status.step === "cancelled";
+
         // Show dialog if initialization is in progress
-        if (!status.is_complete && !status.has_error) {
+        if (!status.is_complete && !status.has_error && !isCancelled) {
           setShowInitDialog(true);
         }
         
-        if (status.is_complete) {
+        if (isCancelled) {
+          setIsOvmsRunning(false);
+        } else if (status.is_complete) {
           setIsOvmsRunning(true);
           showNotification("OVMS initialized successfully", "success");
           
@@ -119,13 +155,18 @@ function App() {
     const unlisten = listen("ovms-init-status", (event) => {
       const status = event.payload;
       setInitStatus(status);
+      const isCancelled = status.step === "cancelled";
       
       // Show dialog when initialization starts
-      if (!status.is_complete && !status.has_error) {
+      if (!status.is_complete && !status.has_error && !isCancelled) {
         setShowInitDialog(true);
       }
       
-      if (status.is_complete) {
+      if (isCancelled) {
+        setIsOvmsRunning(false);
+        showNotification("OVMS initialization cancelled", "info");
+        setShowInitDialog(false);
+      } else if (status.is_complete) {
         setIsOvmsRunning(true);
         showNotification("OVMS initialized successfully", "success");
         // Close dialog after a brief delay to show completion
@@ -145,6 +186,26 @@ function App() {
     };
   }, [setIsOvmsRunning, showNotification]);
 
+  useEffect(() => {
+    const unlisten = listen("ovms-download-progress", (event) => {
+      setOvmsDownload(event.payload);
+    });
+
+    return () => {
+      unlisten.then(f => f());
+    };
+  }, []);
+
+  const formatMegabytes = (bytes) => (bytes / (1024 * 1024)).toFixed(1);
+
+  const handleCancelInit = async () => {
+    try {
+      await invoke("cancel_ovms_init");
+    } catch (error) {
+      console.error("Failed to cancel OVMS initialization:", error);
+    }
+  };
+
   const renderPage = () => {
     switch (currentPage) {
       case "chat":
@@ -235,9 +296,21 @@ function App() {
               <Typography variant="body2" color="text.secondary">
                 {initStatus.progress}%
               </Typography>
+              {initStatus.step === "downloading" && ovmsDownload && (
+                <Typography variant="caption" color="text.secondary">
+                  {ovmsDownload.totalBytes
+                    ? `${formatMegabytes(ovmsDownload.downloadedBytes)} MB of ${formatMegabytes(ovmsDownload.totalBytes)} MB`
+                    : `${formatMegabytes(ovmsDownload.downloadedBytes)} MB downloaded`}
+                </Typography>
+              )}
             </Box>
           )}
         </DialogContent>
+        <DialogActions sx={{ justifyContent: "center", pb: 2 }}>
+          <Button onClick={handleCancelInit} color="inherit">
+            Cancel
+          </Button>
+        </DialogActions>
       </Dialog>
     </ThemeProvider>
   );
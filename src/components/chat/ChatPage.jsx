@@ -31,6 +31,7 @@ import {
   ExpandMore as ExpandMoreIcon,
 } from "@mui/icons-material";
 import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "../../utils/errors";
 import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import ReactMarkdown from "react-markdown";
@@ -89,6 +90,17 @@ const ChatPage = () => {
   const messagesEndRef = useRef(null);
   const unlistenRef = useRef(null);
 
+  // RAG falls back to a plain answer when retrieval isn't possible, tell the user why
+  useEffect(() => {
+    const unlisten = listen("rag-warning", (event) => {
+      showNotification(event.payload.message, "warning");
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [showNotification]);
+
   useEffect(() => {
     const initialize = async () => {
       await setupEventListeners();
@@ -477,24 +489,9 @@ const ChatPage = () => {
           }
         }
 
-        // Build the model path manually to avoid canonical path issues
-        const modelName = newModelId.includes("/")
-          ? newModelId.split("/")[1]
-          : newModelId;
-
-        // Get user profile directory and build clean path
-        const userProfile = await invoke("get_user_profile_dir");
-        const model_path =
-          `${userProfile}/.sparrow/models/${newModelId}`.replace(/\\/g, "/");
-
-        // Update OVMS config with the new model
-        await invoke("update_ovms_config", {
-          modelName: modelName,
-          modelPath: model_path,
-        });
-
-        // Reload OVMS config
-        await invoke("reload_ovms_config");
+        // Adds the model to the OVMS config, waits for it and runs a warmup request
+        // so the first message doesn't pay the compile cost
+        await invoke("load_model", { modelId: newModelId, warmup: true });
 
         showNotification(
           `Model loaded: ${
@@ -510,7 +507,7 @@ const ChatPage = () => {
       // The loadedModel state is already updated above, no need to refresh arrays
     } catch (error) {
       console.error("Failed to load model:", error);
-      showNotification(`Failed to load model: ${error}`, "error");
+      showNotification(`Failed to load model: ${getErrorMessage(error)}`, "error");
       // Reset selection on error
       setSelectedModel("");
     } finally {
@@ -617,11 +614,9 @@ const ChatPage = () => {
         console.log("Added user message to existing session");
 
         // Update session title if it was auto-generated and refresh chat sessions
-        const sessionData = await invoke("get_chat_sessions");
-        const currentSession =
-          sessionData && sessionData.sessions
-            ? sessionData.sessions[activeChatSessionId]
-            : null;
+        const currentSession = await invoke("get_chat_session", {
+          sessionId: activeChatSessionId,
+        });
         if (currentSession) {
           // Update the session in the store with the latest data including messages
           updateChatSession(activeChatSessionId, currentSession);
@@ -651,6 +646,7 @@ const ChatPage = () => {
         seed: settings.seed,
         maxTokens: settings.maxTokens,
         maxCompletionTokens: settings.maxCompletionTokens,
+        useSystemMessage: settings.useSystemMessage ?? true,
       };
 
       // Add RAG-specific parameters if RAG is enabled
@@ -776,13 +772,15 @@ const ChatPage = () => {
     }
 
     // Tool responses
-    const toolResponseRegex = /<tool_response>([\s\S]*?)<\/tool_response>/g;
+    const toolResponseRegex =
+      /<tool_response(?:\s+id="([^"]*)")?>([\s\S]*?)<\/tool_response>/g;
     while ((match = toolResponseRegex.exec(content)) !== null) {
       allMatches.push({
         type: "tool_response",
         start: match.index,
         end: match.index + match[0].length,
-        content: match[1].trim(),
+        id: match[1] || null,
+        content: match[2].trim(),
         fullMatch: match[0],
       });
     }
@@ -26,6 +26,7 @@ import {
   InsertDriveFile as FileIcon,
 } from "@mui/icons-material";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useUI } from "../../store";
 
 const DocumentsPage = () => {
@@ -38,6 +39,30 @@ const DocumentsPage = () => {
   const [error, setError] = useState(null);
   const [expandedFile, setExpandedFile] = useState(null);
   const [fileChunks, setFileChunks] = useState({});
+  const [supportedTypes, setSupportedTypes] = useState(["pdf", "docx", "xlsx", "xls"]);
+  const [maxUploadMb, setMaxUploadMb] = useState(100);
+  const [processingProgress, setProcessingProgress] = useState(null);
+  // Id of the running embedding request, so it can be cancelled
+  const [embeddingOperationId, setEmbeddingOperationId] = useState(null);
+
+  useEffect(() => {
+    invoke("supported_document_types")
+      .then(setSupportedTypes)
+      .catch((err) => console.error("Failed to load supported document types:", err));
+    invoke("get_settings")
+      .then((settings) => setMaxUploadMb(settings.max_upload_mb))
+      .catch((err) => console.error("Failed to load upload size limit:", err));
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen("document-processing-progress", (event) => {
+      setProcessingProgress(event.payload);
+    });
+
+    return () => {
+      unlisten.then(f => f());
+    };
+  }, []);
 
   useEffect(() => {
     const safeLoadDocuments = async () => {
@@ -97,20 +122,17 @@ const DocumentsPage = () => {
     const file = event.target.files[0];
     if (!file) return;
 
-    const supportedTypes = [
-      'application/pdf', 
-      'application/vnd.openxmlformats-officedocument.wordprocessingml.document', 
-      'application/vnd.openxmlformats-officedocument.spreadsheetml.sheet',
-      'application/vnd.ms-excel'
-    ];
-    
-    if (!supportedTypes.includes(file.type)) {
-      showNotification("Unsupported file type. Please upload PDF, DOCX, or XLSX files.", "error");
+    const extension = file.name.split('.').pop().toLowerCase();
+    if (!file.name.includes('.') || !supportedTypes.includes(extension)) {
+      showNotification(
+        `Unsupported file type. Please upload ${supportedTypes.map((ext) => ext.toUpperCase()).join(", ")} files.`,
+        "error"
+      );
       return;
     }
 
-    if (file.size > 50 * 1024 * 1024) { // 50MB limit
-      showNotification("File size must be less than 50MB", "error");
+    if (file.size > maxUploadMb * 1024 * 1024) {
+      showNotification(`File size must be at most ${maxUploadMb}MB`, "error");
       return;
     }
 
@@ -123,8 +145,8 @@ const DocumentsPage = () => {
         fileData: Array.from(new Uint8Array(await file.arrayBuffer()))
       });
       
-      // Process document
-      const documents = await invoke("process_document", { filePath });
+      // Process document, reporting page/sheet progress while it runs
+      const documents = await invoke("process_document_streaming", { filePath });
       
       if (!documents || documents.length === 0) {
         showNotification("No content could be extracted from the document", "warning");
@@ -132,12 +154,39 @@ const DocumentsPage = () => {
       }
       
       // Create embeddings
-      const documentsWithEmbeddings = await invoke("create_document_embeddings", { documents });
+      const operationId = crypto.randomUUID();
+      setEmbeddingOperationId(operationId);
+      let embeddingOutcome;
+      try {
+        embeddingOutcome = await invoke("create_document_embeddings", {
+          documents,
+          operationId,
+        });
+      } finally {
+        setEmbeddingOperationId(null);
+      }
+
+      if (embeddingOutcome.status === "cancelled") {
+        // With the embedding cache on, uploading the file again resumes from the finished batches
+        showNotification(
+          embeddingOutcome.resumable
+            ? `Document processing cancelled after ${embeddingOutcome.processed} of ${embeddingOutcome.total} chunks, upload it again to resume`
+            : "Document processing cancelled",
+          "info"
+        );
+        event.target.value = '';
+        return;
+      }
+      const documentsWithEmbeddings = embeddingOutcome.documents;
       
       // Store in vector database
-      await invoke("store_documents", { documents: documentsWithEmbeddings });
+      const storeResult = await invoke("store_documents", { documents: documentsWithEmbeddings });
       
-      showNotification(`Document processed: ${documents.length} chunks created`, "success");
+      if (storeResult.warning) {
+        showNotification(storeResult.warning, "warning");
+      } else {
+        showNotification(`Document processed: ${documents.length} chunks created`, "success");
+      }
       
       // Reload documents
       await loadDocuments();
@@ -150,9 +199,28 @@ const DocumentsPage = () => {
       showNotification(`Document processing failed: ${error}`, "error");
     } finally {
       setUploadingDocument(false);
+      setProcessingProgress(null);
     }
   };
 
+  const handleCancelEmbedding = async () => {
+    if (!embeddingOperationId) return;
+    try {
+      await invoke("cancel_embedding_operation", { operationId: embeddingOperationId });
+    } catch (error) {
+      console.error("Failed to cancel embedding:", error);
+    }
+  };
+
+  const getProcessingLabel = () => {
+    const progress = processingProgress;
+    if (!progress || progress.total === 0 || progress.stage === "complete") {
+      return "Processing...";
+    }
+    const unit = progress.stage === "sheets" ? "sheet" : "page";
+    return `Processing ${unit} ${Math.min(progress.processed + 1, progress.total)} of ${progress.total}...`;
+  };
+
   const handleDeleteFile = async (filePath) => {
     if (!filePath) {
       showNotification("Invalid file path", "error");
@@ -267,12 +335,12 @@ const DocumentsPage = () => {
             Upload Documents
           </Typography>
           <Typography variant="body2" color="text.secondary" sx={{ mb: 2 }}>
-            Supported formats: PDF, DOCX, XLSX, XLS (Max 50MB per file)
+            Supported formats: {supportedTypes.map((ext) => ext.toUpperCase()).join(", ")} (Max {maxUploadMb}MB per file)
           </Typography>
           
           <input
             type="file"
-            accept=".pdf,.docx,.xlsx,.xls"
+            accept={supportedTypes.map((ext) => `.${ext}`).join(",")}
             onChange={handleDocumentUpload}
             style={{ display: 'none' }}
             id="document-upload"
@@ -286,9 +354,14 @@ const DocumentsPage = () => {
               startIcon={uploadingDocument ? <CircularProgress size={20} /> : <UploadFileIcon />}
               size="large"
             >
-              {uploadingDocument ? "Processing..." : "Upload Document"}
+              {uploadingDocument ? getProcessingLabel() : "Upload Document"}
             </Button>
           </label>
+          {embeddingOperationId && (
+            <Button onClick={handleCancelEmbedding} size="large" sx={{ ml: 1 }}>
+              Cancel
+            </Button>
+          )}
         </CardContent>
       </Card>
 
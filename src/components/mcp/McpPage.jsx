@@ -369,6 +369,7 @@ const McpPage = () => {
                       label={getStatusText(server.status)}
                       color={getStatusColor(server.status)}
                       size="small"
+                      title={server.last_error || undefined}
                     />
                   </TableCell>
                   <TableCell>
@@ -29,6 +29,7 @@ import {
 } from "@mui/icons-material";
 import { useModels, useUI } from "../../store";
 import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "../../utils/errors";
 
 const ModelCard = ({ modelId }) => {
   const [model, setModel] = useState(null);
@@ -57,7 +58,7 @@ const ModelCard = ({ modelId }) => {
         setError(null);
       } catch (err) {
         console.error("ModelList: Failed to fetch model info:", err);
-        setError(err.toString());
+        setError(getErrorMessage(err));
       } finally {
         setLoading(false);
       }
@@ -86,9 +87,7 @@ const ModelCard = ({ modelId }) => {
 
       // Extract model name and size from result
       const modelName = model.id.split("/").pop() || model.id;
-      const sizeMatch = result.match(/\((\d+(?:\.\d+)?)\s*MB\)/);
-      const sizeInMB = sizeMatch ? parseFloat(sizeMatch[1]) : 0;
-      const sizeInGB = (sizeInMB / 1024).toFixed(2);
+      const sizeInGB = (result.total_bytes / (1024 * 1024 * 1024)).toFixed(2);
 
       showNotification(
         `Downloaded: ${modelName}\nSize: ${sizeInGB} GB`,
@@ -96,7 +95,7 @@ const ModelCard = ({ modelId }) => {
       );
     } catch (error) {
       console.error("ModelList: Download failed:", error);
-      showNotification(`Download failed: ${error}`, "error");
+      showNotification(`Download failed: ${getErrorMessage(error)}`, "error");
     } finally {
       setModelDownloading(model.id, false);
     }
@@ -350,6 +349,15 @@ const ModelCard = ({ modelId }) => {
             <Typography variant="body2" color="text.secondary">
               {downloadProgress.progress}% complete
             </Typography>
+            {downloadProgress.activeFiles?.length > 1 &&
+              downloadProgress.activeFiles.map((file) => (
+                <Box key={file.path} sx={{ mt: 1 }}>
+                  <Typography variant="caption" color="text.secondary">
+                    {file.path}
+                  </Typography>
+                  <LinearProgress variant="determinate" value={file.progress} />
+                </Box>
+              ))}
           </Box>
         )}
       </CardContent>
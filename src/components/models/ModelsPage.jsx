@@ -34,12 +34,14 @@ import ModelDetails from "./ModelDetails";
 import { useModels, useUI } from "../../store";
 import { invoke } from "@tauri-apps/api/core";
 
-const DownloadedModelCard = ({ modelId, loadedModelId }) => {
+const DownloadedModelCard = ({ modelId, loadedModelStatus }) => {
   const { removeDownloadedModel } = useModels();
   const { showNotification } = useUI();
   const [deleteDialogOpen, setDeleteDialogOpen] = useState(false);
 
-  const isLoaded = loadedModelId === modelId;
+  const isCurrentModel = loadedModelStatus?.model_id === modelId;
+  const isLoaded = isCurrentModel && loadedModelStatus.state === "loaded";
+  const isLoading = isCurrentModel && loadedModelStatus.state === "loading";
   const isBgeReranker = modelId.includes("bge-reranker-base-int8-ov");
   const isBgeBase = modelId.includes("bge-base-en-v1.5-int8-ov");
   const isSystemModel = isBgeReranker || isBgeBase;
@@ -110,11 +112,15 @@ const DownloadedModelCard = ({ modelId, loadedModelId }) => {
           </Box>
 
           <Box sx={{ display: "flex", gap: 1 }}>
-            <Tooltip title={isLoaded ? "Model is loaded" : "Model not loaded"}>
+            <Tooltip
+              title={
+                isLoaded ? "Model is loaded" : isLoading ? "Model is loading" : "Model not loaded"
+              }
+            >
               <IconButton
                 size="small"
                 sx={{
-                  color: isLoaded ? "success.main" : "text.disabled",
+                  color: isLoaded ? "success.main" : isLoading ? "warning.main" : "text.disabled",
                   cursor: "default",
                 }}
                 disabled
@@ -183,7 +189,7 @@ const DownloadedModelCard = ({ modelId, loadedModelId }) => {
 
 const ModelsPage = () => {
   const [activeTab, setActiveTab] = useState(0);
-  const [loadedModelId, setLoadedModelId] = useState(null);
+  const [loadedModelStatus, setLoadedModelStatus] = useState(null);
   const { downloadedModels } = useModels();
   const downloadedModelsList = Array.from(downloadedModels);
 
@@ -196,11 +202,11 @@ const ModelsPage = () => {
 
   const checkLoadedModel = async () => {
     try {
-      const result = await invoke("get_loaded_model");
-      setLoadedModelId(result);
+      const result = await invoke("get_loaded_model_status");
+      setLoadedModelStatus(result);
     } catch (error) {
       console.error("ModelsPage: Failed to get loaded model:", error);
-      setLoadedModelId(null);
+      setLoadedModelStatus(null);
     }
   };
 
@@ -288,7 +294,7 @@ const ModelsPage = () => {
                   <Grid size={12} key={modelId}>
                     <DownloadedModelCard
                       modelId={modelId}
-                      loadedModelId={loadedModelId}
+                      loadedModelStatus={loadedModelStatus}
                     />
                   </Grid>
                 ))}
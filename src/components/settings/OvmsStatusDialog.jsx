@@ -16,6 +16,7 @@ import {
   Settings as SettingsIcon,
 } from "@mui/icons-material";
 import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "../../utils/errors";
 
 const OvmsStatusDialog = ({ open, onClose }) => {
   const [ovmsStatus, setOvmsStatus] = useState(null);
@@ -43,7 +44,7 @@ const OvmsStatusDialog = ({ open, onClose }) => {
       setOvmsStatus(ovmsStatusResponse);
     } catch (error) {
       console.error("OvmsStatusDialog: Failed to check OVMS status:", error);
-      setStatusError(error.toString());
+      setStatusError(getErrorMessage(error));
     } finally {
       setCheckingStatus(false);
     }
@@ -65,7 +66,7 @@ const OvmsStatusDialog = ({ open, onClose }) => {
       );
     } catch (error) {
       console.error("OvmsStatusDialog: Failed to get model metadata:", error);
-      alert(`Failed to get details for ${modelName}:\n${error.toString()}`);
+      alert(`Failed to get details for ${modelName}:\n${getErrorMessage(error)}`);
     }
   };
 
@@ -171,6 +172,19 @@ const OvmsStatusDialog = ({ open, onClose }) => {
               </Box>
             </Alert>
           )}
+
+          {ovmsStatus?.warnings?.length > 0 && (
+            <Alert severity="warning" sx={{ mt: 2 }}>
+              <Typography variant="body2" gutterBottom>
+                <strong>Some model statuses could not be read:</strong>
+              </Typography>
+              {ovmsStatus.warnings.map((warning, idx) => (
+                <Typography key={idx} variant="body2">
+                  {warning}
+                </Typography>
+              ))}
+            </Alert>
+          )}
         </Box>
       </DialogContent>
 
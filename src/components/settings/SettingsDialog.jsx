@@ -64,6 +64,10 @@ const SettingsDialog = () => {
     updateSettings({ includeConversationHistory: event.target.checked });
   };
 
+  const handleSystemMessageChange = (event) => {
+    updateSettings({ useSystemMessage: event.target.checked });
+  };
+
   const handleRAGToggleChange = (event) => {
     updateSettings({ useRAG: event.target.checked });
   };
@@ -272,6 +276,27 @@ const SettingsDialog = () => {
                   }
                   sx={{ alignItems: "flex-start", mb: 2 }}
                 />
+
+                <FormControlLabel
+                  control={
+                    <Switch
+                      checked={settings.useSystemMessage ?? true}
+                      onChange={handleSystemMessageChange}
+                      color="primary"
+                    />
+                  }
+                  label={
+                    <Box>
+                      <Typography variant="body1" fontWeight={500}>
+                        Send System Message
+                      </Typography>
+                      <Typography variant="body2" color="text.secondary">
+                        Turn off for base models whose templates don't support a system role (disables tools and RAG)
+                      </Typography>
+                    </Box>
+                  }
+                  sx={{ alignItems: "flex-start", mb: 2 }}
+                />
                 
                 <FormControlLabel
                   control={
@@ -32,13 +32,14 @@ export const useDownloadedModels = () => {
   // Listen for download progress events
   useEffect(() => {
     const unlisten = listen('download-progress', (event) => {
-      const { modelId, progress, currentFile, fileIndex, totalFiles } = event.payload;
+      const { modelId, progress, currentFile, fileIndex, totalFiles, activeFiles } = event.payload;
       
       setDownloadProgress(modelId, {
         progress,
         currentFile,
         fileIndex,
         totalFiles,
+        activeFiles: activeFiles || [],
       });
     });
 
@@ -7,6 +7,7 @@ const defaultSettings = {
   maxTokens: null,
   maxCompletionTokens: null,
   useRAG: false,
+  useSystemMessage: true,
 };
 
 export const createSettingsSlice = (set, get) => ({
@@ -0,0 +1,7 @@
+// Commands migrated to SparrowError reject with { code, message }, older ones with a plain string
+export const getErrorMessage = (error) => {
+  if (error && typeof error === "object" && "message" in error) {
+    return error.message;
+  }
+  return String(error);
+};